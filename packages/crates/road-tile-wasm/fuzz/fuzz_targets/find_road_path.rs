@@ -0,0 +1,40 @@
+//! Feed arbitrary grid dimensions and endpoints into the path search and
+//! tile every path it finds. The `fuzz-checks` invariants inside the crate
+//! panic on an inconsistent search or tiling; the asserts here check the
+//! result itself.
+#![no_main]
+
+use libfuzzer_sys::arbitrary::{self, Arbitrary};
+use libfuzzer_sys::fuzz_target;
+use road_tile_wasm::fuzzing;
+use road_tile_wasm::{GridSize, Point};
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    rows: i8,
+    cols: i8,
+    start: (i8, i8),
+    end: (i8, i8),
+    max_iterations: u16,
+}
+
+fuzz_target!(|input: Input| {
+    // i8 covers zero, negative and off-grid values while keeping grids
+    // small; a nonzero budget skips the area-based auto budget, which
+    // would make single inputs slow
+    let grid_size = GridSize {
+        rows: input.rows.into(),
+        cols: input.cols.into(),
+    };
+    let start = Point::new(input.start.0.into(), input.start.1.into());
+    let end = Point::new(input.end.0.into(), input.end.1.into());
+    let max_iterations = u32::from(input.max_iterations).max(1);
+
+    let result = fuzzing::find_road_path(start, end, grid_size, max_iterations);
+    if result.found {
+        assert_eq!(result.path.first(), Some(&start));
+        assert_eq!(result.path.last(), Some(&end));
+        assert_eq!(result.path.len(), (grid_size.rows * grid_size.cols) as usize);
+        assert!(fuzzing::path_to_road_grid(&result.path, grid_size).valid);
+    }
+});
@@ -5,6 +5,12 @@
 //! with proper port connections.
 
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
 use wasm_bindgen::prelude::*;
 
 // ============================================================================
@@ -42,13 +48,59 @@ impl Point {
     }
 }
 
+/// Largest grid accepted, in cells. The search keeps several tables with an
+/// entry per cell plus a stack as deep as the path, so boards beyond this
+/// would only end in an out-of-memory abort.
+const MAX_GRID_CELLS: i64 = 1 << 20;
+
 /// Grid size specification
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct GridSize {
     pub rows: i32,
     pub cols: i32,
 }
 
+impl GridSize {
+    /// Whether `p` lies inside the grid
+    pub fn contains(&self, p: Point) -> bool {
+        p.row >= 0 && p.row < self.rows && p.col >= 0 && p.col < self.cols
+    }
+
+    /// Why this size can't describe a grid: a dimension that is not positive,
+    /// or more than `MAX_GRID_CELLS` cells
+    pub fn dimension_error(&self) -> Option<String> {
+        if self.rows <= 0 || self.cols <= 0 {
+            return Some(format!(
+                "grid dimensions must be positive, got {} rows by {} cols",
+                self.rows, self.cols
+            ));
+        }
+        let area = i64::from(self.rows) * i64::from(self.cols);
+        (area > MAX_GRID_CELLS).then(|| {
+            format!(
+                "a {}x{} grid has {} cells, more than the {} supported",
+                self.rows, self.cols, area, MAX_GRID_CELLS
+            )
+        })
+    }
+
+    /// Number of cells, None when `dimension_error` rejects the size
+    pub fn cell_count(&self) -> Option<usize> {
+        self.dimension_error()
+            .is_none()
+            .then(|| self.rows as usize * self.cols as usize)
+    }
+
+    /// A rows x cols table filled with `value`; sizes `dimension_error`
+    /// rejects give an empty table instead of a huge or wrapped allocation
+    fn table<T: Clone>(&self, value: T) -> Vec<Vec<T>> {
+        if self.dimension_error().is_some() {
+            return vec![];
+        }
+        vec![vec![value; self.cols as usize]; self.rows as usize]
+    }
+}
+
 /// Direction of movement/connection
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Direction {
@@ -56,8 +108,25 @@ pub enum Direction {
     Down,
     Left,
     Right,
+    // Diagonal moves, only used in diagonal mode (`SearchOptions::diagonals`)
+    UpRight,
+    DownRight,
+    DownLeft,
+    UpLeft,
 }
 
+/// Orthogonal directions followed by the diagonal ones
+const ALL_DIRECTIONS: [Direction; 8] = [
+    Direction::Up,
+    Direction::Right,
+    Direction::Down,
+    Direction::Left,
+    Direction::UpRight,
+    Direction::DownRight,
+    Direction::DownLeft,
+    Direction::UpLeft,
+];
+
 impl Direction {
     pub fn opposite(&self) -> Direction {
         match self {
@@ -65,6 +134,10 @@ impl Direction {
             Direction::Down => Direction::Up,
             Direction::Left => Direction::Right,
             Direction::Right => Direction::Left,
+            Direction::UpRight => Direction::DownLeft,
+            Direction::DownRight => Direction::UpLeft,
+            Direction::DownLeft => Direction::UpRight,
+            Direction::UpLeft => Direction::DownRight,
         }
     }
 
@@ -74,6 +147,10 @@ impl Direction {
             Direction::Down => (1, 0),
             Direction::Left => (0, -1),
             Direction::Right => (0, 1),
+            Direction::UpRight => (-1, 1),
+            Direction::DownRight => (1, 1),
+            Direction::DownLeft => (1, -1),
+            Direction::UpLeft => (-1, -1),
         }
     }
 
@@ -83,9 +160,19 @@ impl Direction {
             Direction::Down => "down",
             Direction::Left => "left",
             Direction::Right => "right",
+            Direction::UpRight => "up-right",
+            Direction::DownRight => "down-right",
+            Direction::DownLeft => "down-left",
+            Direction::UpLeft => "up-left",
         }
     }
 
+    /// Parse a direction name as produced by `to_string` (see `FromStr`)
+    pub fn parse(name: &str) -> Option<Direction> {
+        name.parse().ok()
+    }
+
+    /// The four orthogonal directions
     pub fn all() -> [Direction; 4] {
         [
             Direction::Up,
@@ -94,15 +181,100 @@ impl Direction {
             Direction::Left,
         ]
     }
+
+    /// All eight directions, orthogonal ones first
+    pub fn with_diagonals() -> [Direction; 8] {
+        ALL_DIRECTIONS
+    }
+
+    pub fn is_diagonal(&self) -> bool {
+        matches!(
+            self,
+            Direction::UpRight | Direction::DownRight | Direction::DownLeft | Direction::UpLeft
+        )
+    }
+
+    /// Clockwise angle from up, in degrees
+    pub fn angle(&self) -> u16 {
+        match self {
+            Direction::Up => 0,
+            Direction::UpRight => 45,
+            Direction::Right => 90,
+            Direction::DownRight => 135,
+            Direction::Down => 180,
+            Direction::DownLeft => 225,
+            Direction::Left => 270,
+            Direction::UpLeft => 315,
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = ParseNameError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "up" => Ok(Direction::Up),
+            "down" => Ok(Direction::Down),
+            "left" => Ok(Direction::Left),
+            "right" => Ok(Direction::Right),
+            "up-right" => Ok(Direction::UpRight),
+            "down-right" => Ok(Direction::DownRight),
+            "down-left" => Ok(Direction::DownLeft),
+            "up-left" => Ok(Direction::UpLeft),
+            _ => Err(ParseNameError::new("direction", name, ALL_DIRECTIONS.map(|d| d.to_string()))),
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(Direction::to_string(self))
+    }
+}
+
+/// A string that names no direction or port set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseNameError {
+    /// What was being parsed ("direction", "port set")
+    pub kind: &'static str,
+    pub name: String,
+    /// The accepted names
+    pub expected: Vec<&'static str>,
+}
+
+impl ParseNameError {
+    fn new(
+        kind: &'static str,
+        name: &str,
+        expected: impl IntoIterator<Item = &'static str>,
+    ) -> Self {
+        Self {
+            kind,
+            name: name.to_string(),
+            expected: expected.into_iter().collect(),
+        }
+    }
 }
 
-/// Port set for 2-lane roads
+impl fmt::Display for ParseNameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let expected = self.expected.join(", ");
+        write!(f, "unknown {} {:?}, expected one of {}", self.kind, self.name, expected)
+    }
+}
+
+impl std::error::Error for ParseNameError {}
+
+/// Port set (lane) of a connection
 /// "12" uses ports 1,2 (positions 10,20)
 /// "23" uses ports 2,3 (positions 20,30)
+/// "34" uses ports 3,4 (positions 30,40), the third lane of 3-lane roads
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum PortSet {
     P12, // Ports 1,2
     P23, // Ports 2,3
+    P34, // Ports 3,4
 }
 
 impl PortSet {
@@ -110,555 +282,10359 @@ impl PortSet {
         match self {
             PortSet::P12 => "12",
             PortSet::P23 => "23",
+            PortSet::P34 => "34",
+        }
+    }
+
+    /// Parse a port set name as produced by `to_string` (see `FromStr`)
+    pub fn parse(s: &str) -> Option<PortSet> {
+        s.parse().ok()
+    }
+
+    pub fn all() -> [PortSet; 3] {
+        [PortSet::P12, PortSet::P23, PortSet::P34]
+    }
+
+    /// Numeric positions of the two ports along the edge
+    pub fn positions(&self) -> (u8, u8) {
+        match self {
+            PortSet::P12 => (10, 20),
+            PortSet::P23 => (20, 30),
+            PortSet::P34 => (30, 40),
+        }
+    }
+
+    /// The same lane pair counted from the other end of a 3-port edge.
+    /// P34 has no mirror there and is returned unchanged; 3-lane tiles don't
+    /// use this (see `tile_orientation`).
+    pub fn flip(&self) -> PortSet {
+        match self {
+            PortSet::P12 => PortSet::P23,
+            PortSet::P23 => PortSet::P12,
+            PortSet::P34 => PortSet::P34,
         }
     }
 }
 
+impl FromStr for PortSet {
+    type Err = ParseNameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "12" => Ok(PortSet::P12),
+            "23" => Ok(PortSet::P23),
+            "34" => Ok(PortSet::P34),
+            _ => Err(ParseNameError::new("port set", s, PortSet::all().map(|p| p.to_string()))),
+        }
+    }
+}
+
+impl fmt::Display for PortSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(PortSet::to_string(self))
+    }
+}
+
 /// Connection specification for a tile
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Connection {
     pub direction: String,
     pub ports: String,
 }
 
+impl Connection {
+    pub fn new(dir: Direction, ports: PortSet) -> Self {
+        Self {
+            direction: dir.to_string().to_string(),
+            ports: ports.to_string().to_string(),
+        }
+    }
+
+    /// The direction and port set this connection names
+    pub fn parse(&self) -> Result<(Direction, PortSet), ParseNameError> {
+        Ok((self.direction.parse()?, self.ports.parse()?))
+    }
+}
+
 /// Result of path finding
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PathResult {
     pub found: bool,
     pub path: Vec<Point>,
     pub iterations: u32,
+    /// Times a cell was taken back off the path (rejected on arrival or
+    /// its branch exhausted); the rest of `iterations` was forward descent
+    pub backtracks: u32,
+    /// True when the search was aborted through its cancellation hook
+    pub cancelled: bool,
+    /// True when the search ran past its wall-clock budget
+    pub timed_out: bool,
+    /// Nodes expanded at each depth (index 0 is the start cell), when the
+    /// search was instrumented
+    pub depth_counts: Option<Vec<u32>>,
+    /// Why the search was skipped: invalid grid dimensions or endpoints, or
+    /// the parity pre-check ruling the pair out
+    pub reason: Option<String>,
+    /// Longest path prefix the search reached, when it ran but found nothing
+    pub best_partial: Option<Vec<Point>>,
+    /// Visited flags (`[row][col]`) at the deepest point of a failed search:
+    /// obstacles and missing cells plus `best_partial`. Only filled in debug
+    /// runs.
+    pub visited_grid: Option<Vec<Vec<bool>>>,
+    /// Structured form of `reason` when the input itself was invalid
+    pub input_error: Option<InputError>,
 }
 
-/// Cell data for rendering
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CellData {
-    pub tile_id: String,
-    pub connections: Vec<Connection>,
-    pub path_index: usize,
-}
-
-/// Road grid result
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RoadGridResult {
-    pub grid: Vec<Vec<Option<CellData>>>,
-    pub valid: bool,
-}
-
-// ============================================================================
-// Tile Definitions
-// ============================================================================
+impl PathResult {
+    /// A result for a search that was skipped, explaining why in `reason`
+    fn rejected(reason: String) -> Self {
+        Self {
+            found: false,
+            path: vec![],
+            iterations: 0,
+            backtracks: 0,
+            cancelled: false,
+            timed_out: false,
+            depth_counts: None,
+            reason: Some(reason),
+            best_partial: None,
+            visited_grid: None,
+            input_error: None,
+        }
+    }
 
-/// Tile variant type
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum TileVariant {
-    Curve,
-    Sharp,
-    Straight,
+    /// A result for a search whose input was invalid
+    fn invalid(error: InputError) -> Self {
+        Self {
+            input_error: Some(error.clone()),
+            ..Self::rejected(error.message())
+        }
+    }
 }
 
-/// Tile definition
-#[derive(Debug, Clone)]
-pub struct TileDefinition {
-    pub id: &'static str,
-    pub variant: TileVariant,
-    pub mask: u8,
-    pub conn1: (Direction, PortSet),
-    pub conn2: (Direction, PortSet),
+/// Search input rejected before any search state is built
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputError {
+    /// "invalid_dimensions", "out_of_bounds" or "same_endpoints"
+    pub kind: String,
+    /// The offending endpoint ("start" or "end") for "out_of_bounds"
+    pub point: Option<String>,
+    /// The offending cell, except for "invalid_dimensions"
+    pub cell: Option<Point>,
+    pub grid_size: GridSize,
 }
 
-impl TileDefinition {
-    pub fn get_connection(&self, dir: Direction) -> Option<PortSet> {
-        if self.conn1.0 == dir {
-            Some(self.conn1.1)
-        } else if self.conn2.0 == dir {
-            Some(self.conn2.1)
-        } else {
-            None
+impl InputError {
+    fn out_of_bounds(point: &str, cell: Point, grid_size: GridSize) -> Self {
+        Self {
+            kind: "out_of_bounds".to_string(),
+            point: Some(point.to_string()),
+            cell: Some(cell),
+            grid_size,
         }
     }
 
-    pub fn has_direction(&self, dir: Direction) -> bool {
-        self.conn1.0 == dir || self.conn2.0 == dir
+    /// Human-readable description, as used for `PathResult::reason`
+    fn message(&self) -> String {
+        let GridSize { rows, cols } = self.grid_size;
+        match (self.kind.as_str(), self.cell) {
+            ("out_of_bounds", Some(p)) => format!(
+                "{} cell ({}, {}) is outside the {}x{} grid",
+                self.point.as_deref().unwrap_or("a"),
+                p.row,
+                p.col,
+                rows,
+                cols
+            ),
+            ("same_endpoints", Some(p)) => format!(
+                "start and end are both ({}, {}), but a path covering the {}x{} grid needs two \
+                 distinct endpoints",
+                p.row, p.col, rows, cols
+            ),
+            _ => self.grid_size.dimension_error().unwrap_or_default(),
+        }
     }
 }
 
-/// Get all tile definitions
-fn get_all_tiles() -> Vec<TileDefinition> {
-    use Direction::*;
-    use PortSet::*;
-    use TileVariant::*;
-
-    vec![
-        // Curve tiles (16)
-        TileDefinition { id: "curve-05", variant: Curve, mask: 0x05, conn1: (Up, P12), conn2: (Right, P12) },
-        TileDefinition { id: "curve-06", variant: Curve, mask: 0x06, conn1: (Up, P23), conn2: (Right, P12) },
-        TileDefinition { id: "curve-09", variant: Curve, mask: 0x09, conn1: (Up, P12), conn2: (Right, P23) },
-        TileDefinition { id: "curve-0A", variant: Curve, mask: 0x0A, conn1: (Up, P23), conn2: (Right, P23) },
-        TileDefinition { id: "curve-14", variant: Curve, mask: 0x14, conn1: (Right, P12), conn2: (Down, P12) },
-        TileDefinition { id: "curve-18", variant: Curve, mask: 0x18, conn1: (Right, P23), conn2: (Down, P12) },
-        TileDefinition { id: "curve-24", variant: Curve, mask: 0x24, conn1: (Right, P12), conn2: (Down, P23) },
-        TileDefinition { id: "curve-28", variant: Curve, mask: 0x28, conn1: (Right, P23), conn2: (Down, P23) },
-        TileDefinition { id: "curve-41", variant: Curve, mask: 0x41, conn1: (Up, P12), conn2: (Left, P12) },
-        TileDefinition { id: "curve-42", variant: Curve, mask: 0x42, conn1: (Up, P23), conn2: (Left, P12) },
-        TileDefinition { id: "curve-50", variant: Curve, mask: 0x50, conn1: (Down, P12), conn2: (Left, P12) },
-        TileDefinition { id: "curve-60", variant: Curve, mask: 0x60, conn1: (Down, P23), conn2: (Left, P12) },
-        TileDefinition { id: "curve-81", variant: Curve, mask: 0x81, conn1: (Up, P12), conn2: (Left, P23) },
-        TileDefinition { id: "curve-82", variant: Curve, mask: 0x82, conn1: (Up, P23), conn2: (Left, P23) },
-        TileDefinition { id: "curve-90", variant: Curve, mask: 0x90, conn1: (Down, P12), conn2: (Left, P23) },
-        TileDefinition { id: "curve-A0", variant: Curve, mask: 0xA0, conn1: (Down, P23), conn2: (Left, P23) },
-
-        // Sharp tiles (16)
-        TileDefinition { id: "sharp-05", variant: Sharp, mask: 0x05, conn1: (Up, P12), conn2: (Right, P12) },
-        TileDefinition { id: "sharp-06", variant: Sharp, mask: 0x06, conn1: (Up, P23), conn2: (Right, P12) },
-        TileDefinition { id: "sharp-09", variant: Sharp, mask: 0x09, conn1: (Up, P12), conn2: (Right, P23) },
-        TileDefinition { id: "sharp-0A", variant: Sharp, mask: 0x0A, conn1: (Up, P23), conn2: (Right, P23) },
-        TileDefinition { id: "sharp-14", variant: Sharp, mask: 0x14, conn1: (Right, P12), conn2: (Down, P12) },
-        TileDefinition { id: "sharp-18", variant: Sharp, mask: 0x18, conn1: (Right, P23), conn2: (Down, P12) },
-        TileDefinition { id: "sharp-24", variant: Sharp, mask: 0x24, conn1: (Right, P12), conn2: (Down, P23) },
-        TileDefinition { id: "sharp-28", variant: Sharp, mask: 0x28, conn1: (Right, P23), conn2: (Down, P23) },
-        TileDefinition { id: "sharp-41", variant: Sharp, mask: 0x41, conn1: (Up, P12), conn2: (Left, P12) },
-        TileDefinition { id: "sharp-42", variant: Sharp, mask: 0x42, conn1: (Up, P23), conn2: (Left, P12) },
-        TileDefinition { id: "sharp-50", variant: Sharp, mask: 0x50, conn1: (Down, P12), conn2: (Left, P12) },
-        TileDefinition { id: "sharp-60", variant: Sharp, mask: 0x60, conn1: (Down, P23), conn2: (Left, P12) },
-        TileDefinition { id: "sharp-81", variant: Sharp, mask: 0x81, conn1: (Up, P12), conn2: (Left, P23) },
-        TileDefinition { id: "sharp-82", variant: Sharp, mask: 0x82, conn1: (Up, P23), conn2: (Left, P23) },
-        TileDefinition { id: "sharp-90", variant: Sharp, mask: 0x90, conn1: (Down, P12), conn2: (Left, P23) },
-        TileDefinition { id: "sharp-A0", variant: Sharp, mask: 0xA0, conn1: (Down, P23), conn2: (Left, P23) },
-
-        // Straight tiles - Vertical (4 variants)
-        TileDefinition { id: "straight-v-11", variant: Straight, mask: 0x11, conn1: (Up, P12), conn2: (Down, P12) },
-        TileDefinition { id: "straight-v-12", variant: Straight, mask: 0x12, conn1: (Up, P12), conn2: (Down, P23) },
-        TileDefinition { id: "straight-v-21", variant: Straight, mask: 0x21, conn1: (Up, P23), conn2: (Down, P12) },
-        TileDefinition { id: "straight-v-22", variant: Straight, mask: 0x22, conn1: (Up, P23), conn2: (Down, P23) },
-        // Straight tiles - Horizontal (4 variants)
-        TileDefinition { id: "straight-h-44", variant: Straight, mask: 0x44, conn1: (Left, P12), conn2: (Right, P12) },
-        TileDefinition { id: "straight-h-48", variant: Straight, mask: 0x48, conn1: (Left, P12), conn2: (Right, P23) },
-        TileDefinition { id: "straight-h-84", variant: Straight, mask: 0x84, conn1: (Left, P23), conn2: (Right, P12) },
-        TileDefinition { id: "straight-h-88", variant: Straight, mask: 0x88, conn1: (Left, P23), conn2: (Right, P23) },
-    ]
+/// Result of multi-solution path finding
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiPathResult {
+    pub found: bool,
+    pub paths: Vec<Vec<Point>>,
+    /// True only when the search proved exactly one path exists
+    pub unique: bool,
+    pub iterations: u32,
 }
 
-/// Find tiles that connect in given directions with given ports
-#[allow(dead_code)]
-fn find_matching_tiles(
-    from_dir: Direction,
-    from_ports: PortSet,
-    to_dir: Direction,
-    to_ports: PortSet,
-    tiles: &[TileDefinition],
-) -> Vec<&TileDefinition> {
-    tiles
-        .iter()
-        .filter(|tile| {
-            tile.get_connection(from_dir.opposite()) == Some(from_ports)
-                && tile.get_connection(to_dir) == Some(to_ports)
-        })
-        .collect()
+/// Result of counting Hamiltonian paths
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathCount {
+    pub count: u32,
+    pub iterations: u32,
+    /// True when max_iterations cut the enumeration short (count is a lower bound)
+    pub capped: bool,
 }
 
-// ============================================================================
-// Path Finding Algorithm
-// ============================================================================
+/// Result of searching for the path with the fewest turns
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmoothestPathResult {
+    pub found: bool,
+    pub path: Vec<Point>,
+    /// Direction changes along `path` (0 when nothing was found)
+    pub turns: usize,
+    pub iterations: u32,
+    /// True when the search finished, so no path has fewer turns; false when
+    /// max_iterations cut it short (`path` is then the best seen so far)
+    pub optimal: bool,
+}
 
-/// State for path finding
-struct PathState {
-    grid: Vec<Vec<bool>>, // visited cells
-    path: Vec<Point>,
-    grid_size: GridSize,
-    iterations: u32,
-    max_iterations: u32,
+/// Cell data for rendering
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CellData {
+    pub tile_id: String,
+    pub connections: Vec<Connection>,
+    pub path_index: usize,
+    /// Clockwise rotation (degrees) of the base sprite: the tile's
+    /// `rotation`, or for start/goal markers (whose base opens upward) the
+    /// angle of their connection
+    #[serde(default)]
+    pub rotation: u16,
+    /// Direction of travel into the cell (None for the start cell)
+    #[serde(default)]
+    pub entry_dir: Option<String>,
+    /// Direction of travel out of the cell (None for the goal cell)
+    #[serde(default)]
+    pub exit_dir: Option<String>,
+    /// Index of the road the cell belongs to on a multi-road grid (0 for a
+    /// single road)
+    #[serde(default)]
+    pub road_id: usize,
+    /// Other tiles with exactly the same connections (e.g. the sharp
+    /// counterpart of a curve), any of which can replace `tile_id` without
+    /// touching the neighbors. Only filled when tiling with alternatives.
+    #[serde(default)]
+    pub alternatives: Vec<String>,
 }
 
-impl PathState {
-    fn new(grid_size: GridSize, max_iterations: u32) -> Self {
-        let grid = vec![vec![false; grid_size.cols as usize]; grid_size.rows as usize];
+impl CellData {
+    /// Cell showing `tile_id`, with the rotation derived from it.
+    ///
+    /// `connections` list the side the road enters from first, as the path
+    /// tilers build them; the travel directions are read off that order
+    /// (a start marker only exits, a goal marker only enters). Junctions and
+    /// unknown directions leave them unset.
+    pub fn new(tile_id: String, connections: Vec<Connection>, path_index: usize) -> Self {
+        let rotation = find_tile(&tile_id).map(|tile| tile.rotation).unwrap_or_else(|| {
+            connections
+                .first()
+                .and_then(|conn| Direction::parse(&conn.direction))
+                .map_or(0, |dir| dir.angle())
+        });
+        let side = |i: usize| connections.get(i).and_then(|conn| Direction::parse(&conn.direction));
+        let (entry, exit) = match (tile_id.as_str(), connections.len()) {
+            ("start", 1) => (None, side(0)),
+            ("goal", 1) => (side(0).map(|dir| dir.opposite()), None),
+            (_, 2) => (side(0).map(|dir| dir.opposite()), side(1)),
+            _ => (None, None),
+        };
+        let name = |dir: Direction| dir.to_string().to_string();
         Self {
-            grid,
-            path: Vec::new(),
-            grid_size,
-            iterations: 0,
-            max_iterations,
+            tile_id,
+            connections,
+            path_index,
+            rotation,
+            entry_dir: entry.map(name),
+            exit_dir: exit.map(name),
+            road_id: 0,
+            alternatives: Vec::new(),
         }
     }
 
-    fn is_valid(&self, p: Point) -> bool {
-        p.row >= 0
-            && p.row < self.grid_size.rows
-            && p.col >= 0
-            && p.col < self.grid_size.cols
+    /// Connections packed into the tile `mask` format, so two cells can be
+    /// compared with a single integer check. None for unknown names.
+    pub fn connection_mask(&self) -> Option<u16> {
+        encode_connections(&self.connections)
     }
 
-    fn is_visited(&self, p: Point) -> bool {
-        if !self.is_valid(p) {
-            return true;
+    /// Whether both cells show the same tile with the same connections.
+    /// Connections compare as masks (ignoring order) when both parse, and
+    /// as lists otherwise; path index, rotation and road id don't count.
+    pub fn same_tile(&self, other: &CellData) -> bool {
+        if self.tile_id != other.tile_id {
+            return false;
+        }
+        match (self.connection_mask(), other.connection_mask()) {
+            (Some(a), Some(b)) => a == b,
+            _ => self.connections == other.connections,
         }
-        self.grid[p.row as usize][p.col as usize]
     }
+}
 
-    fn visit(&mut self, p: Point) {
-        self.grid[p.row as usize][p.col as usize] = true;
-        self.path.push(p);
-    }
+/// Version of the RoadGridResult/CellData shape. Bump it whenever CellData
+/// changes and teach `RoadGridResult::migrate` the step from the previous one.
+///
+/// - 0: unversioned payloads (no `rotation`, `entry_dir` or `exit_dir`)
+/// - 1: cells carry `rotation`, `entry_dir` and `exit_dir`
+/// - 2: cells carry `road_id`
+/// - 3: cells carry `alternatives`
+const GRID_SCHEMA_VERSION: u32 = 3;
 
-    fn unvisit(&mut self, p: Point) {
-        self.grid[p.row as usize][p.col as usize] = false;
-        self.path.pop();
-    }
+/// Road grid result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoadGridResult {
+    pub grid: Vec<Vec<Option<CellData>>>,
+    pub valid: bool,
+    /// The cell path tiling stopped at, when no tile fit there
+    pub failure: Option<TileFailure>,
+    /// Why tiling stopped, when a constraint (such as a lane lock) rather
+    /// than the tile set ruled the cell out
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// Shape version (`GRID_SCHEMA_VERSION` when produced by this crate;
+    /// missing counts as 0)
+    #[serde(default)]
+    pub schema_version: u32,
+}
 
-    fn all_visited(&self) -> bool {
-        let total = (self.grid_size.rows * self.grid_size.cols) as usize;
-        self.path.len() == total
+impl RoadGridResult {
+    fn new(grid: Vec<Vec<Option<CellData>>>, valid: bool, failure: Option<TileFailure>) -> Self {
+        Self {
+            grid,
+            valid,
+            failure,
+            reason: None,
+            schema_version: GRID_SCHEMA_VERSION,
+        }
     }
 
-    fn get_neighbors(&self, p: Point) -> Vec<(Point, Direction)> {
-        Direction::all()
-            .iter()
-            .filter_map(|&dir| {
-                let (dr, dc) = dir.delta();
-                let next = Point::new(p.row + dr, p.col + dc);
-                if self.is_valid(next) && !self.is_visited(next) {
-                    Some((next, dir))
-                } else {
-                    None
+    /// Upgrade a payload saved as `from_version` to `GRID_SCHEMA_VERSION`.
+    /// None for versions newer than this crate knows.
+    fn migrate(mut self, from_version: u32) -> Option<Self> {
+        if from_version > GRID_SCHEMA_VERSION {
+            return None;
+        }
+        if from_version < 1 {
+            // Fill in the fields derived from the tile and its connections
+            for cell in self.grid.iter_mut().flatten() {
+                if let Some(old) = cell.take() {
+                    *cell = Some(CellData::new(old.tile_id, old.connections, old.path_index));
                 }
-            })
-            .collect()
+            }
+        }
+        if from_version < 2 {
+            // Grids from before multi-road tiling hold a single road
+            for cell in self.grid.iter_mut().flatten().flatten() {
+                cell.road_id = 0;
+            }
+        }
+        if from_version < 3 {
+            // Alternatives are only listed on request, which wasn't possible yet
+            for cell in self.grid.iter_mut().flatten().flatten() {
+                cell.alternatives.clear();
+            }
+        }
+        self.schema_version = GRID_SCHEMA_VERSION;
+        Some(self)
     }
 }
 
-/// Find a Hamiltonian path from start to end using backtracking
-fn find_path_internal(
-    state: &mut PathState,
-    current: Point,
-    end: Point,
-) -> bool {
-    state.iterations += 1;
+/// A cell claimed by two roads of a multi-road grid
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoadConflict {
+    pub row: i32,
+    pub col: i32,
+    /// The two roads, in input order
+    pub road_ids: [usize; 2],
+}
 
-    if state.iterations > state.max_iterations {
-        return false;
-    }
+/// Several independent roads tiled onto one grid
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiRoadResult {
+    pub grid: Vec<Vec<Option<CellData>>>,
+    pub valid: bool,
+    /// First cell two roads share; nothing is tiled then
+    pub conflict: Option<RoadConflict>,
+    /// The road tiling stopped at, with its `failure` as in RoadGridResult
+    pub failed_road: Option<usize>,
+    pub failure: Option<TileFailure>,
+}
 
-    state.visit(current);
+/// A path cell for which no tile satisfies the turn and the propagated lane
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TileFailure {
+    pub path_index: usize,
+    /// Direction of travel into the cell
+    pub entry_direction: String,
+    /// Direction of travel out of the cell
+    pub exit_direction: String,
+    /// Lane the previous tile exited on
+    pub required_entry_port: Option<String>,
+}
 
-    // Check if we reached the end and visited all cells
-    if current == end {
-        if state.all_visited() {
-            return true;
-        }
-        state.unvisit(current);
-        return false;
-    }
+/// Tile `path_to_tiles` picks for one turn, and the ports it uses
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TurnTiling {
+    pub tile_id: String,
+    /// Ports on the side the road enters through
+    pub entry_port: String,
+    /// Ports on the side the road leaves through
+    pub exit_port: String,
+}
 
-    // Check if we visited all cells but not at end
-    if state.all_visited() {
-        state.unvisit(current);
-        return false;
-    }
+/// Road grid after appending one cell, with the lane the new goal was entered on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppendResult {
+    pub grid: Vec<Vec<Option<CellData>>>,
+    pub valid: bool,
+    /// Set when the old goal could not be retiled as a middle tile
+    pub failure: Option<TileFailure>,
+    /// Port set the next appended cell must be entered on ("12", "23" or "34")
+    pub required_entry_port: Option<String>,
+}
 
-    // Get unvisited neighbors
-    let mut neighbors = state.get_neighbors(current);
+/// Road grid after reversing part of the path
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentReversal {
+    /// The path with the segment reversed
+    pub path: Vec<Point>,
+    pub result: RoadGridResult,
+}
 
-    // Heuristic: Sort neighbors by distance to end (closer first when near end)
-    // and by number of unvisited neighbors (fewer first - Warnsdorff's rule)
-    neighbors.sort_by(|(a, _), (b, _)| {
-        let a_neighbors = count_unvisited_neighbors(state, *a);
-        let b_neighbors = count_unvisited_neighbors(state, *b);
+/// A neighbor the search would try next, with its Warnsdorff degree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Candidate {
+    pub row: i32,
+    pub col: i32,
+    pub direction: String,
+    /// Unvisited neighbors of this cell, the primary sort key
+    pub unvisited_neighbors: usize,
+}
 
-        // Prioritize cells with fewer unvisited neighbors (Warnsdorff's rule)
-        a_neighbors.cmp(&b_neighbors)
-    });
+/// A cell reachable from the start, with its BFS distance in steps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReachableCell {
+    pub row: i32,
+    pub col: i32,
+    pub distance: u32,
+}
 
-    for (next, _dir) in neighbors {
-        if find_path_internal(state, next, end) {
-            return true;
+/// Shortest orthogonal route between two cells, ignoring coverage
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShortestPath {
+    pub found: bool,
+    /// From start to end inclusive; empty when not found
+    pub path: Vec<Point>,
+    /// Number of steps (`path.len() - 1`), 0 when not found
+    pub length: usize,
+}
+
+/// A free cell whose blocking would split its part of the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CutCell {
+    pub row: i32,
+    pub col: i32,
+    /// Pieces its part of the board falls into without it (at least 2)
+    pub pieces: usize,
+}
+
+/// Articulation points of the free cells, to explain unsolvable boards
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CutCellReport {
+    /// Connected groups of free cells
+    pub components: usize,
+    /// In row-major order
+    pub cut_cells: Vec<CutCell>,
+    /// True when the board alone rules out a path over every free cell:
+    /// the free cells are disconnected, or a cut cell leaves three or more
+    /// pieces (a path passes through a cell once, joining at most two).
+    /// False does not promise a path exists.
+    pub ruled_out: bool,
+}
+
+/// Free cells a partial path has yet to cover
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemainingCells {
+    /// Cells neither on the path nor blocked, in row-major order
+    pub cells: Vec<Point>,
+    /// Whether every one of them can still be reached from the path's last
+    /// cell through uncovered cells (true when none are left)
+    pub all_reachable: bool,
+}
+
+/// An endpoint pair with a known Hamiltonian path, and what finding it cost
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SolvableEndpoints {
+    pub start: Point,
+    pub end: Point,
+    pub iterations: u32,
+}
+
+/// Rough difficulty of a path search, estimated without searching
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DifficultyEstimate {
+    /// Whether the endpoints are valid and the parity pre-check allows a
+    /// path at all
+    pub feasible: bool,
+    /// Estimated log10 of the iterations a plain search needs (0 if infeasible)
+    pub score: f64,
+    /// Iteration budget worth passing as `max_iterations` (0 if infeasible)
+    pub suggested_max_iterations: u32,
+}
+
+/// Timing of one benchmark search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkEntry {
+    pub rows: i32,
+    pub cols: i32,
+    /// The corner the search ran to from (0, 0)
+    pub end: Point,
+    pub found: bool,
+    pub iterations: u32,
+    pub elapsed_ms: f64,
+}
+
+/// Iteration range of a batch histogram and how many searches fell in it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IterationBucket {
+    pub name: String,
+    pub min: u32,
+    /// Exclusive upper bound; None for the last bucket
+    pub max: Option<u32>,
+    pub count: usize,
+}
+
+/// Aggregate figures over a batch of search results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchStats {
+    pub count: usize,
+    pub found: usize,
+    /// `found / count` (0 for an empty batch)
+    pub found_fraction: f64,
+    pub mean_iterations: f64,
+    pub median_iterations: f64,
+    /// One bucket per decade of iterations, from "<1k" to "1M+"
+    pub histogram: Vec<IterationBucket>,
+}
+
+/// Star rating of an endpoint pair as a puzzle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PuzzleRating {
+    /// Whether a path was found (by the search or the enumeration)
+    pub solvable: bool,
+    /// 1 (easiest) to 5 (hardest); 0 if unsolvable
+    pub stars: u8,
+    /// Weighted sum of the normalized metrics, in [0, 1]
+    pub score: f64,
+    /// Iterations the plain search needed for its first path
+    pub first_solution_iterations: u32,
+    pub solution_count: u32,
+    /// True when the budget cut the count short (`solution_count` is a lower
+    /// bound, so the rating errs towards harder)
+    pub count_capped: bool,
+}
+
+/// A cell the designer locked to a specific tile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TileLock {
+    pub row: i32,
+    pub col: i32,
+    pub tile_id: String,
+}
+
+/// Outcome of a search with locked tiles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockedPathResult {
+    pub result: PathResult,
+    /// The path tiled with the default options and the locked tiles in
+    /// place, when one was found
+    pub grid: Option<RoadGridResult>,
+}
+
+/// A full-cover path that ends next to its start
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NearLoopResult {
+    pub result: PathResult,
+    /// The neighbor of the start the path ends on, when one was found
+    pub end: Option<Point>,
+}
+
+/// A route of 2x2 footprints for wide roads
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThickPathResult {
+    /// Outcome of the search; `path` and `best_partial` hold the top-left
+    /// cell of each footprint
+    pub result: PathResult,
+    /// The same route on the grid of 2x2 blocks, one point per footprint
+    pub block_path: Vec<Point>,
+    /// Dimensions of the block grid (half the cell grid)
+    pub block_grid: GridSize,
+}
+
+/// A connection in a road grid that the neighboring cell does not mirror
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GridMismatch {
+    pub row: i32,
+    pub col: i32,
+    pub direction: String,
+    /// "unknown_direction", "unknown_ports", "out_of_bounds",
+    /// "missing_neighbor", "missing_connection" or "port_mismatch"
+    pub kind: String,
+}
+
+/// Outcome of checking a user-supplied path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathCheck {
+    pub valid: bool,
+    /// Index of the first point that breaks the path
+    pub first_bad_index: Option<usize>,
+    pub reason: Option<String>,
+}
+
+/// Area and checkerboard coloring of a full rectangular grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GridInfo {
+    pub area: usize,
+    pub is_even_area: bool,
+    /// Equal numbers of even and odd cells: a Hamiltonian path then needs
+    /// endpoints of different parity; otherwise both must be on the majority
+    pub parity_balanced: bool,
+    /// Cells with `cell_parity` 0
+    pub even_cells: usize,
+    /// Cells with `cell_parity` 1
+    pub odd_cells: usize,
+}
+
+/// Outcome of parsing a direction or port set name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParsedName {
+    /// The canonical name, when it parsed
+    pub value: Option<String>,
+    /// Why it didn't, naming the accepted values
+    pub error: Option<String>,
+}
+
+impl ParsedName {
+    fn from_result<T: fmt::Display>(result: Result<T, ParseNameError>) -> Self {
+        match result {
+            Ok(value) => Self {
+                value: Some(value.to_string()),
+                error: None,
+            },
+            Err(error) => Self {
+                value: None,
+                error: Some(error.to_string()),
+            },
+        }
+    }
+}
+
+/// One value of each main result type, shaped like the real payloads, for
+/// deriving TypeScript types from Rust instead of maintaining them by hand
+#[derive(Debug, Clone, Serialize)]
+pub struct TypeExamples {
+    pub path_result: PathResult,
+    pub cell_data: CellData,
+    pub road_grid_result: RoadGridResult,
+}
+
+impl TypeExamples {
+    /// Examples built from a small tiled road. Every optional field is
+    /// filled in so its type shows up; the values need not be consistent
+    /// with each other (a found path with a `reason`, say).
+    fn build() -> Self {
+        let grid_size = GridSize { rows: 2, cols: 3 };
+        let path = vec![Point::new(0, 0), Point::new(0, 1), Point::new(1, 1), Point::new(1, 0)];
+        let road = path_to_tiles(&path, grid_size, TileOptions::default());
+        // A middle cell, so both travel directions are set
+        let cell_data = road.grid[0][1].clone().unwrap();
+        let path_result = PathResult {
+            found: true,
+            iterations: 4,
+            depth_counts: Some(vec![1; path.len()]),
+            best_partial: Some(path[..2].to_vec()),
+            visited_grid: Some(vec![vec![true, true, false]; 2]),
+            input_error: Some(InputError::out_of_bounds("end", Point::new(2, 0), grid_size)),
+            path,
+            ..PathResult::rejected("example reason".to_string())
+        };
+        let failure = TileFailure {
+            path_index: 2,
+            entry_direction: Direction::Down.to_string().to_string(),
+            exit_direction: Direction::Left.to_string().to_string(),
+            required_entry_port: Some(PortSet::P23.to_string().to_string()),
+        };
+        let road_grid_result = RoadGridResult::new(road.grid, road.valid, Some(failure));
+        Self {
+            path_result,
+            cell_data,
+            road_grid_result,
+        }
+    }
+}
+
+// ============================================================================
+// Tile Definitions
+// ============================================================================
+
+/// Tile variant type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileVariant {
+    Curve,
+    Sharp,
+    Straight,
+    /// Roads leaving through a cell corner (diagonal mode)
+    Diagonal,
+    /// Three roads meeting (T-junction)
+    Tee,
+    /// Four roads meeting
+    Cross,
+}
+
+impl TileVariant {
+    pub fn to_string(&self) -> &'static str {
+        match self {
+            TileVariant::Curve => "curve",
+            TileVariant::Sharp => "sharp",
+            TileVariant::Straight => "straight",
+            TileVariant::Diagonal => "diagonal",
+            TileVariant::Tee => "tee",
+            TileVariant::Cross => "cross",
+        }
+    }
+
+    /// Parse a variant name as produced by `to_string`
+    pub fn parse(name: &str) -> Option<TileVariant> {
+        match name {
+            "curve" => Some(TileVariant::Curve),
+            "sharp" => Some(TileVariant::Sharp),
+            "straight" => Some(TileVariant::Straight),
+            "diagonal" => Some(TileVariant::Diagonal),
+            "tee" => Some(TileVariant::Tee),
+            "cross" => Some(TileVariant::Cross),
+            _ => None,
+        }
+    }
+}
+
+/// Tile definition
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileDefinition {
+    pub id: String,
+    pub variant: TileVariant,
+    pub mask: u16,
+    /// Two connections for road pieces, three or four for junctions
+    pub connections: Vec<(Direction, PortSet)>,
+    /// Clockwise rotation (degrees) applied to the base sprite
+    pub rotation: u16,
+    /// Base sprite (an Up→Right corner or a vertical straight) this tile rotates
+    pub base_id: String,
+}
+
+impl TileDefinition {
+    pub fn get_connection(&self, dir: Direction) -> Option<PortSet> {
+        self.connections.iter().find(|&&(d, _)| d == dir).map(|&(_, ports)| ports)
+    }
+
+    pub fn has_direction(&self, dir: Direction) -> bool {
+        self.connections.iter().any(|&(d, _)| d == dir)
+    }
+
+    /// Whether the tile branches (a tee or crossing) rather than continuing
+    /// a single road
+    pub fn is_junction(&self) -> bool {
+        self.connections.len() > 2
+    }
+
+    /// Serializable view of this tile for JavaScript
+    pub fn to_info(&self) -> TileInfo {
+        let connection = |(dir, ports): (Direction, PortSet)| Connection::new(dir, ports);
+        TileInfo {
+            id: self.id.to_string(),
+            variant: self.variant.to_string().to_string(),
+            mask: self.mask,
+            connections: self.connections.iter().copied().map(connection).collect(),
+            rotation: self.rotation,
+            base_id: self.base_id.to_string(),
+        }
+    }
+
+    /// Ports on each orthogonal side, null where the tile has no connection
+    pub fn side_ports(&self) -> TileConnections {
+        let side = |dir| self.get_connection(dir).map(|ports| ports.to_string().to_string());
+        TileConnections {
+            up: side(Direction::Up),
+            right: side(Direction::Right),
+            down: side(Direction::Down),
+            left: side(Direction::Left),
+        }
+    }
+}
+
+/// Tile definition as exposed to JavaScript (and accepted by
+/// `set_custom_tiles`, where `rotation` and `base_id` may be left out)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TileInfo {
+    pub id: String,
+    pub variant: String,
+    pub mask: u16,
+    pub connections: Vec<Connection>,
+    #[serde(default)]
+    pub rotation: u16,
+    #[serde(default)]
+    pub base_id: String,
+}
+
+/// A tile's port set ("12", "23" or "34") on each side, or null
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TileConnections {
+    pub up: Option<String>,
+    pub right: Option<String>,
+    pub down: Option<String>,
+    pub left: Option<String>,
+}
+
+/// Mask bit for a connection: U=0x01/0x02, R=0x04/0x08, D=0x10/0x20, L=0x40/0x80
+/// for P12/P23, and U=0x100, R=0x200, D=0x400, L=0x800 for P34. Corners have
+/// one junction whatever the ports: UR=0x1000, DR=0x2000, DL=0x4000, UL=0x8000.
+fn connection_bit(dir: Direction, ports: PortSet) -> u16 {
+    let index = match dir {
+        Direction::Up => 0,
+        Direction::Right => 1,
+        Direction::Down => 2,
+        Direction::Left => 3,
+        Direction::UpRight => return 0x1000,
+        Direction::DownRight => return 0x2000,
+        Direction::DownLeft => return 0x4000,
+        Direction::UpLeft => return 0x8000,
+    };
+    match ports {
+        PortSet::P12 => 1 << (2 * index),
+        PortSet::P23 => 2 << (2 * index),
+        PortSet::P34 => 0x100 << index,
+    }
+}
+
+/// Mask a tile with these connections must declare
+fn derive_mask(connections: &[(Direction, PortSet)]) -> u16 {
+    connections.iter().fold(0, |mask, &(dir, ports)| mask | connection_bit(dir, ports))
+}
+
+/// Decode a tile mask into its connections, in `Direction::with_diagonals()`
+/// order. Corner connections come back as P23.
+fn mask_connections(mask: u16) -> Vec<(Direction, PortSet)> {
+    Direction::with_diagonals()
+        .iter()
+        .flat_map(|&dir| PortSet::all().map(|ports| (dir, ports)))
+        .filter(|&(dir, ports)| !dir.is_diagonal() || ports == PortSet::P23)
+        .filter(|&(dir, ports)| mask & connection_bit(dir, ports) != 0)
+        .collect()
+}
+
+/// Pack string connections into a tile mask; None if a direction or port
+/// set name is unknown
+fn encode_connections(connections: &[Connection]) -> Option<u16> {
+    connections.iter().try_fold(0, |mask, conn| {
+        let (dir, ports) = conn.parse().ok()?;
+        Some(mask | connection_bit(dir, ports))
+    })
+}
+
+/// Unpack a tile mask into string connections, in `Direction::with_diagonals()` order
+fn decode_connections(mask: u16) -> Vec<Connection> {
+    mask_connections(mask)
+        .into_iter()
+        .map(|(dir, ports)| Connection::new(dir, ports))
+        .collect()
+}
+
+/// Rotate a connection a quarter turn clockwise.
+///
+/// Ports are numbered left-to-right on the top/bottom edges and top-to-bottom
+/// on the left/right edges, so moving onto the bottom or top edge reverses
+/// the numbering.
+fn rotate_connection_cw((dir, ports): (Direction, PortSet)) -> (Direction, PortSet) {
+    match dir {
+        Direction::Up => (Direction::Right, ports),
+        Direction::Right => (Direction::Down, ports.flip()),
+        Direction::Down => (Direction::Left, ports),
+        Direction::Left => (Direction::Up, ports.flip()),
+        Direction::UpRight => (Direction::DownRight, ports),
+        Direction::DownRight => (Direction::DownLeft, ports),
+        Direction::DownLeft => (Direction::UpLeft, ports),
+        Direction::UpLeft => (Direction::UpRight, ports),
+    }
+}
+
+/// Rotation (degrees clockwise) and base sprite id for a tile, derived from its mask.
+///
+/// Corners are rotations of an Up→Right base named `{variant}-{up}-{right}`
+/// (0° Up+Right, 90° Right+Down, 180° Down+Left, 270° Left+Up). Straights are
+/// rotations of a vertical base; horizontal ones are at 90°.
+///
+/// 3-lane tiles (any P34 connection) get a rotation but no base id yet: their
+/// edges carry four ports, so the 2-lane base sprites don't apply.
+fn tile_orientation(variant: TileVariant, mask: u16) -> (u16, &'static str) {
+    use Direction::*;
+    use PortSet::*;
+
+    let mut conns = mask_connections(mask);
+    let has = |conns: &[(Direction, PortSet)], dir| conns.iter().any(|&(d, _)| d == dir);
+
+    // Diagonal tiles have no sprites yet; the rotation places the first
+    // corner (clockwise from up-right)
+    if variant == TileVariant::Diagonal {
+        let corners = [Direction::UpRight, Direction::DownRight, Direction::DownLeft, Direction::UpLeft];
+        let quarter_turns = corners.iter().position(|&dir| has(&conns, dir)).unwrap_or(0) as u16;
+        return (quarter_turns * 90, "");
+    }
+
+    // Junctions have no sprites yet either. A tee rotates a base open at
+    // the bottom; a cross looks the same at every angle.
+    match variant {
+        TileVariant::Tee => {
+            let open_side = [Direction::Down, Direction::Left, Direction::Up, Direction::Right];
+            let quarter_turns = open_side.iter().position(|&dir| !has(&conns, dir)).unwrap_or(0) as u16;
+            return (quarter_turns * 90, "");
+        }
+        TileVariant::Cross => return (0, ""),
+        _ => {}
+    }
+
+    let quarter_turns = match variant {
+        TileVariant::Straight if has(&conns, Up) => 0,
+        TileVariant::Straight => 1,
+        _ if has(&conns, Up) && has(&conns, Right) => 0,
+        _ if has(&conns, Right) && has(&conns, Down) => 1,
+        _ if has(&conns, Down) && has(&conns, Left) => 2,
+        _ => 3,
+    };
+
+    if conns.iter().any(|&(_, ports)| ports == P34) {
+        return (quarter_turns * 90, "");
+    }
+
+    // Undo the rotation (4 - n more clockwise turns is the identity)
+    for _ in 0..(4 - quarter_turns) % 4 {
+        conns = conns.into_iter().map(rotate_connection_cw).collect();
+    }
+    let port = |dir| conns.iter().find(|&&(d, _)| d == dir).map(|&(_, p)| p);
+
+    let base_id = match (variant, port(Up), port(Right), port(Down)) {
+        (TileVariant::Curve, Some(P12), Some(P12), _) => "curve-12-12",
+        (TileVariant::Curve, Some(P12), Some(P23), _) => "curve-12-23",
+        (TileVariant::Curve, Some(P23), Some(P12), _) => "curve-23-12",
+        (TileVariant::Curve, Some(P23), Some(P23), _) => "curve-23-23",
+        (TileVariant::Sharp, Some(P12), Some(P12), _) => "sharp-12-12",
+        (TileVariant::Sharp, Some(P12), Some(P23), _) => "sharp-12-23",
+        (TileVariant::Sharp, Some(P23), Some(P12), _) => "sharp-23-12",
+        (TileVariant::Sharp, Some(P23), Some(P23), _) => "sharp-23-23",
+        (TileVariant::Straight, Some(P12), _, Some(P12)) => "straight-v-11",
+        (TileVariant::Straight, Some(P12), _, Some(P23)) => "straight-v-12",
+        (TileVariant::Straight, Some(P23), _, Some(P12)) => "straight-v-21",
+        (TileVariant::Straight, Some(P23), _, Some(P23)) => "straight-v-22",
+        _ => "",
+    };
+
+    (quarter_turns * 90, base_id)
+}
+
+/// Build a tile definition, deriving its rotation metadata from the mask
+fn tile(
+    id: &str,
+    variant: TileVariant,
+    mask: u16,
+    conn1: (Direction, PortSet),
+    conn2: (Direction, PortSet),
+) -> TileDefinition {
+    junction(id, variant, mask, &[conn1, conn2])
+}
+
+/// Build a tile definition with any number of connections
+fn junction(
+    id: &str,
+    variant: TileVariant,
+    mask: u16,
+    connections: &[(Direction, PortSet)],
+) -> TileDefinition {
+    // Rotation and base id come from the mask alone, so a typo there would
+    // silently mis-rotate the sprite
+    debug_assert_eq!(derive_mask(connections), mask, "mask of {id}");
+    let (rotation, base_id) = tile_orientation(variant, mask);
+    TileDefinition {
+        id: id.to_string(),
+        variant,
+        mask,
+        connections: connections.to_vec(),
+        rotation,
+        base_id: base_id.to_string(),
+    }
+}
+
+/// Ports on (Up, Right) of the Up→Right corners that the 2-lane curve and
+/// sharp tiles are rotations of, in the order of their `{variant}-{up}-{right}`
+/// base sprites
+const CORNER_BASES: [(PortSet, PortSet); 4] = [
+    (PortSet::P12, PortSet::P12),
+    (PortSet::P12, PortSet::P23),
+    (PortSet::P23, PortSet::P12),
+    (PortSet::P23, PortSet::P23),
+];
+
+/// The 16 2-lane corner tiles of `variant` (curve or sharp): each base
+/// corner in each of its four rotations, sorted by mask and named
+/// `{variant}-{mask:02X}`. Rotating turns the Right and Left edges over,
+/// which `rotate_connection_cw` accounts for by flipping their ports.
+fn corner_tiles(variant: TileVariant) -> &'static [TileDefinition] {
+    static CURVES: OnceLock<Vec<TileDefinition>> = OnceLock::new();
+    static SHARPS: OnceLock<Vec<TileDefinition>> = OnceLock::new();
+    let cache = match variant {
+        TileVariant::Curve => &CURVES,
+        TileVariant::Sharp => &SHARPS,
+        _ => return &[],
+    };
+    cache.get_or_init(|| {
+        let order = |dir: Direction| Direction::all().iter().position(|&d| d == dir);
+        let mut tiles: Vec<TileDefinition> = CORNER_BASES
+            .iter()
+            .flat_map(|&(up, right)| {
+                let mut connections = vec![(Direction::Up, up), (Direction::Right, right)];
+                (0..4).map(move |_| {
+                    let rotated = connections.clone();
+                    connections = connections.iter().copied().map(rotate_connection_cw).collect();
+                    rotated
+                })
+            })
+            .map(|mut connections| {
+                connections.sort_by_key(|&(dir, _)| order(dir));
+                let mask = derive_mask(&connections);
+                let id = format!("{}-{:02X}", variant.to_string(), mask);
+                junction(&id, variant, mask, &connections)
+            })
+            .collect();
+        tiles.sort_by_key(|tile| tile.mask);
+        tiles
+    })
+}
+
+/// The active tile set: the built-in tiles, or the set installed with
+/// `set_custom_tiles`
+fn get_all_tiles() -> Vec<TileDefinition> {
+    tile_table().tiles.clone()
+}
+
+/// The tiles this crate ships with
+fn builtin_tiles() -> Vec<TileDefinition> {
+    use Direction::*;
+    use PortSet::*;
+    use TileVariant::*;
+
+    // Curve and sharp tiles (16 each)
+    let mut tiles: Vec<TileDefinition> = [Curve, Sharp]
+        .into_iter()
+        .flat_map(|variant| corner_tiles(variant).iter().cloned())
+        .collect();
+    tiles.extend([
+        // Straight tiles - Vertical (4 variants)
+        tile("straight-v-11", Straight, 0x11, (Up, P12), (Down, P12)),
+        tile("straight-v-12", Straight, 0x21, (Up, P12), (Down, P23)),
+        tile("straight-v-21", Straight, 0x12, (Up, P23), (Down, P12)),
+        tile("straight-v-22", Straight, 0x22, (Up, P23), (Down, P23)),
+        // Straight tiles - Horizontal (4 variants)
+        tile("straight-h-44", Straight, 0x44, (Left, P12), (Right, P12)),
+        tile("straight-h-48", Straight, 0x48, (Left, P12), (Right, P23)),
+        tile("straight-h-84", Straight, 0x84, (Left, P23), (Right, P12)),
+        tile("straight-h-88", Straight, 0x88, (Left, P23), (Right, P23)),
+
+        // Curve tiles using the third lane (20)
+        tile("curve-104", Curve, 0x104, (Up, P34), (Right, P12)),
+        tile("curve-108", Curve, 0x108, (Up, P34), (Right, P23)),
+        tile("curve-140", Curve, 0x140, (Up, P34), (Left, P12)),
+        tile("curve-180", Curve, 0x180, (Up, P34), (Left, P23)),
+        tile("curve-201", Curve, 0x201, (Up, P12), (Right, P34)),
+        tile("curve-202", Curve, 0x202, (Up, P23), (Right, P34)),
+        tile("curve-210", Curve, 0x210, (Right, P34), (Down, P12)),
+        tile("curve-220", Curve, 0x220, (Right, P34), (Down, P23)),
+        tile("curve-300", Curve, 0x300, (Up, P34), (Right, P34)),
+        tile("curve-404", Curve, 0x404, (Right, P12), (Down, P34)),
+        tile("curve-408", Curve, 0x408, (Right, P23), (Down, P34)),
+        tile("curve-440", Curve, 0x440, (Down, P34), (Left, P12)),
+        tile("curve-480", Curve, 0x480, (Down, P34), (Left, P23)),
+        tile("curve-600", Curve, 0x600, (Right, P34), (Down, P34)),
+        tile("curve-801", Curve, 0x801, (Up, P12), (Left, P34)),
+        tile("curve-802", Curve, 0x802, (Up, P23), (Left, P34)),
+        tile("curve-810", Curve, 0x810, (Down, P12), (Left, P34)),
+        tile("curve-820", Curve, 0x820, (Down, P23), (Left, P34)),
+        tile("curve-900", Curve, 0x900, (Up, P34), (Left, P34)),
+        tile("curve-C00", Curve, 0xC00, (Down, P34), (Left, P34)),
+
+        // Sharp tiles using the third lane (20)
+        tile("sharp-104", Sharp, 0x104, (Up, P34), (Right, P12)),
+        tile("sharp-108", Sharp, 0x108, (Up, P34), (Right, P23)),
+        tile("sharp-140", Sharp, 0x140, (Up, P34), (Left, P12)),
+        tile("sharp-180", Sharp, 0x180, (Up, P34), (Left, P23)),
+        tile("sharp-201", Sharp, 0x201, (Up, P12), (Right, P34)),
+        tile("sharp-202", Sharp, 0x202, (Up, P23), (Right, P34)),
+        tile("sharp-210", Sharp, 0x210, (Right, P34), (Down, P12)),
+        tile("sharp-220", Sharp, 0x220, (Right, P34), (Down, P23)),
+        tile("sharp-300", Sharp, 0x300, (Up, P34), (Right, P34)),
+        tile("sharp-404", Sharp, 0x404, (Right, P12), (Down, P34)),
+        tile("sharp-408", Sharp, 0x408, (Right, P23), (Down, P34)),
+        tile("sharp-440", Sharp, 0x440, (Down, P34), (Left, P12)),
+        tile("sharp-480", Sharp, 0x480, (Down, P34), (Left, P23)),
+        tile("sharp-600", Sharp, 0x600, (Right, P34), (Down, P34)),
+        tile("sharp-801", Sharp, 0x801, (Up, P12), (Left, P34)),
+        tile("sharp-802", Sharp, 0x802, (Up, P23), (Left, P34)),
+        tile("sharp-810", Sharp, 0x810, (Down, P12), (Left, P34)),
+        tile("sharp-820", Sharp, 0x820, (Down, P23), (Left, P34)),
+        tile("sharp-900", Sharp, 0x900, (Up, P34), (Left, P34)),
+        tile("sharp-C00", Sharp, 0xC00, (Down, P34), (Left, P34)),
+
+        // Straight tiles using the third lane - Vertical (5 variants)
+        tile("straight-v-13", Straight, 0x401, (Up, P12), (Down, P34)),
+        tile("straight-v-23", Straight, 0x402, (Up, P23), (Down, P34)),
+        tile("straight-v-31", Straight, 0x110, (Up, P34), (Down, P12)),
+        tile("straight-v-32", Straight, 0x120, (Up, P34), (Down, P23)),
+        tile("straight-v-33", Straight, 0x500, (Up, P34), (Down, P34)),
+        // Straight tiles using the third lane - Horizontal (5 variants)
+        tile("straight-h-240", Straight, 0x240, (Left, P12), (Right, P34)),
+        tile("straight-h-280", Straight, 0x280, (Left, P23), (Right, P34)),
+        tile("straight-h-804", Straight, 0x804, (Left, P34), (Right, P12)),
+        tile("straight-h-808", Straight, 0x808, (Left, P34), (Right, P23)),
+        tile("straight-h-A00", Straight, 0xA00, (Left, P34), (Right, P34)),
+
+        // Diagonal tiles: an edge lane to a corner (outer lane listed first),
+        // or corner to corner. Corners have a single junction, written P23
+        tile("diagonal-1002", Diagonal, 0x1002, (Up, P23), (UpRight, P23)),
+        tile("diagonal-1001", Diagonal, 0x1001, (Up, P12), (UpRight, P23)),
+        tile("diagonal-2002", Diagonal, 0x2002, (Up, P23), (DownRight, P23)),
+        tile("diagonal-2001", Diagonal, 0x2001, (Up, P12), (DownRight, P23)),
+        tile("diagonal-4002", Diagonal, 0x4002, (Up, P23), (DownLeft, P23)),
+        tile("diagonal-4001", Diagonal, 0x4001, (Up, P12), (DownLeft, P23)),
+        tile("diagonal-8002", Diagonal, 0x8002, (Up, P23), (UpLeft, P23)),
+        tile("diagonal-8001", Diagonal, 0x8001, (Up, P12), (UpLeft, P23)),
+        tile("diagonal-1008", Diagonal, 0x1008, (Right, P23), (UpRight, P23)),
+        tile("diagonal-1004", Diagonal, 0x1004, (Right, P12), (UpRight, P23)),
+        tile("diagonal-2008", Diagonal, 0x2008, (Right, P23), (DownRight, P23)),
+        tile("diagonal-2004", Diagonal, 0x2004, (Right, P12), (DownRight, P23)),
+        tile("diagonal-4008", Diagonal, 0x4008, (Right, P23), (DownLeft, P23)),
+        tile("diagonal-4004", Diagonal, 0x4004, (Right, P12), (DownLeft, P23)),
+        tile("diagonal-8008", Diagonal, 0x8008, (Right, P23), (UpLeft, P23)),
+        tile("diagonal-8004", Diagonal, 0x8004, (Right, P12), (UpLeft, P23)),
+        tile("diagonal-1020", Diagonal, 0x1020, (Down, P23), (UpRight, P23)),
+        tile("diagonal-1010", Diagonal, 0x1010, (Down, P12), (UpRight, P23)),
+        tile("diagonal-2020", Diagonal, 0x2020, (Down, P23), (DownRight, P23)),
+        tile("diagonal-2010", Diagonal, 0x2010, (Down, P12), (DownRight, P23)),
+        tile("diagonal-4020", Diagonal, 0x4020, (Down, P23), (DownLeft, P23)),
+        tile("diagonal-4010", Diagonal, 0x4010, (Down, P12), (DownLeft, P23)),
+        tile("diagonal-8020", Diagonal, 0x8020, (Down, P23), (UpLeft, P23)),
+        tile("diagonal-8010", Diagonal, 0x8010, (Down, P12), (UpLeft, P23)),
+        tile("diagonal-1080", Diagonal, 0x1080, (Left, P23), (UpRight, P23)),
+        tile("diagonal-1040", Diagonal, 0x1040, (Left, P12), (UpRight, P23)),
+        tile("diagonal-2080", Diagonal, 0x2080, (Left, P23), (DownRight, P23)),
+        tile("diagonal-2040", Diagonal, 0x2040, (Left, P12), (DownRight, P23)),
+        tile("diagonal-4080", Diagonal, 0x4080, (Left, P23), (DownLeft, P23)),
+        tile("diagonal-4040", Diagonal, 0x4040, (Left, P12), (DownLeft, P23)),
+        tile("diagonal-8080", Diagonal, 0x8080, (Left, P23), (UpLeft, P23)),
+        tile("diagonal-8040", Diagonal, 0x8040, (Left, P12), (UpLeft, P23)),
+        tile("diagonal-3000", Diagonal, 0x3000, (UpRight, P23), (DownRight, P23)),
+        tile("diagonal-5000", Diagonal, 0x5000, (UpRight, P23), (DownLeft, P23)),
+        tile("diagonal-9000", Diagonal, 0x9000, (UpRight, P23), (UpLeft, P23)),
+        tile("diagonal-6000", Diagonal, 0x6000, (DownRight, P23), (DownLeft, P23)),
+        tile("diagonal-A000", Diagonal, 0xA000, (DownRight, P23), (UpLeft, P23)),
+        tile("diagonal-C000", Diagonal, 0xC000, (DownLeft, P23), (UpLeft, P23)),
+
+        // Junctions for branching layouts; the path finder never picks them.
+        // Tees are open on one side; all roads of a junction share one lane
+        junction("tee-45", Tee, 0x45, &[(Up, P12), (Right, P12), (Left, P12)]),
+        junction("tee-8A", Tee, 0x8A, &[(Up, P23), (Right, P23), (Left, P23)]),
+        junction("tee-15", Tee, 0x15, &[(Up, P12), (Right, P12), (Down, P12)]),
+        junction("tee-2A", Tee, 0x2A, &[(Up, P23), (Right, P23), (Down, P23)]),
+        junction("tee-54", Tee, 0x54, &[(Right, P12), (Down, P12), (Left, P12)]),
+        junction("tee-A8", Tee, 0xA8, &[(Right, P23), (Down, P23), (Left, P23)]),
+        junction("tee-51", Tee, 0x51, &[(Up, P12), (Down, P12), (Left, P12)]),
+        junction("tee-A2", Tee, 0xA2, &[(Up, P23), (Down, P23), (Left, P23)]),
+        junction("cross-55", Cross, 0x55, &[(Up, P12), (Right, P12), (Down, P12), (Left, P12)]),
+        junction("cross-AA", Cross, 0xAA, &[(Up, P23), (Right, P23), (Down, P23), (Left, P23)]),
+    ]);
+    tiles
+}
+
+/// A tile set with the lookups the tilers use, built once per set
+struct TileTable {
+    tiles: Vec<TileDefinition>,
+    by_id: HashMap<String, usize>,
+    /// Answers of `find_tile_with_port_constraint` for every key
+    index: HashMap<TileKey, (String, PortSet, PortSet)>,
+}
+
+impl TileTable {
+    fn new(tiles: Vec<TileDefinition>) -> Self {
+        let by_id = tiles.iter().enumerate().map(|(i, tile)| (tile.id.clone(), i)).collect();
+        let mut index = HashMap::new();
+        for entry in Direction::with_diagonals() {
+            for exit in Direction::with_diagonals() {
+                for required in [None, Some(PortSet::P12), Some(PortSet::P23), Some(PortSet::P34)] {
+                    for allow_lane_change in [false, true] {
+                        let found = find_tile_with_port_constraint(&tiles, entry, exit, required, allow_lane_change);
+                        if let Some(hit) = found {
+                            index.insert((entry.opposite(), exit, required, allow_lane_change), hit);
+                        }
+                    }
+                }
+            }
+        }
+        Self { tiles, by_id, index }
+    }
+}
+
+thread_local! {
+    /// Table of the built-in tiles, shared by every reset to them
+    static BUILTIN_TILES: Rc<TileTable> = Rc::new(TileTable::new(builtin_tiles()));
+    /// Table of the active set: the built-in one, or the one installed by
+    /// `set_custom_tiles`, dropped once replaced. Per thread: wasm has only
+    /// one, and native tests stay independent.
+    static ACTIVE_TILES: RefCell<Rc<TileTable>> = RefCell::new(BUILTIN_TILES.with(Rc::clone));
+}
+
+/// The table of the active tile set
+fn tile_table() -> Rc<TileTable> {
+    ACTIVE_TILES.with(|active| Rc::clone(&active.borrow()))
+}
+
+/// Check and convert a replacement tile set. Every tile needs a unique id
+/// (not a start/goal marker name), a known variant, at least two
+/// connections on distinct sides and the mask `derive_mask` gives for them.
+/// Without a `base_id`, the rotation and base id are derived from the mask
+/// as for the built-in tiles.
+fn custom_tile_definitions(infos: &[TileInfo]) -> Result<Vec<TileDefinition>, String> {
+    if infos.is_empty() {
+        return Err("the tile set is empty".to_string());
+    }
+    let mut ids = HashSet::new();
+    let mut tiles = Vec::with_capacity(infos.len());
+    for info in infos {
+        let id = info.id.as_str();
+        if id.is_empty() || id == "start" || id == "goal" || !ids.insert(id) {
+            return Err(format!("tile id \"{}\" is empty, reserved or used twice", id));
+        }
+        let variant = TileVariant::parse(&info.variant)
+            .ok_or_else(|| format!("tile {} has unknown variant \"{}\"", id, info.variant))?;
+        let connections = info
+            .connections
+            .iter()
+            .map(Connection::parse)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| format!("tile {}: {}", id, error))?;
+        let sides: HashSet<Direction> = connections.iter().map(|&(dir, _)| dir).collect();
+        if connections.len() < 2 || sides.len() != connections.len() {
+            return Err(format!("tile {} needs two or more connections on distinct sides", id));
+        }
+        let mask = derive_mask(&connections);
+        if mask != info.mask {
+            return Err(format!(
+                "tile {} declares mask {:#X} but its connections give {:#X}",
+                id, info.mask, mask
+            ));
+        }
+        let (rotation, base_id) = if info.base_id.is_empty() {
+            tile_orientation(variant, mask)
+        } else {
+            (info.rotation, info.base_id.as_str())
+        };
+        tiles.push(TileDefinition {
+            id: info.id.clone(),
+            variant,
+            mask,
+            connections,
+            rotation,
+            base_id: base_id.to_string(),
+        });
+    }
+    Ok(tiles)
+}
+
+/// Tiles of the active set with a connection on the `dir` side
+fn tiles_toward(dir: Direction) -> Vec<TileDefinition> {
+    get_all_tiles().into_iter().filter(|tile| tile.has_direction(dir)).collect()
+}
+
+/// Make `infos` the active tile set, or leave the active set unchanged and
+/// report the first problem
+fn install_custom_tiles(infos: &[TileInfo]) -> Result<(), String> {
+    let table = Rc::new(TileTable::new(custom_tile_definitions(infos)?));
+    ACTIVE_TILES.with(|active| *active.borrow_mut() = table);
+    Ok(())
+}
+
+/// Go back to the built-in tile set
+fn reset_tile_set() {
+    let builtin = BUILTIN_TILES.with(Rc::clone);
+    ACTIVE_TILES.with(|active| *active.borrow_mut() = builtin);
+}
+
+/// Find tiles that connect in given directions with given ports
+#[allow(dead_code)]
+fn find_matching_tiles(
+    from_dir: Direction,
+    from_ports: PortSet,
+    to_dir: Direction,
+    to_ports: PortSet,
+    tiles: &[TileDefinition],
+) -> Vec<&TileDefinition> {
+    tiles
+        .iter()
+        .filter(|tile| {
+            tile.get_connection(from_dir.opposite()) == Some(from_ports)
+                && tile.get_connection(to_dir) == Some(to_ports)
+        })
+        .collect()
+}
+
+// ============================================================================
+// Path Finding Algorithm
+// ============================================================================
+
+/// How many iterations pass between two polls of the cancellation hook
+const CANCEL_POLL_INTERVAL: u32 = 1024;
+
+/// Iterations per grid cell granted when `max_iterations` is 0 ("auto")
+const AUTO_ITERATIONS_PER_CELL: u32 = 20_000;
+
+/// Bounds of the automatic budget, so tiny grids still get a real search and
+/// huge ones don't run unbounded
+const AUTO_ITERATIONS_MIN: u32 = 100_000;
+const AUTO_ITERATIONS_MAX: u32 = 50_000_000;
+
+/// Largest budget honoured; higher requests are clamped so the iteration
+/// counter can never overflow
+const MAX_ITERATIONS_CAP: u32 = u32::MAX / 2;
+
+/// Budget picked for `max_iterations: 0`: `rows * cols * 20_000`, clamped to
+/// `[100_000, 50_000_000]`
+fn auto_max_iterations(grid_size: GridSize) -> u32 {
+    let area = grid_size.rows.max(0) as u64 * grid_size.cols.max(0) as u64;
+    (area * AUTO_ITERATIONS_PER_CELL as u64)
+        .clamp(AUTO_ITERATIONS_MIN as u64, AUTO_ITERATIONS_MAX as u64) as u32
+}
+
+/// Resolve a caller's `max_iterations`: 0 picks `auto_max_iterations`,
+/// anything above `MAX_ITERATIONS_CAP` is clamped to it
+fn effective_max_iterations(max_iterations: u32, grid_size: GridSize) -> u32 {
+    match max_iterations {
+        0 => auto_max_iterations(grid_size),
+        n => n.min(MAX_ITERATIONS_CAP),
+    }
+}
+
+/// Cancellation hook polled during the search; returns true to abort
+type CancelFn = Box<dyn FnMut() -> bool>;
+
+/// Progress hook called with (iterations, path depth); returns false to abort
+type ProgressFn = Box<dyn FnMut(u32, usize) -> bool>;
+
+/// Clock returning the current time in milliseconds
+type ClockFn = Box<dyn FnMut() -> f64>;
+
+/// Small deterministic PRNG (xorshift32) used to break heuristic ties
+#[derive(Debug, Clone)]
+struct XorShift32 {
+    state: u32,
+}
+
+impl XorShift32 {
+    /// `seed` must be nonzero; a zero state would stay zero forever
+    fn new(seed: u32) -> Self {
+        debug_assert!(seed != 0);
+        Self { state: seed }
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+
+    /// Fisher-Yates shuffle
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = (self.next_u32() % (i as u32 + 1)) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+/// Backtracking strategy used by the path search
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum SearchStrategy {
+    /// Plain DFS with Warnsdorff neighbor ordering
+    #[default]
+    Warnsdorff,
+    /// Return a snake (see `build_snake_path`) without searching when the
+    /// endpoints fit one on a plain rectangle, else plain Warnsdorff DFS
+    Snake,
+}
+
+impl SearchStrategy {
+    fn parse(s: &str) -> Option<SearchStrategy> {
+        match s {
+            "warnsdorff" => Some(SearchStrategy::Warnsdorff),
+            "snake" => Some(SearchStrategy::Snake),
+            _ => None,
+        }
+    }
+}
+
+/// How neighbors that tie under Warnsdorff's rule (and on weight) are ordered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum TieBreak {
+    /// Keep the `Direction::all()` order (or the seeded shuffle)
+    #[default]
+    FixedOrder,
+    /// Try continuing in the direction of the last step first
+    PreferStraight,
+    /// Try turning away from the direction of the last step first
+    PreferTurn,
+    /// Try cells with the fewest visited or blocked neighbors (besides the
+    /// current cell) first, so the road runs alongside itself less often
+    Spacious,
+}
+
+impl TieBreak {
+    fn parse(s: &str) -> Option<TieBreak> {
+        match s {
+            "fixed" => Some(TieBreak::FixedOrder),
+            "straight" => Some(TieBreak::PreferStraight),
+            "turn" => Some(TieBreak::PreferTurn),
+            "spacious" => Some(TieBreak::Spacious),
+            _ => None,
+        }
+    }
+}
+
+/// Overall shape the search leans toward. Ranked right after Warnsdorff's
+/// rule and weights, so it only reorders neighbors and never changes which
+/// paths exist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum PathStyle {
+    /// No preference
+    #[default]
+    Default,
+    /// Try cells nearest the grid border first, so the road hugs the walls
+    /// and winds inward like a spiral
+    Spiral,
+    /// Try horizontal steps first, so the road keeps running along rows
+    Snake,
+}
+
+impl PathStyle {
+    fn parse(s: &str) -> Option<PathStyle> {
+        match s {
+            "default" => Some(PathStyle::Default),
+            "spiral" => Some(PathStyle::Spiral),
+            "snake" => Some(PathStyle::Snake),
+            _ => None,
+        }
+    }
+}
+
+/// Optional constraints and hooks for a path search
+#[derive(Default)]
+struct SearchOptions {
+    /// Obstacle cells the path skips entirely
+    blocked: Vec<Point>,
+    /// Cells the path must pass through, in this order
+    waypoints: Vec<Point>,
+    /// Nonzero seeds shuffle neighbors that tie under Warnsdorff's rule
+    seed: u32,
+    /// Row-major flags (length rows*cols) marking which cells exist; `None`
+    /// means the full rectangle
+    cell_mask: Option<Vec<bool>>,
+    should_cancel: Option<CancelFn>,
+    /// Called every `progress_interval` iterations (0 means `CANCEL_POLL_INTERVAL`)
+    on_progress: Option<ProgressFn>,
+    progress_interval: u32,
+    /// Clock and wall-clock budget in milliseconds, checked every
+    /// `CANCEL_POLL_INTERVAL` iterations; without a clock only
+    /// `max_iterations` bounds the search
+    clock: Option<ClockFn>,
+    time_budget_ms: f64,
+    strategy: SearchStrategy,
+    /// Reject states whose unvisited cells are split, cut off from the end or
+    /// contain a dead end (costs a flood fill per step). Ignored together
+    /// with `target_length`, since the path then needn't cover everything.
+    pruning: bool,
+    /// Succeed on reaching `end` with exactly this many cells instead of
+    /// covering every free cell
+    target_length: Option<usize>,
+    /// Count expanded nodes per depth (see `PathResult::depth_counts`)
+    record_depths: bool,
+    /// Return the visited grid of a failed search (see
+    /// `PathResult::visited_grid`)
+    record_visited: bool,
+    /// Per-cell weights (`weights[row][col]`, missing entries count as 0);
+    /// among neighbors that tie under Warnsdorff's rule, lower weights are
+    /// tried first. Only changes the order, never which paths exist.
+    weights: Option<Vec<Vec<i32>>>,
+    /// Also allow diagonal steps, as long as they don't cross a diagonal
+    /// step already on the path. Disables the parity checks and pruning,
+    /// which assume orthogonal moves.
+    diagonals: bool,
+    /// Shape to lean toward among neighbors tied under Warnsdorff's rule
+    /// and weights
+    style: PathStyle,
+    /// Order of neighbors still tied after Warnsdorff's rule, weights and style
+    tie_break: TieBreak,
+    /// Succeed as soon as the target is covered, wherever the path ends;
+    /// the `end` passed to the search is ignored. Disables pruning, which
+    /// needs a fixed end.
+    open_end: bool,
+    /// Skip pairs already proven unsolvable in this session and remember
+    /// newly proven ones (see `unsolvable_pairs`)
+    memoize_unsolvable: bool,
+    /// Cells whose tile is fixed: the path must enter and leave them through
+    /// the tile's two sides, and tiling it with the default options must
+    /// give the tile's ports there
+    locked_tiles: Vec<(Point, TileDefinition)>,
+    /// Treat the grid as a torus: stepping off an edge enters the opposite
+    /// one (see `torus_step`). Orthogonal moves only, so `diagonals` is
+    /// ignored.
+    wrap: bool,
+}
+
+/// State for path finding
+struct PathState {
+    grid: Vec<Vec<bool>>, // visited cells (blocked cells are permanently visited)
+    path: Vec<Point>,
+    grid_size: GridSize,
+    cell_count: usize,            // grid area, 0 for sizes that can't describe a grid
+    cell_mask: Option<Vec<bool>>, // row-major existing-cell flags
+    missing_count: usize,         // cells excluded by the mask
+    blocked_count: usize,
+    waypoints: Vec<Point>,
+    next_waypoint: usize, // index of the next waypoint the path may enter
+    iterations: u32,
+    backtracks: u32, // unvisit calls, i.e. cells taken back off the path
+    max_iterations: u32,
+    rng: Option<XorShift32>,
+    should_cancel: Option<CancelFn>,
+    on_progress: Option<ProgressFn>,
+    progress_interval: u32,
+    cancelled: bool,
+    clock: Option<ClockFn>,
+    deadline_ms: f64,
+    timed_out: bool,
+    pruning: bool,
+    target_length: Option<usize>,
+    depth_counts: Option<Vec<u32>>,
+    record_visited: bool,
+    weights: Option<Vec<Vec<i32>>>,
+    diagonals: bool,
+    style: PathStyle,
+    tie_break: TieBreak,
+    open_end: bool,
+    memoize_unsolvable: bool,
+    locked_tiles: Vec<(Point, TileDefinition)>,
+    wrap: bool,
+    best_path: Vec<Point>, // longest path seen so far
+}
+
+impl PathState {
+    fn new(grid_size: GridSize, max_iterations: u32) -> Self {
+        let grid = grid_size.table(false);
+        Self {
+            grid,
+            path: Vec::new(),
+            grid_size,
+            cell_count: grid_size.cell_count().unwrap_or(0),
+            cell_mask: None,
+            missing_count: 0,
+            blocked_count: 0,
+            waypoints: Vec::new(),
+            next_waypoint: 0,
+            iterations: 0,
+            backtracks: 0,
+            max_iterations: effective_max_iterations(max_iterations, grid_size),
+            rng: None,
+            should_cancel: None,
+            on_progress: None,
+            progress_interval: CANCEL_POLL_INTERVAL,
+            cancelled: false,
+            clock: None,
+            deadline_ms: f64::INFINITY,
+            timed_out: false,
+            pruning: false,
+            target_length: None,
+            depth_counts: None,
+            record_visited: false,
+            weights: None,
+            diagonals: false,
+            style: PathStyle::Default,
+            tie_break: TieBreak::FixedOrder,
+            open_end: false,
+            memoize_unsolvable: false,
+            locked_tiles: Vec::new(),
+            wrap: false,
+            best_path: Vec::new(),
+        }
+    }
+
+    /// Create a state configured with the given constraints and hooks
+    fn with_options(grid_size: GridSize, max_iterations: u32, options: SearchOptions) -> Self {
+        let mut state = Self::new(grid_size, max_iterations);
+        if let Some(mask) = options.cell_mask {
+            state.missing_count = mask.iter().filter(|&&exists| !exists).count();
+            state.cell_mask = Some(mask);
+        }
+        state.block_cells(&options.blocked);
+        state.waypoints = options.waypoints;
+        state.rng = (options.seed != 0).then(|| XorShift32::new(options.seed));
+        state.should_cancel = options.should_cancel;
+        state.on_progress = options.on_progress;
+        if options.progress_interval > 0 {
+            state.progress_interval = options.progress_interval;
+        }
+        state.target_length = options.target_length;
+        state.weights = options.weights;
+        state.diagonals = options.diagonals && !options.wrap;
+        state.wrap = options.wrap;
+        state.style = options.style;
+        state.tie_break = options.tie_break;
+        state.open_end = options.open_end;
+        state.memoize_unsolvable = options.memoize_unsolvable;
+        state.locked_tiles = options.locked_tiles;
+        if options.record_depths {
+            state.depth_counts = Some(vec![0; state.target_cells()]);
+        }
+        state.record_visited = options.record_visited;
+        state.pruning = options.pruning
+            && options.target_length.is_none()
+            && !state.diagonals
+            && !options.open_end;
+        if let Some(mut clock) = options.clock {
+            state.deadline_ms = clock() + options.time_budget_ms;
+            state.clock = Some(clock);
+        }
+        state
+    }
+
+    /// Clear the current path and counters so the state can run another
+    /// search. Blocked cells and the mask are kept.
+    fn reset(&mut self) {
+        for p in self.path.drain(..) {
+            self.grid[p.row as usize][p.col as usize] = false;
+        }
+        self.best_path.clear();
+        self.next_waypoint = 0;
+        self.iterations = 0;
+        self.backtracks = 0;
+        self.cancelled = false;
+        self.timed_out = false;
+        if let Some(counts) = self.depth_counts.as_mut() {
+            counts.fill(0);
+        }
+    }
+
+    /// Sort rank of stepping `dir` from `current` to `p` after a step
+    /// `previous`, under the tie-break setting (lower goes first)
+    fn tie_rank(
+        &self,
+        previous: Option<Direction>,
+        current: Point,
+        p: Point,
+        dir: Direction,
+    ) -> usize {
+        let straight = previous == Some(dir);
+        match self.tie_break {
+            TieBreak::FixedOrder => 0,
+            TieBreak::PreferStraight => usize::from(!straight),
+            TieBreak::PreferTurn => usize::from(straight),
+            TieBreak::Spacious => Direction::all()
+                .iter()
+                .map(|&d| self.step(p, d))
+                .filter(|&q| q != current && self.is_valid(q) && self.is_visited(q))
+                .count(),
+        }
+    }
+
+    /// Sort rank of stepping `dir` onto `p` under the path style (lower goes
+    /// first): the distance to the nearest border for a spiral, and whether
+    /// the step leaves the row for a snake
+    fn style_rank(&self, p: Point, dir: Direction) -> i32 {
+        match self.style {
+            PathStyle::Default => 0,
+            PathStyle::Spiral => {
+                let GridSize { rows, cols } = self.grid_size;
+                p.row.min(p.col).min(rows - 1 - p.row).min(cols - 1 - p.col)
+            }
+            PathStyle::Snake => i32::from(dir.delta().0 != 0),
+        }
+    }
+
+    /// Weight of `p` for neighbor ordering (0 without a weight grid)
+    fn cell_weight(&self, p: Point) -> i32 {
+        self.weights
+            .as_ref()
+            .and_then(|w| w.get(p.row as usize)?.get(p.col as usize).copied())
+            .unwrap_or(0)
+    }
+
+    /// Poll the cancellation and progress hooks and the clock when due. A
+    /// progress hook returning false counts as cancellation, running out of
+    /// time as a timeout. Once stopped, stays stopped.
+    fn poll_cancelled(&mut self) -> bool {
+        if !self.cancelled && self.iterations.is_multiple_of(CANCEL_POLL_INTERVAL) {
+            if let Some(hook) = self.should_cancel.as_mut() {
+                self.cancelled = hook();
+            }
+            if let Some(clock) = self.clock.as_mut() {
+                if !self.cancelled && clock() >= self.deadline_ms {
+                    self.timed_out = true;
+                    self.cancelled = true;
+                }
+            }
+        }
+        if !self.cancelled && self.iterations.is_multiple_of(self.progress_interval) {
+            if let Some(hook) = self.on_progress.as_mut() {
+                self.cancelled = !hook(self.iterations, self.path.len());
+            }
+        }
+        self.cancelled
+    }
+
+    /// Mark cells as permanently visited obstacles the path must skip.
+    /// Out-of-bounds, masked-out and duplicate points are ignored.
+    fn block_cells(&mut self, blocked: &[Point]) {
+        for &p in blocked {
+            if self.is_valid(p) && !self.is_visited(p) {
+                self.grid[p.row as usize][p.col as usize] = true;
+                self.blocked_count += 1;
+            }
+        }
+    }
+
+    /// Whether `p` is an existing cell: inside the grid and not masked out
+    fn is_valid(&self, p: Point) -> bool {
+        self.grid_size.contains(p)
+            && self
+                .cell_mask
+                .as_ref()
+                .is_none_or(|mask| mask[(p.row * self.grid_size.cols + p.col) as usize])
+    }
+
+    fn is_visited(&self, p: Point) -> bool {
+        if !self.is_valid(p) {
+            return true;
+        }
+        self.grid[p.row as usize][p.col as usize]
+    }
+
+    fn visit(&mut self, p: Point) {
+        #[cfg(feature = "fuzz-checks")]
+        assert!(!self.is_visited(p), "visiting {:?}, which is off the grid or taken", p);
+        self.grid[p.row as usize][p.col as usize] = true;
+        self.path.push(p);
+        if self.path.len() > self.best_path.len() {
+            self.best_path.clone_from(&self.path);
+        }
+        if self.waypoints.get(self.next_waypoint) == Some(&p) {
+            self.next_waypoint += 1;
+        }
+    }
+
+    fn unvisit(&mut self, p: Point) {
+        #[cfg(feature = "fuzz-checks")]
+        assert!(self.path.last() == Some(&p), "unvisiting {:?}, which is not the path head", p);
+        self.grid[p.row as usize][p.col as usize] = false;
+        self.path.pop();
+        self.backtracks += 1;
+        if self.next_waypoint > 0 && self.waypoints[self.next_waypoint - 1] == p {
+            self.next_waypoint -= 1;
+        }
+    }
+
+    /// Consistency checks run after each search with the `fuzz-checks`
+    /// feature: the path stays on existing cells, never repeats one and
+    /// moves between neighbors, and the visited flags are exactly the path
+    /// plus the obstacles.
+    #[cfg(feature = "fuzz-checks")]
+    fn check_invariants(&self) {
+        let path = &self.path;
+        assert!(path.iter().all(|&p| self.is_valid(p)), "path leaves the grid: {:?}", path);
+        let cells: HashSet<Point> = path.iter().copied().collect();
+        assert_eq!(cells.len(), path.len(), "path repeats a cell: {:?}", path);
+        let joined = path
+            .windows(2)
+            .all(|w| self.directions().iter().any(|&dir| self.step(w[0], dir) == w[1]));
+        assert!(joined, "path makes a step that is not a move: {:?}", path);
+        let visited = self.grid.iter().flatten().filter(|&&v| v).count();
+        let expected = path.len() + self.blocked_count;
+        assert_eq!(visited, expected, "visited flags out of sync with the path");
+    }
+
+    /// Cache key for `start`..`end` when memoizing and the search is a plain
+    /// full-grid one, whose outcome depends only on the dimensions and the
+    /// endpoints. Neighbor order (seed, strategy, tie-break) never changes
+    /// whether a path exists, so it isn't part of the key.
+    fn solvability_key(&self, start: Point, end: Point) -> Option<SolvabilityKey> {
+        let plain = self.blocked_count == 0
+            && self.missing_count == 0
+            && self.waypoints.is_empty()
+            && self.target_length.is_none()
+            && !self.diagonals
+            && !self.open_end
+            && self.locked_tiles.is_empty()
+            && !self.wrap;
+        (self.memoize_unsolvable && plain).then(|| solvability_key(start, end, self.grid_size))
+    }
+
+    /// The visited grid as it was when the path was longest: permanently
+    /// visited cells (obstacles, masked-out cells) plus `best_path`
+    fn deepest_visited_grid(&self) -> Vec<Vec<bool>> {
+        let (rows, cols) = (self.grid_size.rows, self.grid_size.cols);
+        let mut grid: Vec<Vec<bool>> = (0..rows)
+            .map(|row| (0..cols).map(|col| self.is_visited(Point::new(row, col))).collect())
+            .collect();
+        for p in &self.best_path {
+            grid[p.row as usize][p.col as usize] = true;
+        }
+        grid
+    }
+
+    /// Whether entering `p` now respects the waypoint order: a waypoint may
+    /// only be entered once all earlier waypoints have been passed.
+    fn waypoint_order_allows(&self, p: Point) -> bool {
+        match self.waypoints.iter().position(|&w| w == p) {
+            Some(index) => index == self.next_waypoint,
+            None => true,
+        }
+    }
+
+    /// The tile `p` is locked to, if any
+    fn locked_tile(&self, p: Point) -> Option<&TileDefinition> {
+        self.locked_tiles.iter().find(|(q, _)| *q == p).map(|(_, tile)| tile)
+    }
+
+    /// Narrow the next steps from `current` (the last path cell) to those
+    /// the locked tiles allow. A locked cell may only be entered through a
+    /// side its tile connects, a locked `current` must leave through its
+    /// other side, and an unvisited locked neighbor connecting towards
+    /// `current` must come next: that shared edge can't be used later.
+    fn lock_candidates(&self, current: Point, candidates: Vec<Point>) -> Vec<Point> {
+        if self.locked_tiles.is_empty() {
+            return candidates;
+        }
+        let mut forced = None;
+        if let Some(tile) = self.locked_tile(current) {
+            let entry_side = match self.path.as_slice() {
+                [.., before, _] => self.direction_to(current, *before),
+                _ => None,
+            };
+            forced = tile
+                .connections
+                .iter()
+                .find(|&&(dir, _)| Some(dir) != entry_side)
+                .map(|&(dir, _)| self.step(current, dir));
+        }
+        for dir in Direction::all() {
+            let next = self.step(current, dir);
+            let pulls = !self.is_visited(next)
+                && self.locked_tile(next).is_some_and(|tile| tile.has_direction(dir.opposite()));
+            if pulls {
+                if forced.is_some_and(|cell| cell != next) {
+                    return Vec::new();
+                }
+                forced = Some(next);
+            }
+        }
+
+        candidates
+            .into_iter()
+            .filter(|&p| forced.is_none_or(|cell| cell == p))
+            .filter(|&p| {
+                self.locked_tile(p).is_none_or(|tile| {
+                    self.direction_to(p, current).is_some_and(|dir| tile.has_direction(dir))
+                })
+            })
+            .collect()
+    }
+
+    /// Whether tiling the current path puts each locked tile's connections,
+    /// ports included, on its cell
+    fn locks_satisfied(&self) -> bool {
+        if self.locked_tiles.is_empty() {
+            return true;
+        }
+        let options = TileOptions {
+            wrap: self.wrap,
+            ..TileOptions::default()
+        };
+        let tiled = path_to_tiles(&self.path, self.grid_size, options);
+        tiled.valid
+            && self.locked_tiles.iter().all(|(p, tile)| {
+                tiled.grid[p.row as usize][p.col as usize]
+                    .as_ref()
+                    .and_then(CellData::connection_mask)
+                    == Some(tile.mask)
+            })
+    }
+
+    /// Why the locked tiles can't all be on a path between `start` and
+    /// `end`: a lock on an endpoint (those show start/goal markers), on an
+    /// obstacle, or with a tile that isn't a two-sided orthogonal road piece
+    fn lock_infeasibility_reason(&self, start: Point, end: Point) -> Option<String> {
+        self.locked_tiles.iter().find_map(|&(p, ref tile)| {
+            let at = format!("({}, {})", p.row, p.col);
+            if p == start || (p == end && !self.open_end) {
+                Some(format!("the locked cell {} is an endpoint, which shows a marker", at))
+            } else if self.is_visited(p) {
+                Some(format!("the locked cell {} is not a free cell", at))
+            } else if !matches!(
+                tile.variant,
+                TileVariant::Straight | TileVariant::Curve | TileVariant::Sharp
+            ) {
+                Some(format!("the locked tile {} at {} is not a two-sided road piece", tile.id, at))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Whether the path has its target number of cells (every free cell by
+    /// default) and has passed all waypoints
+    fn target_reached(&self) -> bool {
+        self.path.len() == self.target_cells() && self.next_waypoint == self.waypoints.len()
+    }
+
+    /// Number of cells a solution has: `target_length`, or every free cell
+    fn target_cells(&self) -> usize {
+        self.target_length.unwrap_or_else(|| self.free_cell_count())
+    }
+
+    /// Number of cells the path has to cover (grid area minus obstacles)
+    fn free_cell_count(&self) -> usize {
+        self.cell_count - self.missing_count - self.blocked_count
+    }
+
+    /// Count the free (unvisited) cells of each parity class: (even, odd)
+    fn free_parity_counts(&self) -> (usize, usize) {
+        let mut counts = (0, 0);
+        for row in 0..self.grid_size.rows {
+            for col in 0..self.grid_size.cols {
+                if !self.is_visited(Point::new(row, col)) {
+                    if cell_parity(row, col) == 0 {
+                        counts.0 += 1;
+                    } else {
+                        counts.1 += 1;
+                    }
+                }
+            }
+        }
+        counts
+    }
+
+    /// Pre-check for a path of exactly `length` cells: it must fit in the free
+    /// cells, span at least the step distance (Manhattan, or Chebyshev with
+    /// diagonals), and without diagonals end on the color its length implies
+    /// (a path alternates colors).
+    fn length_infeasibility_reason(&self, start: Point, end: Point, length: usize) -> Option<String> {
+        let free = self.free_cell_count();
+        if length < 2 || length > free {
+            return Some(format!("a path of {} cells needs between 2 and {} cells", length, free));
+        }
+        if self.open_end {
+            return None;
+        }
+        let (dr, dc) = (start.row.abs_diff(end.row), start.col.abs_diff(end.col));
+        let (dr, dc) = match self.wrap {
+            // Going around may be shorter on a torus
+            true => (
+                torus_distance(dr, self.grid_size.rows),
+                torus_distance(dc, self.grid_size.cols),
+            ),
+            false => (dr, dc),
+        };
+        let distance = if self.diagonals { dr.max(dc) } else { dr + dc } as usize;
+        if length < distance + 1 {
+            return Some(format!(
+                "the endpoints are {} steps apart, so a path needs at least {} cells",
+                distance,
+                distance + 1
+            ));
+        }
+        let same_color = cell_parity(start.row, start.col) == cell_parity(end.row, end.col);
+        if self.is_bipartite() && same_color == length.is_multiple_of(2) {
+            return Some(format!(
+                "a path alternates checkerboard colors, so a path of {} cells needs endpoints \
+                 of {} colors",
+                length,
+                if length.is_multiple_of(2) { "different" } else { "the same" }
+            ));
+        }
+        None
+    }
+
+    /// Parity (checkerboard coloring) pre-check for a Hamiltonian path.
+    fn parity_allows_path(&self, start: Point, end: Point) -> bool {
+        self.infeasibility_reason(start, end).is_none()
+    }
+
+    /// Connectivity pruning: the unvisited cells must form one region that
+    /// contains `end`, and no unvisited cell other than `end` may be a dead
+    /// end (fewer than two unvisited neighbors, counting `current`).
+    fn remaining_region_ok(&self, current: Point, end: Point) -> bool {
+        let remaining = self.free_cell_count() - self.path.len();
+        let mut seen = self.grid_size.table(false);
+        let mut stack = vec![end];
+        seen[end.row as usize][end.col as usize] = true;
+        let mut reached = 0;
+        while let Some(p) = stack.pop() {
+            reached += 1;
+            for dir in Direction::all() {
+                let next = self.step(p, dir);
+                if !self.is_visited(next) && !seen[next.row as usize][next.col as usize] {
+                    seen[next.row as usize][next.col as usize] = true;
+                    stack.push(next);
+                }
+            }
+        }
+        if reached != remaining {
+            return false;
+        }
+
+        for row in 0..self.grid_size.rows {
+            for col in 0..self.grid_size.cols {
+                let p = Point::new(row, col);
+                if p == end || self.is_visited(p) {
+                    continue;
+                }
+                let degree = Direction::all()
+                    .iter()
+                    .filter(|&&dir| {
+                        let next = self.step(p, dir);
+                        next == current || !self.is_visited(next)
+                    })
+                    .count();
+                if degree < 2 {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Explain why no Hamiltonian path can join `start` and `end`, if the
+    /// checkerboard coloring argument rules it out.
+    ///
+    /// Consecutive cells on a path alternate colors, so with an even number of
+    /// free cells both colors must be equally common and the endpoints must
+    /// differ in color. With an odd number, one color must have exactly one
+    /// extra cell and both endpoints must lie on that majority color.
+    fn infeasibility_reason(&self, start: Point, end: Point) -> Option<String> {
+        if self.is_visited(start) || self.is_visited(end) {
+            return Some("start and end must be free cells inside the grid".to_string());
+        }
+        if let Some(reason) = self.lock_infeasibility_reason(start, end) {
+            return Some(reason);
+        }
+        if let Some(length) = self.target_length {
+            return self.length_infeasibility_reason(start, end, length);
+        }
+        // Diagonal steps, and wrapping across an odd dimension, stay on one
+        // color, so the coloring argument is void
+        if !self.is_bipartite() {
+            return None;
+        }
+        let (even, odd) = self.free_parity_counts();
+        let total = even + odd;
+        let start_parity = cell_parity(start.row, start.col);
+        let end_parity = cell_parity(end.row, end.col);
+
+        if even == odd {
+            if self.open_end || start_parity != end_parity {
+                return None;
+            }
+            return Some(format!(
+                "a path alternates checkerboard colors, so covering an even number of cells ({}) \
+                 requires endpoints of different colors, but both have parity {}",
+                total, start_parity
+            ));
+        }
+
+        let majority = match even.abs_diff(odd) {
+            1 if even > odd => 0,
+            1 => 1,
+            _ => {
+                return Some(format!(
+                    "a path alternates checkerboard colors, so the color counts may differ by at \
+                     most one, but the free cells split {} even / {} odd",
+                    even, odd
+                ))
+            }
+        };
+        if start_parity == majority && (self.open_end || end_parity == majority) {
+            return None;
+        }
+        Some(format!(
+            "a path alternates checkerboard colors, so covering an odd number of cells ({}) \
+             requires {} on the majority color (parity {})",
+            total,
+            if self.open_end { "the start" } else { "both endpoints" },
+            majority
+        ))
+    }
+
+    /// Whether every step changes the checkerboard color: no diagonals, and
+    /// no wrapping across an odd dimension (whose edge cells share a color)
+    fn is_bipartite(&self) -> bool {
+        let odd_wrap = |n: i32| n >= 3 && n % 2 == 1;
+        !self.diagonals
+            && !(self.wrap && (odd_wrap(self.grid_size.rows) || odd_wrap(self.grid_size.cols)))
+    }
+
+    /// The cell one step from `p` in `dir`, across the edge in wrap mode
+    fn step(&self, p: Point, dir: Direction) -> Point {
+        if self.wrap {
+            return torus_step(p, dir, self.grid_size);
+        }
+        let (dr, dc) = dir.delta();
+        Point::new(p.row + dr, p.col + dc)
+    }
+
+    /// Direction of the step from `from` to `to`, across the edge in wrap mode
+    fn direction_to(&self, from: Point, to: Point) -> Option<Direction> {
+        if self.wrap {
+            torus_step_direction(from, to, self.grid_size)
+        } else {
+            step_direction(from, to)
+        }
+    }
+
+    /// Directions a step may take: orthogonal, plus diagonal in diagonal mode
+    fn directions(&self) -> &'static [Direction] {
+        if self.diagonals {
+            &ALL_DIRECTIONS
+        } else {
+            &ALL_DIRECTIONS[..4]
+        }
+    }
+
+    /// Whether stepping from `p` to its diagonal neighbor `next` would cross
+    /// a diagonal step between the two cells sharing that corner
+    fn crosses_path(&self, p: Point, next: Point) -> bool {
+        let a = Point::new(next.row, p.col);
+        let b = Point::new(p.row, next.col);
+        self.path
+            .windows(2)
+            .any(|w| (w[0] == a && w[1] == b) || (w[0] == b && w[1] == a))
+    }
+
+    fn get_neighbors(&self, p: Point) -> Vec<(Point, Direction)> {
+        self.directions()
+            .iter()
+            .filter_map(|&dir| {
+                let next = self.step(p, dir);
+                let free = self.is_valid(next) && !self.is_visited(next);
+                if free && !(dir.is_diagonal() && self.crosses_path(p, next)) {
+                    Some((next, dir))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+/// Find a Hamiltonian path from start to end using backtracking.
+/// On failure the state is unwound to an empty path.
+fn find_path_internal(
+    state: &mut PathState,
+    current: Point,
+    end: Point,
+) -> bool {
+    let mut stack = SearchStack::new(current);
+    match run_search(state, &mut stack, end) {
+        SearchStatus::Found => true,
+        SearchStatus::Exhausted => false,
+        SearchStatus::Suspended => {
+            // Unwinding after the search stopped isn't backtracking
+            let backtracks = state.backtracks;
+            while let Some(&p) = state.path.last() {
+                state.unvisit(p);
+            }
+            state.backtracks = backtracks;
+            false
+        }
+    }
+}
+
+/// One level of the explicit DFS stack: the neighbors of the matching path
+/// cell, in the order they are tried, and how many have been tried
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SearchFrame {
+    candidates: Vec<Point>,
+    next: usize,
+}
+
+/// Explicit DFS stack; `frames[i]` belongs to `state.path[i]`
+#[derive(Debug, Clone)]
+struct SearchStack {
+    frames: Vec<SearchFrame>,
+    /// Cell to enter before taking the next candidate: the start cell, or
+    /// the cell whose entry ran out of budget
+    pending: Option<Point>,
+}
+
+impl SearchStack {
+    fn new(start: Point) -> Self {
+        Self {
+            frames: Vec::new(),
+            pending: Some(start),
+        }
+    }
+}
+
+/// How `run_search` stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SearchStatus {
+    /// `state.path` holds a solution
+    Found,
+    /// Every branch was tried
+    Exhausted,
+    /// Out of iterations or cancelled; the stack can be run again once
+    /// `max_iterations` is raised
+    Suspended,
+}
+
+/// Result of trying to extend the path by one cell
+enum Step<F> {
+    Found,
+    /// The cell was rejected or is a dead end and is not on the path
+    Rejected,
+    /// The cell is on the path and this is its frame
+    Pushed(F),
+    Suspended,
+}
+
+/// Backtracking search driven by an explicit stack, so that it can stop at
+/// any node and continue later from exactly the same place
+fn run_search(state: &mut PathState, stack: &mut SearchStack, end: Point) -> SearchStatus {
+    if state.cancelled {
+        return SearchStatus::Suspended;
+    }
+
+    loop {
+        let current = match stack.pending.take() {
+            Some(p) => p,
+            None => {
+                let Some(frame) = stack.frames.last_mut() else {
+                    return SearchStatus::Exhausted;
+                };
+                match frame.candidates.get(frame.next) {
+                    Some(&p) => {
+                        frame.next += 1;
+                        p
+                    }
+                    None => {
+                        // All neighbors tried: backtrack
+                        stack.frames.pop();
+                        if let Some(&p) = state.path.last() {
+                            state.unvisit(p);
+                        }
+                        continue;
+                    }
+                }
+            }
+        };
+
+        match enter_cell(state, current, end) {
+            Step::Found => return SearchStatus::Found,
+            Step::Pushed(frame) => stack.frames.push(frame),
+            Step::Rejected => {}
+            Step::Suspended => {
+                stack.pending = Some(current);
+                return SearchStatus::Suspended;
+            }
+        }
+    }
+}
+
+/// Visit `current` if the constraints allow it and build its frame
+fn enter_cell(state: &mut PathState, current: Point, end: Point) -> Step<SearchFrame> {
+    if state.iterations >= state.max_iterations {
+        return Step::Suspended;
+    }
+    state.iterations += 1;
+    if state.poll_cancelled() {
+        return Step::Suspended;
+    }
+
+    // Prune branches that reach a later waypoint before an earlier one
+    if !state.waypoint_order_allows(current) {
+        return Step::Rejected;
+    }
+
+    state.visit(current);
+    if let Some(counts) = state.depth_counts.as_mut() {
+        counts[state.path.len() - 1] += 1;
+    }
+
+    // Check if we reached the end with the target number of cells
+    if state.open_end {
+        if state.target_reached() && state.locks_satisfied() {
+            return Step::Found;
+        }
+    } else if current == end {
+        if state.target_reached() && state.locks_satisfied() {
+            return Step::Found;
+        }
+        state.unvisit(current);
+        return Step::Rejected;
+    }
+
+    // Check if the path is already as long as the target but not at end
+    if state.path.len() >= state.target_cells() {
+        state.unvisit(current);
+        return Step::Rejected;
+    }
+
+    // Connectivity pruning: backtrack as soon as the rest can't be covered
+    if state.pruning && !state.remaining_region_ok(current, end) {
+        state.unvisit(current);
+        return Step::Rejected;
+    }
+
+    let candidates = ordered_neighbors(state, current).into_iter().map(|(p, _)| p).collect();
+    let candidates = state.lock_candidates(current, candidates);
+    Step::Pushed(SearchFrame { candidates, next: 0 })
+}
+
+/// Unvisited neighbors of `current` in the order the search tries them
+fn ordered_neighbors(state: &mut PathState, current: Point) -> Vec<(Point, Direction)> {
+    let mut neighbors = state.get_neighbors(current);
+
+    // With a seed, randomize the order first so the stable sort below breaks
+    // ties reproducibly instead of by Direction::all() order
+    if let Some(rng) = state.rng.as_mut() {
+        rng.shuffle(&mut neighbors);
+    }
+
+    // Prioritize cells with fewer unvisited neighbors (Warnsdorff's rule),
+    // then lower cell weights, then the style and the tie-break preference
+    let previous = match state.path.as_slice() {
+        [.., before, last] if *last == current => step_direction(*before, current),
+        _ => None,
+    };
+    neighbors.sort_by_key(|&(p, dir)| {
+        (
+            count_unvisited_neighbors(state, p),
+            state.cell_weight(p),
+            state.style_rank(p, dir),
+            state.tie_rank(previous, current, p, dir),
+        )
+    });
+    neighbors
+}
+
+/// The neighbors of `current` in search order, for visualizing the
+/// heuristic. `visited` are the cells already on the path (or blocked);
+/// `current` counts as visited too, as it does inside the search.
+fn next_candidates_internal(visited: &[Point], current: Point, grid_size: GridSize) -> Vec<Candidate> {
+    let mut state = PathState::new(grid_size, 0);
+    state.block_cells(visited);
+    state.block_cells(&[current]);
+
+    ordered_neighbors(&mut state, current)
+        .into_iter()
+        .map(|(p, dir)| Candidate {
+            row: p.row,
+            col: p.col,
+            direction: dir.to_string().to_string(),
+            unvisited_neighbors: count_unvisited_neighbors(&state, p),
+        })
+        .collect()
+}
+
+/// Enumerate Hamiltonian paths from `start` to `end`, handing each one to
+/// `on_solution`. Returns false once `on_solution` asks to stop or the search
+/// is cancelled. Runs on an explicit stack like `run_search`, so the grid
+/// size is not limited by the call stack.
+fn enumerate_paths_internal(
+    state: &mut PathState,
+    start: Point,
+    end: Point,
+    on_solution: &mut dyn FnMut(&[Point]) -> bool,
+) -> bool {
+    let mut frames: Vec<SearchFrame> = Vec::new();
+    let mut pending = Some(start);
+    loop {
+        if let Some(current) = pending.take() {
+            match enter_enumerated_cell(state, current, end, on_solution) {
+                Step::Pushed(frame) => frames.push(frame),
+                Step::Rejected => {}
+                _ => {
+                    unwind_frames(state, &mut frames);
+                    return false;
+                }
+            }
+        }
+        let Some(frame) = frames.last_mut() else {
+            return true;
+        };
+        match frame.candidates.get(frame.next) {
+            Some(&next) => {
+                frame.next += 1;
+                pending = Some(next);
+            }
+            None => {
+                frames.pop();
+                if let Some(&p) = state.path.last() {
+                    state.unvisit(p);
+                }
+            }
+        }
+    }
+}
+
+/// One node of `enumerate_paths_internal`: `Suspended` stops the whole
+/// enumeration. Past the iteration budget every cell is rejected, which
+/// lets the stack drain.
+fn enter_enumerated_cell(
+    state: &mut PathState,
+    current: Point,
+    end: Point,
+    on_solution: &mut dyn FnMut(&[Point]) -> bool,
+) -> Step<SearchFrame> {
+    if state.cancelled {
+        return Step::Suspended;
+    }
+
+    state.iterations += 1;
+
+    if state.iterations > state.max_iterations || state.poll_cancelled() {
+        return Step::Rejected;
+    }
+
+    if !state.waypoint_order_allows(current) {
+        return Step::Rejected;
+    }
+
+    state.visit(current);
+
+    if current == end || state.path.len() >= state.target_cells() {
+        let keep_going = !(current == end && state.target_reached()) || on_solution(&state.path);
+        state.unvisit(current);
+        return if keep_going { Step::Rejected } else { Step::Suspended };
+    }
+
+    if state.pruning && !state.remaining_region_ok(current, end) {
+        state.unvisit(current);
+        return Step::Rejected;
+    }
+
+    let candidates = ordered_neighbors(state, current).into_iter().map(|(p, _)| p).collect();
+    Step::Pushed(SearchFrame { candidates, next: 0 })
+}
+
+/// Take every cell with a frame off the path again
+fn unwind_frames<T>(state: &mut PathState, frames: &mut Vec<T>) {
+    while frames.pop().is_some() {
+        if let Some(&p) = state.path.last() {
+            state.unvisit(p);
+        }
+    }
+}
+
+/// Fewest-turn path found so far, with its turn count
+type BestPath = Option<(Vec<Point>, usize)>;
+
+/// Stack frame of `smoothest_path_internal`: the neighbors of a path cell
+/// with the step direction to each, how many were tried, and the direction
+/// the cell was entered in with the turns taken up to it
+struct TurnFrame {
+    neighbors: Vec<(Point, Direction)>,
+    next: usize,
+    entered: Option<Direction>,
+    turns: usize,
+}
+
+/// Branch-and-bound search for the Hamiltonian path with the fewest turns.
+///
+/// Like `enumerate_paths_internal`, but carries the turns taken so far and
+/// skips any step that would reach the best count already found, so only
+/// strictly smoother paths replace `best`. Returns false once the iteration
+/// budget runs out.
+fn smoothest_path_internal(
+    state: &mut PathState,
+    start: Point,
+    end: Point,
+    best: &mut BestPath,
+) -> bool {
+    let mut frames: Vec<TurnFrame> = Vec::new();
+    let mut pending = Some((start, None, 0));
+    loop {
+        if let Some((current, entered, turns)) = pending.take() {
+            match enter_turn_cell(state, current, end, entered, turns, best) {
+                Step::Pushed(frame) => frames.push(frame),
+                Step::Rejected => {}
+                _ => {
+                    unwind_frames(state, &mut frames);
+                    return false;
+                }
+            }
+        }
+        let Some(frame) = frames.last_mut() else {
+            return true;
+        };
+        match frame.neighbors.get(frame.next) {
+            Some(&(next, dir)) => {
+                frame.next += 1;
+                let next_turns = frame.turns + usize::from(frame.entered.is_some_and(|p| p != dir));
+                if best.as_ref().is_none_or(|(_, bound)| next_turns < *bound) {
+                    pending = Some((next, Some(dir), next_turns));
+                }
+            }
+            None => {
+                frames.pop();
+                if let Some(&p) = state.path.last() {
+                    state.unvisit(p);
+                }
+            }
+        }
+    }
+}
+
+/// One node of `smoothest_path_internal`; `Suspended` means out of budget
+fn enter_turn_cell(
+    state: &mut PathState,
+    current: Point,
+    end: Point,
+    entered: Option<Direction>,
+    turns: usize,
+    best: &mut BestPath,
+) -> Step<TurnFrame> {
+    state.iterations += 1;
+    if state.iterations > state.max_iterations {
+        return Step::Suspended;
+    }
+
+    state.visit(current);
+
+    if current == end || state.path.len() >= state.target_cells() {
+        if current == end && state.target_reached() {
+            *best = Some((state.path.clone(), turns));
+        }
+        state.unvisit(current);
+        return Step::Rejected;
+    }
+
+    if state.pruning && !state.remaining_region_ok(current, end) {
+        state.unvisit(current);
+        return Step::Rejected;
+    }
+
+    let neighbors = ordered_neighbors(state, current);
+    Step::Pushed(TurnFrame { neighbors, next: 0, entered, turns })
+}
+
+/// Lazy iterator over the Hamiltonian paths from `start` to `end`, for
+/// native callers that want solutions one at a time instead of collecting
+/// them like `find_road_paths` does.
+///
+/// Each `next()` resumes the explicit-stack search where the previous
+/// solution was found, so `take(100)` only does the work for 100 paths.
+/// Paths come out in the same order as the enumeration. Iteration ends when
+/// every branch is tried or `max_iterations` (shared by all paths) runs out.
+///
+/// Only compiled for native targets; WASM callers use `find_road_paths`.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct PathIterator {
+    state: PathState,
+    stack: SearchStack,
+    end: Point,
+    done: bool,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl PathIterator {
+    /// Iterate the paths that skip `blocked` and cover every other cell.
+    /// Endpoints off the grid or ruled out by parity yield nothing.
+    pub fn new(
+        start: Point,
+        end: Point,
+        grid_size: GridSize,
+        blocked: &[Point],
+        max_iterations: u32,
+    ) -> Self {
+        let options = SearchOptions {
+            blocked: blocked.to_vec(),
+            ..Default::default()
+        };
+        let done = endpoint_error(start, end, grid_size).is_some();
+        let state = PathState::with_options(grid_size, max_iterations, options);
+        let done = done || state.infeasibility_reason(start, end).is_some();
+        Self {
+            state,
+            stack: SearchStack::new(start),
+            end,
+            done,
+        }
+    }
+
+    /// Iterations spent so far, over all paths yielded
+    pub fn iterations(&self) -> u32 {
+        self.state.iterations
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Iterator for PathIterator {
+    type Item = Vec<Point>;
+
+    fn next(&mut self) -> Option<Vec<Point>> {
+        if self.done {
+            return None;
+        }
+        // The previous solution ended on `end` without pushing a frame for
+        // it; leave it so the search moves on to the next candidate
+        if self.state.path.last() == Some(&self.end) {
+            self.state.unvisit(self.end);
+        }
+        match run_search(&mut self.state, &mut self.stack, self.end) {
+            SearchStatus::Found => Some(self.state.path.clone()),
+            SearchStatus::Exhausted | SearchStatus::Suspended => {
+                self.done = true;
+                None
+            }
+        }
+    }
+}
+
+/// Breadth-first search from `start` over free cells, up to `steps` moves.
+/// Ignores the full-cover constraint; cells come out in BFS order.
+fn reachable_within(state: &PathState, start: Point, steps: u32) -> Vec<ReachableCell> {
+    if state.is_visited(start) {
+        return Vec::new();
+    }
+
+    let mut distance = state.grid_size.table(None);
+    distance[start.row as usize][start.col as usize] = Some(0);
+    let mut queue = std::collections::VecDeque::from([start]);
+    let mut cells = Vec::new();
+
+    while let Some(p) = queue.pop_front() {
+        let d = distance[p.row as usize][p.col as usize].unwrap_or(0);
+        cells.push(ReachableCell {
+            row: p.row,
+            col: p.col,
+            distance: d,
+        });
+        if d == steps {
+            continue;
+        }
+        for (next, _dir) in state.get_neighbors(p) {
+            let seen = &mut distance[next.row as usize][next.col as usize];
+            if seen.is_none() {
+                *seen = Some(d + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    cells
+}
+
+/// Breadth-first search for a fewest-steps route from `start` to `end` over
+/// free cells. Unlike the Hamiltonian search it needn't cover anything, so
+/// it serves as a reference route for hints.
+fn shortest_path_between(state: &PathState, start: Point, end: Point) -> ShortestPath {
+    let not_found = ShortestPath {
+        found: false,
+        path: Vec::new(),
+        length: 0,
+    };
+    if state.is_visited(start) || state.is_visited(end) {
+        return not_found;
+    }
+
+    // Cell each cell was first reached from (the start points to itself)
+    let mut parent = state.grid_size.table(None);
+    parent[start.row as usize][start.col as usize] = Some(start);
+    let mut queue = std::collections::VecDeque::from([start]);
+
+    while let Some(p) = queue.pop_front() {
+        if p == end {
+            let mut path = vec![end];
+            let mut cell = end;
+            while cell != start {
+                let Some(previous) = parent[cell.row as usize][cell.col as usize] else {
+                    break;
+                };
+                path.push(previous);
+                cell = previous;
+            }
+            path.reverse();
+            let length = path.len() - 1;
+            return ShortestPath {
+                found: true,
+                path,
+                length,
+            };
+        }
+        for (next, _dir) in state.get_neighbors(p) {
+            let seen = &mut parent[next.row as usize][next.col as usize];
+            if seen.is_none() {
+                *seen = Some(p);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    not_found
+}
+
+/// Articulation points of the free-cell graph by the DFS lowlink method,
+/// run on an explicit stack so large boards don't exhaust the call stack
+fn find_cut_cells(state: &PathState) -> CutCellReport {
+    let size = state.grid_size;
+    let mut discovered: Vec<Vec<Option<usize>>> = size.table(None);
+    let mut low = size.table(0);
+    // Children whose subtree hangs off the cell alone
+    let mut separated = size.table(0);
+    let mut is_root = size.table(false);
+    let mut components = 0;
+    let mut order = 0;
+
+    for row in 0..size.rows {
+        for col in 0..size.cols {
+            let root = Point::new(row, col);
+            if state.is_visited(root) || discovered[row as usize][col as usize].is_some() {
+                continue;
+            }
+            components += 1;
+            is_root[row as usize][col as usize] = true;
+            discovered[row as usize][col as usize] = Some(order);
+            low[row as usize][col as usize] = order;
+            order += 1;
+            // (cell, DFS parent, neighbors, neighbors tried)
+            let mut stack = vec![(root, None, state.get_neighbors(root), 0)];
+            while let Some((v, parent, neighbors, tried)) = stack.last_mut() {
+                let (v, parent) = (*v, *parent);
+                let (vr, vc) = (v.row as usize, v.col as usize);
+                if let Some(&(w, _)) = neighbors.get(*tried) {
+                    *tried += 1;
+                    let (wr, wc) = (w.row as usize, w.col as usize);
+                    match discovered[wr][wc] {
+                        Some(seen) if parent != Some(w) => low[vr][vc] = low[vr][vc].min(seen),
+                        Some(_) => {}
+                        None => {
+                            discovered[wr][wc] = Some(order);
+                            low[wr][wc] = order;
+                            order += 1;
+                            stack.push((w, Some(v), state.get_neighbors(w), 0));
+                        }
+                    }
+                    continue;
+                }
+                stack.pop();
+                if let Some(u) = parent {
+                    let (ur, uc) = (u.row as usize, u.col as usize);
+                    low[ur][uc] = low[ur][uc].min(low[vr][vc]);
+                    if discovered[ur][uc].is_some_and(|d| low[vr][vc] >= d) {
+                        separated[ur][uc] += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    let mut cut_cells = Vec::new();
+    for row in 0..size.rows {
+        for col in 0..size.cols {
+            let (r, c) = (row as usize, col as usize);
+            // A root is split by each child; another cell also keeps the
+            // piece it was reached from
+            let pieces = separated[r][c] + usize::from(!is_root[r][c]);
+            if pieces >= 2 {
+                cut_cells.push(CutCell { row, col, pieces });
+            }
+        }
+    }
+    let ruled_out = components > 1 || cut_cells.iter().any(|cell| cell.pieces >= 3);
+    CutCellReport {
+        components,
+        cut_cells,
+        ruled_out,
+    }
+}
+
+/// What is left to cover after `path` on a state whose obstacles are
+/// blocked. None unless `check_path` accepts the path and it avoids the
+/// obstacles.
+fn remaining_after(state: &mut PathState, path: &[Point]) -> Option<RemainingCells> {
+    if !check_path(path, state.grid_size).valid || path.iter().any(|&p| state.is_visited(p)) {
+        return None;
+    }
+    for &p in path {
+        state.visit(p);
+    }
+
+    let mut cells = Vec::new();
+    for row in 0..state.grid_size.rows {
+        for col in 0..state.grid_size.cols {
+            let p = Point::new(row, col);
+            if !state.is_visited(p) {
+                cells.push(p);
+            }
+        }
+    }
+
+    // Flood fill from the head over the uncovered cells
+    let head = *path.last()?;
+    let mut seen = state.grid_size.table(false);
+    let mut stack = vec![head];
+    let mut reached = 0;
+    while let Some(p) = stack.pop() {
+        for (next, _dir) in state.get_neighbors(p) {
+            if !seen[next.row as usize][next.col as usize] {
+                seen[next.row as usize][next.col as usize] = true;
+                reached += 1;
+                stack.push(next);
+            }
+        }
+    }
+
+    Some(RemainingCells {
+        all_reachable: reached == cells.len(),
+        cells,
+    })
+}
+
+fn count_unvisited_neighbors(state: &PathState, p: Point) -> usize {
+    state
+        .directions()
+        .iter()
+        .filter(|&&dir| {
+            let next = state.step(p, dir);
+            state.is_valid(next) && !state.is_visited(next)
+        })
+        .count()
+}
+
+// ============================================================================
+// Grid to Tiles Conversion
+// ============================================================================
+
+/// Which variant to use for corners, where curve and sharp tiles share the
+/// same connection signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum VariantPreference {
+    #[default]
+    PreferCurve,
+    PreferSharp,
+    /// Curve on even path indices, sharp on odd ones
+    Alternate,
+}
+
+impl VariantPreference {
+    fn parse(s: &str) -> Option<VariantPreference> {
+        match s {
+            "curve" => Some(VariantPreference::PreferCurve),
+            "sharp" => Some(VariantPreference::PreferSharp),
+            "alternate" => Some(VariantPreference::Alternate),
+            _ => None,
+        }
+    }
+
+    /// Re-pick a corner tile (curve or sharp) for the cell at `path_index`:
+    /// the tile of the preferred variant with the same mask. Other tiles,
+    /// and corners without a counterpart in the active set, are returned
+    /// unchanged.
+    fn apply(self, tile_id: String, path_index: usize) -> String {
+        let corner = find_tile(&tile_id)
+            .filter(|tile| matches!(tile.variant, TileVariant::Curve | TileVariant::Sharp));
+        let Some(tile) = corner else {
+            return tile_id;
+        };
+        let sharp = match self {
+            VariantPreference::PreferCurve => false,
+            VariantPreference::PreferSharp => true,
+            VariantPreference::Alternate => path_index % 2 == 1,
+        };
+        let variant = if sharp { TileVariant::Sharp } else { TileVariant::Curve };
+        tile_with_mask(variant.to_string(), tile.mask)
+            .map_or(tile_id, |preferred| preferred.id.to_string())
+    }
+}
+
+/// How the cells of an output grid are nested
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum GridOrder {
+    /// `grid[row][col]`
+    #[default]
+    RowMajor,
+    /// `grid[col][row]`
+    ColumnMajor,
+}
+
+impl GridOrder {
+    fn parse(s: &str) -> Option<GridOrder> {
+        match s {
+            "row_major" => Some(GridOrder::RowMajor),
+            "column_major" => Some(GridOrder::ColumnMajor),
+            _ => None,
+        }
+    }
+
+    /// Re-nest a row-major grid in this order. Only the indexing changes:
+    /// cells keep their tiles and connections, which name screen sides.
+    fn arrange(self, grid: Vec<Vec<Option<CellData>>>) -> Vec<Vec<Option<CellData>>> {
+        match self {
+            GridOrder::RowMajor => grid,
+            GridOrder::ColumnMajor => {
+                let cols = grid.first().map_or(0, Vec::len);
+                let mut columns = vec![Vec::with_capacity(grid.len()); cols];
+                for row in grid {
+                    for (column, cell) in columns.iter_mut().zip(row) {
+                        column.push(cell);
+                    }
+                }
+                columns
+            }
+        }
+    }
+}
+
+/// Options controlling how a path is tiled
+#[derive(Debug, Clone, Copy, Default)]
+struct TileOptions {
+    /// Permit tiles whose entry and exit ports differ (lane changes)
+    allow_lane_change: bool,
+    /// Corner variant to choose between equivalent curve and sharp tiles
+    variant_preference: VariantPreference,
+    /// Port set the start marker exits on, seeding the lane propagation;
+    /// `None` means P23 (outer lane)
+    start_port: Option<PortSet>,
+    /// Keep the whole route on this port set: the start exits on it and a
+    /// tile leaving it fails the tiling. Overrides `allow_lane_change`.
+    lane_lock: Option<PortSet>,
+    /// List each cell's `alternatives`
+    alternatives: bool,
+    /// Read steps across an edge as wraps onto the opposite edge (see
+    /// `torus_step`); such connections point off the grid
+    wrap: bool,
+}
+
+/// Convert a path to a grid with tile assignments
+/// Uses port propagation to ensure smooth connections between tiles
+///
+/// A one-cell path (e.g. the whole of a 1x1 grid) is both start and goal: it
+/// becomes a lone "start" marker without connections and counts as valid.
+fn path_to_tiles(path: &[Point], grid_size: GridSize, options: TileOptions) -> RoadGridResult {
+    if path.is_empty() || !path.iter().all(|&p| grid_size.contains(p)) {
+        return RoadGridResult::new(grid_size.table(None), false, None);
+    }
+    if let [only] = path {
+        let mut grid = grid_size.table(None);
+        let marker = CellData::new("start".to_string(), Vec::new(), 0);
+        grid[only.row as usize][only.col as usize] = Some(marker);
+        return RoadGridResult::new(grid, true, None);
+    }
+
+    tile_path_from(grid_size.table(None), path, grid_size, 0, None, options)
+}
+
+/// Tile `path[first..]` onto `grid`, keeping the cells of `path[..first]`
+/// as they are. `entry_port` is the lane the cell before `first` exits on
+/// (ignored for `first == 0`, which places the start marker). The cells
+/// being retiled are cleared first, so a failure leaves them empty.
+fn tile_path_from(
+    mut grid: Vec<Vec<Option<CellData>>>,
+    path: &[Point],
+    grid_size: GridSize,
+    first: usize,
+    entry_port: Option<PortSet>,
+    options: TileOptions,
+) -> RoadGridResult {
+    for p in &path[first..] {
+        grid[p.row as usize][p.col as usize] = None;
+    }
+
+    // Track the required entry port for the next tile (propagated from previous tile's exit)
+    let mut required_entry_port: Option<PortSet> = if first == 0 { None } else { entry_port };
+    let mut failure = None;
+    let mut reason = None;
+    let allow_lane_change = options.allow_lane_change && options.lane_lock.is_none();
+    let direction = |from, to| match options.wrap {
+        true => torus_step_direction(from, to, grid_size),
+        false => step_direction(from, to),
+    };
+
+    // Process each cell in the path
+    for i in first..path.len() {
+        let current = path[i];
+
+        // Determine entry and exit directions; a non-adjacent step makes the path invalid
+        let entry_dir = if i > 0 {
+            let prev = path[i - 1];
+            match direction(prev, current) {
+                Some(dir) => Some(dir),
+                None => return RoadGridResult::new(grid, false, None),
+            }
+        } else {
+            None
+        };
+
+        let exit_dir = if i < path.len() - 1 {
+            let next = path[i + 1];
+            match direction(current, next) {
+                Some(dir) => Some(dir),
+                None => return RoadGridResult::new(grid, false, None),
+            }
+        } else {
+            None
+        };
+
+        // Find matching tile with port propagation
+        let (tile_id, entry_port, exit_port) = match (entry_dir, exit_dir) {
+            (Some(entry), Some(exit)) => {
+                // Middle cell: needs entry and exit with port matching
+                let mut result = lookup_tile(entry, exit, required_entry_port, allow_lane_change);
+                // Geometry can force a lane change (e.g. onto a diagonal)
+                if let (Some(lane), Some((_, _, xp))) = (options.lane_lock, &result) {
+                    if *xp != lane {
+                        reason = Some(format!(
+                            "the lane lock to {} needs a lane change at path index {} ({} to {})",
+                            lane, i, entry, exit
+                        ));
+                        result = None;
+                    }
+                }
+                match result {
+                    Some((id, ep, xp)) => (Some(options.variant_preference.apply(id, i)), Some(ep), Some(xp)),
+                    None => {
+                        failure = Some(TileFailure {
+                            path_index: i,
+                            entry_direction: entry.to_string().to_string(),
+                            exit_direction: exit.to_string().to_string(),
+                            required_entry_port: required_entry_port.map(|p| p.to_string().to_string()),
+                        });
+                        (None, None, None)
+                    }
+                }
+            }
+            (None, Some(_exit)) => {
+                // Start cell: use marker, start with P23 (outer lane) unless told otherwise
+                let port = options.lane_lock.or(options.start_port).unwrap_or(PortSet::P23);
+                (Some("start".to_string()), None, Some(port))
+            }
+            (Some(_entry), None) => {
+                // End cell: use marker
+                (Some("goal".to_string()), required_entry_port, None)
+            }
+            (None, None) => {
+                (None, None, None)
+            }
+        };
+
+        if let Some(id) = tile_id {
+            let connections = match (entry_dir, exit_dir) {
+                (Some(entry), Some(exit)) => {
+                    vec![
+                        Connection::new(entry.opposite(), entry_port.unwrap_or(PortSet::P23)),
+                        Connection::new(exit, exit_port.unwrap_or(PortSet::P23)),
+                    ]
+                }
+                (None, Some(exit)) => {
+                    vec![Connection::new(exit, exit_port.unwrap_or(PortSet::P23))]
+                }
+                (Some(entry), None) => {
+                    vec![Connection::new(entry.opposite(), entry_port.unwrap_or(PortSet::P23))]
+                }
+                _ => vec![],
+            };
+
+            let mut cell = CellData::new(id, connections, i);
+            if options.alternatives {
+                cell.alternatives = tile_alternatives(&cell.tile_id);
+            }
+            grid[current.row as usize][current.col as usize] = Some(cell);
+        } else {
+            // 一つでもタイルが見つからなければ無効扱い
+            let mut result = RoadGridResult::new(grid, false, failure);
+            result.reason = reason;
+            return result;
+        }
+
+        // Propagate exit port to next tile's required entry port
+        required_entry_port = exit_port;
+    }
+
+    // Wrapped connections point off the grid, which validate_grid flags
+    #[cfg(feature = "fuzz-checks")]
+    if !options.wrap {
+        let mismatches = validate_grid(&grid, grid_size);
+        assert!(mismatches.is_empty(), "tiled {:?} with mismatched ports: {:?}", path, mismatches);
+    }
+    RoadGridResult::new(grid, true, None)
+}
+
+/// Tile a closed loop: every cell gets both connections, including the
+/// closing edge from the last cell back to the first. No start/goal markers.
+///
+/// Port propagation wraps around, so the first cell's entry port must match
+/// the last cell's exit port; both starting lanes are tried.
+fn cycle_to_tiles(cycle: &[Point], grid_size: GridSize) -> RoadGridResult {
+    let empty = || grid_size.table(None);
+    let n = cycle.len();
+    let closed = n >= 4
+        && cycle.iter().all(|&p| grid_size.contains(p))
+        && (0..n).all(|i| is_adjacent(cycle[i], cycle[(i + 1) % n]));
+    if !closed {
+        return RoadGridResult::new(empty(), false, None);
+    }
+
+    let mut best = empty();
+
+    for start_port in [PortSet::P23, PortSet::P12] {
+        let mut grid: Vec<Vec<Option<CellData>>> = empty();
+        let mut required_entry_port = Some(start_port);
+        let mut complete = true;
+
+        for i in 0..n {
+            let current = cycle[i];
+            // Adjacency was checked above, so both directions exist
+            let (Some(entry), Some(exit)) = (
+                get_direction(cycle[(i + n - 1) % n], current),
+                get_direction(current, cycle[(i + 1) % n]),
+            ) else {
+                complete = false;
+                break;
+            };
+
+            let Some((tile_id, entry_port, exit_port)) =
+                lookup_tile(entry, exit, required_entry_port, false)
+            else {
+                complete = false;
+                break;
+            };
+
+            let connections = vec![
+                Connection::new(entry.opposite(), entry_port),
+                Connection::new(exit, exit_port),
+            ];
+            grid[current.row as usize][current.col as usize] = Some(CellData::new(tile_id, connections, i));
+            required_entry_port = Some(exit_port);
+        }
+
+        // The last exit feeds the first entry
+        if complete && required_entry_port == Some(start_port) {
+            return RoadGridResult::new(grid, true, None);
+        }
+        if start_port == PortSet::P23 {
+            best = grid;
+        }
+    }
+
+    RoadGridResult::new(best, false, None)
+}
+
+/// Tile several disjoint paths onto one grid, each like `path_to_tiles` with
+/// its own lane propagation, tagging every cell with its road's index.
+///
+/// Paths that share a cell are rejected before tiling, with the conflict
+/// cell; a path leaving the grid or failing to tile makes the result invalid.
+fn paths_to_tiles(
+    paths: &[Vec<Point>],
+    grid_size: GridSize,
+    options: TileOptions,
+) -> MultiRoadResult {
+    let mut result = MultiRoadResult {
+        grid: grid_size.table(None),
+        valid: false,
+        conflict: None,
+        failed_road: None,
+        failure: None,
+    };
+    if paths.is_empty() {
+        return result;
+    }
+
+    let mut owner: Vec<Vec<Option<usize>>> = grid_size.table(None);
+    for (road_id, path) in paths.iter().enumerate() {
+        for &p in path {
+            if !grid_size.contains(p) {
+                result.failed_road = Some(road_id);
+                return result;
+            }
+            let cell = &mut owner[p.row as usize][p.col as usize];
+            match *cell {
+                Some(other) if other != road_id => {
+                    result.conflict = Some(RoadConflict {
+                        row: p.row,
+                        col: p.col,
+                        road_ids: [other, road_id],
+                    });
+                    return result;
+                }
+                _ => *cell = Some(road_id),
+            }
+        }
+    }
+
+    for (road_id, path) in paths.iter().enumerate() {
+        let road = path_to_tiles(path, grid_size, options);
+        for (row, cells) in road.grid.into_iter().enumerate() {
+            for (col, cell) in cells.into_iter().enumerate() {
+                if let Some(mut cell) = cell {
+                    cell.road_id = road_id;
+                    result.grid[row][col] = Some(cell);
+                }
+            }
+        }
+        if !road.valid {
+            result.failed_road = Some(road_id);
+            result.failure = road.failure;
+            return result;
+        }
+    }
+    result.valid = true;
+    result
+}
+
+/// Extend an already tiled path by one cell, retiling only what changes:
+/// the old goal becomes a middle tile and `new_point` becomes the goal.
+///
+/// Returns None when `new_point` is outside the grid, already on the path or
+/// not adjacent to the path's last cell, or when `grid` does not match the
+/// grid size.
+fn append_to_tiles(
+    grid: Vec<Vec<Option<CellData>>>,
+    path: &[Point],
+    new_point: Point,
+    grid_size: GridSize,
+) -> Option<AppendResult> {
+    let &last = path.last()?;
+    let exit = get_direction(last, new_point)?;
+    let shape_ok = grid.len() == grid_size.rows as usize
+        && grid.iter().all(|row| row.len() == grid_size.cols as usize);
+    if !shape_ok || !grid_size.contains(new_point) || path.contains(&new_point) {
+        return None;
+    }
+
+    // A single start cell has nothing to reuse
+    if path.len() == 1 {
+        let full = [last, new_point];
+        let result = path_to_tiles(&full, grid_size, TileOptions::default());
+        let required_entry_port = result.valid.then(|| PortSet::P23.to_string().to_string());
+        return Some(AppendResult {
+            grid: result.grid,
+            valid: result.valid,
+            failure: result.failure,
+            required_entry_port,
+        });
+    }
+
+    let mut grid = grid;
+    let entry = get_direction(path[path.len() - 2], last)?;
+    // The old goal was entered on the lane recorded in its only connection
+    let entry_port = grid[last.row as usize][last.col as usize]
+        .as_ref()
+        .and_then(|cell| cell.connections.first())
+        .and_then(|conn| conn.ports.parse().ok());
+
+    let Some((tile_id, ep, xp)) = entry_port.and_then(|port| lookup_tile(entry, exit, Some(port), false)) else {
+        let failure = TileFailure {
+            path_index: path.len() - 1,
+            entry_direction: entry.to_string().to_string(),
+            exit_direction: exit.to_string().to_string(),
+            required_entry_port: entry_port.map(|p| p.to_string().to_string()),
+        };
+        return Some(AppendResult {
+            grid,
+            valid: false,
+            failure: Some(failure),
+            required_entry_port: None,
+        });
+    };
+
+    grid[last.row as usize][last.col as usize] = Some(CellData::new(
+        tile_id,
+        vec![Connection::new(entry.opposite(), ep), Connection::new(exit, xp)],
+        path.len() - 1,
+    ));
+    grid[new_point.row as usize][new_point.col as usize] = Some(CellData::new(
+        "goal".to_string(),
+        vec![Connection::new(exit.opposite(), xp)],
+        path.len(),
+    ));
+
+    Some(AppendResult {
+        grid,
+        valid: true,
+        failure: None,
+        required_entry_port: Some(xp.to_string().to_string()),
+    })
+}
+
+/// Reverse `path[from..=to]` in an already tiled path, retiling only from
+/// the cell before the segment (whose exit now turns toward the old `to`)
+/// onward; earlier cells keep their tiles, and the lane propagation resumes
+/// from the exit port stored on the last of them.
+///
+/// Returns None when the indices are out of order or past the end, the path
+/// is not one `check_path` accepts, the reversed segment no longer joins
+/// its neighbors, or `grid` lacks the tiles of the path's kept cells.
+fn reverse_segment_tiles(
+    grid: Vec<Vec<Option<CellData>>>,
+    path: &[Point],
+    from: usize,
+    to: usize,
+    grid_size: GridSize,
+) -> Option<SegmentReversal> {
+    let shape_ok = grid.len() == grid_size.rows as usize
+        && grid.iter().all(|row| row.len() == grid_size.cols as usize);
+    if from > to || to >= path.len() || !shape_ok || !check_path(path, grid_size).valid {
+        return None;
+    }
+    let mut reversed = path.to_vec();
+    reversed[from..=to].reverse();
+    let seams_ok = (from == 0 || is_adjacent(reversed[from - 1], reversed[from]))
+        && (to + 1 == path.len() || is_adjacent(reversed[to], reversed[to + 1]));
+    if !seams_ok {
+        return None;
+    }
+
+    let first = from.saturating_sub(1);
+    let entry_port = match first {
+        0 => None,
+        _ => {
+            let kept = path[first - 1];
+            let cell = grid[kept.row as usize][kept.col as usize].as_ref()?;
+            Some(cell.connections.last()?.ports.parse().ok()?)
+        }
+    };
+    let options = TileOptions::default();
+    let result = tile_path_from(grid, &reversed, grid_size, first, entry_port, options);
+    Some(SegmentReversal {
+        path: reversed,
+        result,
+    })
+}
+
+fn is_adjacent(a: Point, b: Point) -> bool {
+    get_direction(a, b).is_some()
+}
+
+/// Find tile with port constraint for smooth connections
+/// Returns (tile_id, entry_port, exit_port)
+///
+/// `entry` and `exit` are directions of travel, so the road comes in through
+/// the `entry.opposite()` side. Going straight through (`entry == exit`)
+/// takes a straight tile; a 90° turn takes a curve or sharp corner.
+///
+/// With `allow_lane_change`, the first tile whose entry port matches is taken
+/// even if it exits on the other lane; the caller propagates the exit port.
+/// Diagonal connections meet at a corner with a single junction, so tiles
+/// with one never need to keep the lane.
+fn find_tile_with_port_constraint(
+    tiles: &[TileDefinition],
+    entry: Direction,
+    exit: Direction,
+    required_entry_port: Option<PortSet>,
+    allow_lane_change: bool,
+) -> Option<(String, PortSet, PortSet)> {
+    let entry_from = entry.opposite();
+
+    // Doubling back would enter and leave through the same side
+    if entry_from == exit {
+        return None;
+    }
+    let through = entry == exit;
+    let fits_shape = |tile: &TileDefinition| match tile.variant {
+        TileVariant::Straight => through,
+        TileVariant::Curve | TileVariant::Sharp => !through,
+        _ => true,
+    };
+
+    // 1) 「入るポート＝出るポート」で必ず同一レーンを維持するパターンのみ採用
+    //    (lane change mode lifts this restriction)
+    for tile in tiles.iter().filter(|tile| !tile.is_junction() && fits_shape(tile)) {
+        if tile.has_direction(entry_from) && tile.has_direction(exit) {
+            if let (Some(ep), Some(xp)) = (tile.get_connection(entry_from), tile.get_connection(exit)) {
+                let keeps_lane = ep == xp || entry_from.is_diagonal() || exit.is_diagonal();
+                if (allow_lane_change || keeps_lane) && required_entry_port.is_none_or(|req| ep == req) {
+                    return Some((tile.id.to_string(), ep, xp));
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Ids of the other path tiles of the active set sharing `tile_id`'s mask,
+/// i.e. the same sides and ports; empty for markers and junctions
+fn tile_alternatives(tile_id: &str) -> Vec<String> {
+    let Some(chosen) = find_tile(tile_id).filter(|tile| !tile.is_junction()) else {
+        return Vec::new();
+    };
+    tile_table()
+        .tiles
+        .iter()
+        .filter(|tile| tile.mask == chosen.mask && tile.id != chosen.id)
+        .map(|tile| tile.id.to_string())
+        .collect()
+}
+
+/// Key of the tile lookup index: (entry_from, exit, required_entry_port, allow_lane_change)
+type TileKey = (Direction, Direction, Option<PortSet>, bool);
+
+/// Tile of the active set with the given id (markers like "start" are not
+/// tiles)
+fn find_tile(id: &str) -> Option<TileDefinition> {
+    let table = tile_table();
+    table.by_id.get(id).map(|&i| table.tiles[i].clone())
+}
+
+/// First tile of the named variant whose mask is exactly `mask`
+fn tile_with_mask(variant: &str, mask: u16) -> Option<TileDefinition> {
+    tile_table()
+        .tiles
+        .iter()
+        .find(|tile| tile.variant.to_string() == variant && tile.mask == mask)
+        .cloned()
+}
+
+/// Whether tile `b` may sit next to tile `a` on its `dir` side: `a` has a
+/// connection that way and `b` answers it on the shared edge with the same
+/// ports
+fn tiles_fit(a: &TileDefinition, b: &TileDefinition, dir: Direction) -> bool {
+    a.get_connection(dir).is_some_and(|ports| b.get_connection(dir.opposite()) == Some(ports))
+}
+
+/// Indexed equivalent of `find_tile_with_port_constraint` over the active tiles
+fn lookup_tile(
+    entry: Direction,
+    exit: Direction,
+    required_entry_port: Option<PortSet>,
+    allow_lane_change: bool,
+) -> Option<(String, PortSet, PortSet)> {
+    // The table's index holds the answers for every key, built once per set
+    tile_table()
+        .index
+        .get(&(entry.opposite(), exit, required_entry_port, allow_lane_change))
+        .cloned()
+}
+
+// ============================================================================
+// Grid Validation
+// ============================================================================
+
+/// Check that every connection in a grid is mirrored by its neighbor.
+///
+/// For each connection of each occupied cell, the neighbor in that direction
+/// must exist and have a connection pointing back with the same ports.
+fn validate_grid(grid: &[Vec<Option<CellData>>], grid_size: GridSize) -> Vec<GridMismatch> {
+    let cell_at = |p: Point| -> Option<&CellData> {
+        if !grid_size.contains(p) {
+            return None;
+        }
+        grid.get(p.row as usize)?.get(p.col as usize)?.as_ref()
+    };
+
+    let mut mismatches = Vec::new();
+    for row in 0..grid_size.rows {
+        for col in 0..grid_size.cols {
+            let Some(cell) = cell_at(Point::new(row, col)) else {
+                continue;
+            };
+            for conn in &cell.connections {
+                let mismatch = |kind: &str| GridMismatch {
+                    row,
+                    col,
+                    direction: conn.direction.clone(),
+                    kind: kind.to_string(),
+                };
+                let Ok(dir) = conn.direction.parse::<Direction>() else {
+                    mismatches.push(mismatch("unknown_direction"));
+                    continue;
+                };
+                if conn.ports.parse::<PortSet>().is_err() {
+                    mismatches.push(mismatch("unknown_ports"));
+                    continue;
+                }
+                let (dr, dc) = dir.delta();
+                let neighbor = Point::new(row + dr, col + dc);
+                if !grid_size.contains(neighbor) {
+                    mismatches.push(mismatch("out_of_bounds"));
+                    continue;
+                }
+                let Some(other) = cell_at(neighbor) else {
+                    mismatches.push(mismatch("missing_neighbor"));
+                    continue;
+                };
+                let back = dir.opposite();
+                match other.connections.iter().find(|c| c.direction.parse() == Ok(back)) {
+                    None => mismatches.push(mismatch("missing_connection")),
+                    Some(c) if c.ports != conn.ports => mismatches.push(mismatch("port_mismatch")),
+                    Some(_) => {}
+                }
+            }
+        }
+    }
+    mismatches
+}
+
+/// Check that `path` is a simple path: every point inside the grid, each
+/// step orthogonally adjacent and no cell repeated
+fn check_path(path: &[Point], grid_size: GridSize) -> PathCheck {
+    let bad = |index: Option<usize>, reason: String| PathCheck {
+        valid: false,
+        first_bad_index: index,
+        reason: Some(reason),
+    };
+    if path.is_empty() {
+        return bad(None, "the path is empty".to_string());
+    }
+
+    let mut seen = HashMap::new();
+    for (i, &p) in path.iter().enumerate() {
+        if !grid_size.contains(p) {
+            return bad(Some(i), format!("({}, {}) is outside the grid", p.row, p.col));
+        }
+        if let Some(first) = seen.insert(p, i) {
+            return bad(Some(i), format!("({}, {}) repeats point {}", p.row, p.col, first));
+        }
+        if i > 0 && !is_adjacent(path[i - 1], p) {
+            let prev = path[i - 1];
+            return bad(
+                Some(i),
+                format!("({}, {}) is not adjacent to ({}, {})", p.row, p.col, prev.row, prev.col),
+            );
+        }
+    }
+
+    PathCheck {
+        valid: true,
+        first_bad_index: None,
+        reason: None,
+    }
+}
+
+/// Whether `path` covers every cell of the grid outside `blocked` exactly
+/// once: `check_path` accepts it, it avoids the obstacles and leaves no free
+/// cell out. Obstacles outside the grid are ignored.
+fn is_hamiltonian(path: &[Point], grid_size: GridSize, blocked: &[Point]) -> bool {
+    let Some(area) = grid_size.cell_count() else {
+        return false;
+    };
+    if !check_path(path, grid_size).valid {
+        return false;
+    }
+    let blocked: HashSet<Point> =
+        blocked.iter().copied().filter(|&p| grid_size.contains(p)).collect();
+    let free = area - blocked.len();
+    path.len() == free && path.iter().all(|p| !blocked.contains(p))
+}
+
+/// Draw a path with box-drawing characters, one text line per grid row.
+///
+/// The first cell is 'S', the last 'G' (a single-cell path is just 'S'),
+/// cells in between show how the road runs through them (─ │ ┌ ┐ └ ┘) and
+/// cells off the path are '.'. None unless `check_path` accepts the path.
+fn render_path_ascii(path: &[Point], grid_size: GridSize) -> Option<String> {
+    use Direction::*;
+
+    if !check_path(path, grid_size).valid {
+        return None;
+    }
+    let mut canvas = grid_size.table('.');
+    for (i, &p) in path.iter().enumerate() {
+        let glyph = if i == 0 {
+            'S'
+        } else if i == path.len() - 1 {
+            'G'
+        } else {
+            let back = get_direction(p, path[i - 1])?;
+            let ahead = get_direction(p, path[i + 1])?;
+            let has = |dir| back == dir || ahead == dir;
+            match (has(Up), has(Right), has(Down), has(Left)) {
+                (true, false, true, false) => '│',
+                (false, true, false, true) => '─',
+                (false, true, true, false) => '┌',
+                (false, false, true, true) => '┐',
+                (true, true, false, false) => '└',
+                _ => '┘',
+            }
+        };
+        canvas[p.row as usize][p.col as usize] = glyph;
+    }
+    let lines: Vec<String> = canvas.into_iter().map(|row| row.into_iter().collect()).collect();
+    Some(lines.join("\n"))
+}
+
+/// SVG `<path>` element tracing the centerline of every road in `grid`,
+/// with cells `cell_size` units wide. Cells are visited in `path_index`
+/// order, one subpath per `road_id`: the line runs from cell center to edge
+/// midpoint, rounds curve tiles with a quadratic Bézier through the cell
+/// center, bends sharp tiles at the center and crosses the rest straight.
+///
+/// None for a grid of the wrong shape, a non-positive `cell_size`, no road
+/// cells, or a road whose indices have gaps or whose consecutive cells
+/// aren't adjacent.
+fn grid_svg_path(
+    grid: &[Vec<Option<CellData>>],
+    grid_size: GridSize,
+    cell_size: f64,
+) -> Option<String> {
+    let shape_ok = grid_size.dimension_error().is_none()
+        && grid.len() == grid_size.rows as usize
+        && grid.iter().all(|cells| cells.len() == grid_size.cols as usize);
+    if !shape_ok || cell_size.is_nan() || cell_size <= 0.0 {
+        return None;
+    }
+
+    let mut cells: Vec<(usize, usize, Point, &str)> = Vec::new();
+    for (row, line) in grid.iter().enumerate() {
+        for (col, cell) in line.iter().enumerate() {
+            if let Some(cell) = cell {
+                let p = Point::new(row as i32, col as i32);
+                cells.push((cell.road_id, cell.path_index, p, cell.tile_id.as_str()));
+            }
+        }
+    }
+    if cells.is_empty() {
+        return None;
+    }
+    cells.sort_by_key(|&(road_id, path_index, _, _)| (road_id, path_index));
+
+    let center = |p: Point| ((p.col as f64 + 0.5) * cell_size, (p.row as f64 + 0.5) * cell_size);
+    let mut commands = Vec::new();
+    for road in cells.chunk_by(|a, b| a.0 == b.0) {
+        if road.iter().enumerate().any(|(i, &(_, path_index, _, _))| path_index != i) {
+            return None;
+        }
+        if road.windows(2).any(|pair| step_direction(pair[0].2, pair[1].2).is_none()) {
+            return None;
+        }
+
+        let (x, y) = center(road[0].2);
+        commands.push(format!("M {} {}", x, y));
+        for (i, pair) in road.windows(2).enumerate() {
+            let ((ax, ay), (bx, by)) = (center(pair[0].2), center(pair[1].2));
+            let (mx, my) = ((ax + bx) / 2.0, (ay + by) / 2.0);
+            // The endpoints carry markers; look up what the rest are drawn as
+            let variant = find_tile(pair[0].3).map(|tile| tile.variant).filter(|_| i > 0);
+            match variant {
+                Some(TileVariant::Curve) => {
+                    commands.push(format!("Q {} {} {} {}", ax, ay, mx, my));
+                    continue;
+                }
+                Some(TileVariant::Sharp) => commands.push(format!("L {} {}", ax, ay)),
+                _ => {}
+            }
+            commands.push(format!("L {} {}", mx, my));
+        }
+        if let [_, .., last] = road {
+            let (x, y) = center(last.2);
+            commands.push(format!("L {} {}", x, y));
+        }
+    }
+    Some(format!("<path d=\"{}\" fill=\"none\"/>", commands.join(" ")))
+}
+
+/// Direction of the single step from `from` to `to`, or None when the points
+/// are not orthogonally adjacent
+fn get_direction(from: Point, to: Point) -> Option<Direction> {
+    let dr = to.row - from.row;
+    let dc = to.col - from.col;
+
+    match (dr, dc) {
+        (-1, 0) => Some(Direction::Up),
+        (1, 0) => Some(Direction::Down),
+        (0, -1) => Some(Direction::Left),
+        (0, 1) => Some(Direction::Right),
+        _ => None,
+    }
+}
+
+/// Like `get_direction`, but diagonal steps are allowed too
+fn step_direction(from: Point, to: Point) -> Option<Direction> {
+    let delta = (to.row - from.row, to.col - from.col);
+    Direction::with_diagonals().into_iter().find(|dir| dir.delta() == delta)
+}
+
+/// The cell one orthogonal step from `p` on a torus: stepping off an edge
+/// enters the opposite edge. Only axes of three or more cells wrap; on
+/// shorter ones the far edge is already a neighbor (or the cell itself).
+fn torus_step(p: Point, dir: Direction, grid_size: GridSize) -> Point {
+    let (dr, dc) = dir.delta();
+    let wrap = |x: i32, n: i32| if n >= 3 { x.rem_euclid(n) } else { x };
+    Point::new(wrap(p.row + dr, grid_size.rows), wrap(p.col + dc, grid_size.cols))
+}
+
+/// Direction of the orthogonal step from `from` to `to` on a torus
+fn torus_step_direction(from: Point, to: Point, grid_size: GridSize) -> Option<Direction> {
+    Direction::all().iter().copied().find(|&dir| torus_step(from, dir, grid_size) == to)
+}
+
+/// Steps between two coordinates `diff` apart along a torus axis of `n`
+/// cells, going whichever way round is shorter
+fn torus_distance(diff: u32, n: i32) -> u32 {
+    if n >= 3 {
+        diff.min(n as u32 - diff)
+    } else {
+        diff
+    }
+}
+
+// ============================================================================
+// Path Statistics
+// ============================================================================
+
+/// Shape metrics of a path, for scoring how a route looks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathStats {
+    /// Number of cells
+    pub length: usize,
+    /// Direction changes between consecutive steps
+    pub turns: usize,
+    /// Interior cells entered and left in the same direction
+    pub straights: usize,
+    /// Interior cells where the direction changes (curve/sharp tiles)
+    pub corners: usize,
+    /// Cells covered by the longest straight segment, endpoints included
+    pub longest_straight_run: usize,
+    /// Pairs of path cells that are orthogonally adjacent without being
+    /// consecutive: the road running right alongside itself. Fixed for paths
+    /// covering a whole rectangle, so it tells apart partial paths only.
+    pub self_adjacencies: usize,
+}
+
+/// Walk the steps of `path` and count direction changes.
+/// Returns None if two consecutive cells are not adjacent.
+fn compute_path_stats(path: &[Point]) -> Option<PathStats> {
+    let directions = path
+        .windows(2)
+        .map(|w| get_direction(w[0], w[1]))
+        .collect::<Option<Vec<_>>>()?;
+
+    let mut stats = PathStats {
+        length: path.len(),
+        longest_straight_run: path.len().min(1),
+        ..Default::default()
+    };
+    let mut run = 0;
+    for (i, dir) in directions.iter().enumerate() {
+        if i > 0 && directions[i - 1] != *dir {
+            stats.turns += 1;
+            run = 0;
+        }
+        run += 1;
+        stats.longest_straight_run = stats.longest_straight_run.max(run + 1);
+    }
+    // On a grid path every turn happens at exactly one interior cell
+    stats.corners = stats.turns;
+    stats.straights = path.len().saturating_sub(2) - stats.corners;
+
+    // Look right and down from each cell so every pair is seen once
+    let index: HashMap<Point, usize> = path.iter().enumerate().map(|(i, &p)| (p, i)).collect();
+    stats.self_adjacencies = path
+        .iter()
+        .enumerate()
+        .flat_map(|(i, p)| {
+            [Point::new(p.row, p.col + 1), Point::new(p.row + 1, p.col)]
+                .into_iter()
+                .filter_map(|q| index.get(&q))
+                .filter(move |&&j| j.abs_diff(i) != 1)
+        })
+        .count();
+
+    Some(stats)
+}
+
+/// A found path with, for each of its steps, whether the move was forced
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForcedMoveReport {
+    pub result: PathResult,
+    /// One entry per step: true when the cell left had exactly one unvisited
+    /// neighbor at that point (empty when no path was found)
+    pub forced: Vec<bool>,
+    /// Number of true entries in `forced`
+    pub forced_moves: usize,
+}
+
+/// Search a path and replay it, marking the steps taken from a cell with a
+/// single unvisited neighbor. Mostly-forced paths are the easier puzzles.
+fn forced_moves(
+    start: Point,
+    end: Point,
+    grid_size: GridSize,
+    max_iterations: u32,
+) -> ForcedMoveReport {
+    let result = solve_road_path(start, end, grid_size, max_iterations, SearchOptions::default());
+    let mut state = PathState::new(grid_size, 0);
+    let mut forced = Vec::with_capacity(result.path.len().saturating_sub(1));
+    for (i, &p) in result.path.iter().enumerate() {
+        if i > 0 {
+            forced.push(count_unvisited_neighbors(&state, result.path[i - 1]) == 1);
+        }
+        state.visit(p);
+    }
+    let forced_moves = forced.iter().filter(|&&f| f).count();
+    ForcedMoveReport { result, forced, forced_moves }
+}
+
+/// A path after `simplify_turns`, with its turn counts before and after
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimplifiedPath {
+    pub path: Vec<Point>,
+    pub turns_before: usize,
+    pub turns_after: usize,
+    /// Moves applied, each lowering the turn count
+    pub swaps: usize,
+}
+
+/// Remove turns from a path with 2-opt moves, which trade two steps along
+/// opposite sides of a unit square for the other two sides:
+///
+/// - steps running the same way: reversing the stretch between them gives
+///   another path
+/// - steps running opposite ways: the stretch between them closes into a
+///   cycle, which a second such swap, with a step of the path beside it,
+///   splices back in
+///
+/// Either way the cells and endpoints stay the same. Moves that lower the
+/// turn count are kept until none is left, so the result is a local
+/// minimum, not the smoothest path (see `smoothest_path`). Returns None for
+/// a path with non-adjacent steps or a repeated cell.
+fn simplify_turns(path: &[Point]) -> Option<SimplifiedPath> {
+    let turns_before = compute_path_stats(path)?.turns;
+    if path.iter().collect::<HashSet<_>>().len() != path.len() {
+        return None;
+    }
+
+    let mut path = path.to_vec();
+    let mut turns = turns_before;
+    let mut swaps = 0;
+    while let Some((better, fewer)) = smoother_swap(&path, turns) {
+        path = better;
+        turns = fewer;
+        swaps += 1;
+    }
+
+    Some(SimplifiedPath {
+        path,
+        turns_before,
+        turns_after: turns,
+        swaps,
+    })
+}
+
+/// Direction changes along an orthogonal path, without the rest of
+/// `compute_path_stats`
+fn count_turns(path: &[Point]) -> usize {
+    path.windows(3)
+        .filter(|w| get_direction(w[0], w[1]) != get_direction(w[1], w[2]))
+        .count()
+}
+
+/// The two sides of a unit square next to the step from `from` to `to`,
+/// as the cells beside `from` and `to` on each side
+fn square_sides(from: Point, to: Point) -> impl Iterator<Item = (Point, Point)> {
+    let step = get_direction(from, to);
+    Direction::all()
+        .into_iter()
+        .filter(move |&side| step.is_some_and(|dir| side != dir && side != dir.opposite()))
+        .map(move |side| {
+            let (dr, dc) = side.delta();
+            let beside = |p: Point| Point::new(p.row + dr, p.col + dc);
+            (beside(from), beside(to))
+        })
+}
+
+/// The first 2-opt move (see `simplify_turns`) giving fewer than `turns`
+/// turns, with the new path and its turn count
+fn smoother_swap(path: &[Point], turns: usize) -> Option<(Vec<Point>, usize)> {
+    let index: HashMap<Point, usize> = path.iter().enumerate().map(|(i, &p)| (p, i)).collect();
+    let fewer = |candidate: Vec<Point>| {
+        let candidate_turns = count_turns(&candidate);
+        (candidate_turns < turns).then_some((candidate, candidate_turns))
+    };
+
+    for i in 0..path.len().saturating_sub(1) {
+        for (c, d) in square_sides(path[i], path[i + 1]) {
+            let (Some(&ic), Some(&id)) = (index.get(&c), index.get(&d)) else {
+                continue;
+            };
+            if id == ic + 1 && ic > i {
+                let mut reversed = path.to_vec();
+                reversed[i + 1..=ic].reverse();
+                if let Some(found) = fewer(reversed) {
+                    return Some(found);
+                }
+            } else if ic == id + 1 && id > i {
+                // path[i] joins c and path[i + 1]..=d closes into a cycle
+                let main: Vec<Point> = path[..=i].iter().chain(&path[ic..]).copied().collect();
+                let main_index = main.iter().enumerate().map(|(k, &p)| (p, k)).collect();
+                let found = spliced_cycles(&main, &main_index, &path[i + 1..=id]).find_map(fewer);
+                if found.is_some() {
+                    return found;
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Every path from splicing `cycle` (a closed loop, last cell adjacent to
+/// the first) into `main`, whose cell positions `index` holds, where a step
+/// of each lies on opposite sides of a unit square
+fn spliced_cycles<'a>(
+    main: &'a [Point],
+    index: &'a HashMap<Point, usize>,
+    cycle: &'a [Point],
+) -> impl Iterator<Item = Vec<Point>> + 'a {
+    let n = cycle.len();
+    (0..n).flat_map(move |m| {
+        let (x, y) = (cycle[m], cycle[(m + 1) % n]);
+        square_sides(x, y).filter_map(move |(p, q)| {
+            let (&ip, &iq) = (index.get(&p)?, index.get(&q)?);
+            // Walk the cycle from one end of the removed step to the other
+            let (cut, walk): (usize, Vec<Point>) = if iq == ip + 1 {
+                (ip, (0..n).map(|k| cycle[(m + n - k) % n]).collect())
+            } else if ip == iq + 1 {
+                (iq, (0..n).map(|k| cycle[(m + 1 + k) % n]).collect())
+            } else {
+                return None;
+            };
+            Some(main[..=cut].iter().chain(&walk).chain(&main[cut + 1..]).copied().collect())
+        })
+    })
+}
+
+// ============================================================================
+// Grid Symmetry
+// ============================================================================
+
+/// One of the eight symmetries of a square (rotations are clockwise)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GridTransform {
+    Identity,
+    Rot90,
+    Rot180,
+    Rot270,
+    /// Mirror left-right
+    FlipH,
+    /// Mirror top-bottom
+    FlipV,
+    /// Mirror across the main diagonal
+    Transpose,
+    /// Mirror across the anti-diagonal
+    AntiTranspose,
+}
+
+impl GridTransform {
+    fn all() -> [GridTransform; 8] {
+        [
+            GridTransform::Identity,
+            GridTransform::Rot90,
+            GridTransform::Rot180,
+            GridTransform::Rot270,
+            GridTransform::FlipH,
+            GridTransform::FlipV,
+            GridTransform::Transpose,
+            GridTransform::AntiTranspose,
+        ]
+    }
+
+    /// Parse a transform name: "flip_h", "flip_v", "rot90", "rot180" or
+    /// "rot270"
+    fn parse(name: &str) -> Option<GridTransform> {
+        match name {
+            "flip_h" => Some(GridTransform::FlipH),
+            "flip_v" => Some(GridTransform::FlipV),
+            "rot90" => Some(GridTransform::Rot90),
+            "rot180" => Some(GridTransform::Rot180),
+            "rot270" => Some(GridTransform::Rot270),
+            _ => None,
+        }
+    }
+
+    /// Whether the transform swaps the row and column axes
+    fn swaps_axes(&self) -> bool {
+        matches!(
+            self,
+            GridTransform::Rot90
+                | GridTransform::Rot270
+                | GridTransform::Transpose
+                | GridTransform::AntiTranspose
+        )
+    }
+
+    /// Transforms that map a grid of this size onto itself: all eight for a
+    /// square, only identity, 180° and the two axis mirrors otherwise
+    fn symmetries(grid_size: GridSize) -> Vec<GridTransform> {
+        let square = grid_size.rows == grid_size.cols;
+        GridTransform::all()
+            .into_iter()
+            .filter(|t| square || !t.swaps_axes())
+            .collect()
+    }
+
+    /// Size of the grid after transforming
+    fn apply_size(&self, grid_size: GridSize) -> GridSize {
+        if self.swaps_axes() {
+            GridSize {
+                rows: grid_size.cols,
+                cols: grid_size.rows,
+            }
+        } else {
+            grid_size
+        }
+    }
+
+    /// Image of `p`, a cell of a `grid_size` grid, in the transformed grid
+    fn apply(&self, p: Point, grid_size: GridSize) -> Point {
+        let last_row = grid_size.rows - 1;
+        let last_col = grid_size.cols - 1;
+        match self {
+            GridTransform::Identity => p,
+            GridTransform::Rot90 => Point::new(p.col, last_row - p.row),
+            GridTransform::Rot180 => Point::new(last_row - p.row, last_col - p.col),
+            GridTransform::Rot270 => Point::new(last_col - p.col, p.row),
+            GridTransform::FlipH => Point::new(p.row, last_col - p.col),
+            GridTransform::FlipV => Point::new(last_row - p.row, p.col),
+            GridTransform::Transpose => Point::new(p.col, p.row),
+            GridTransform::AntiTranspose => Point::new(last_col - p.col, last_row - p.row),
+        }
+    }
+}
+
+/// A path moved onto the transformed grid
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransformedPath {
+    pub path: Vec<Point>,
+    pub grid_size: GridSize,
+}
+
+/// Apply `transform` to every cell of `path`. Rotations and reflections
+/// preserve adjacency, so a valid path stays valid on the new grid.
+fn transform_path_internal(path: &[Point], grid_size: GridSize, transform: GridTransform) -> TransformedPath {
+    TransformedPath {
+        path: path.iter().map(|&p| transform.apply(p, grid_size)).collect(),
+        grid_size: transform.apply_size(grid_size),
+    }
+}
+
+/// Rotate a tiled grid `quarter_turns` times clockwise (negative turns go
+/// counterclockwise). Cells move as under `GridTransform::Rot90` and their
+/// connections turn with `rotate_connection_cw`; each tile is swapped for the
+/// tile of its variant whose mask is the rotated one, i.e. the same base
+/// sprite at `rotation` + 90°. Start/goal markers keep their id and pick up
+/// the new angle of their connection.
+///
+/// The grid of the result is `grid_size` with rows and cols swapped for an
+/// odd number of turns. None for a size `dimension_error` rejects, a cell
+/// outside `grid_size`, an unknown tile id, direction or port string, or a
+/// tile without a rotated counterpart.
+fn rotate_road_grid(
+    grid: &[Vec<Option<CellData>>],
+    grid_size: GridSize,
+    quarter_turns: i32,
+) -> Option<RoadGridResult> {
+    if grid_size.dimension_error().is_some() {
+        return None;
+    }
+    let mut grid = grid.to_vec();
+    let mut size = grid_size;
+    for _ in 0..quarter_turns.rem_euclid(4) {
+        grid = rotate_cells_cw(&grid, size)?;
+        size = GridTransform::Rot90.apply_size(size);
+    }
+    let valid = validate_grid(&grid, size).is_empty();
+    Some(RoadGridResult::new(grid, valid, None))
+}
+
+/// One clockwise quarter turn of `rotate_road_grid`
+fn rotate_cells_cw(
+    grid: &[Vec<Option<CellData>>],
+    grid_size: GridSize,
+) -> Option<Vec<Vec<Option<CellData>>>> {
+    let rotated_size = GridTransform::Rot90.apply_size(grid_size);
+    let mut rotated = rotated_size.table(None);
+    for (row, cells) in grid.iter().enumerate() {
+        for (col, cell) in cells.iter().enumerate() {
+            let Some(cell) = cell else {
+                continue;
+            };
+            let p = Point::new(row as i32, col as i32);
+            if !grid_size.contains(p) {
+                return None;
+            }
+            let connections = cell
+                .connections
+                .iter()
+                .map(|conn| {
+                    let (dir, ports) = rotate_connection_cw(conn.parse().ok()?);
+                    Some(Connection::new(dir, ports))
+                })
+                .collect::<Option<Vec<_>>>()?;
+            let tile_id = match find_tile(&cell.tile_id) {
+                Some(tile) => {
+                    let mask = encode_connections(&connections)?;
+                    tile_with_mask(tile.variant.to_string(), mask)?.id.to_string()
+                }
+                None if matches!(cell.tile_id.as_str(), "start" | "goal") => cell.tile_id.clone(),
+                None => return None,
+            };
+            let mut moved = CellData::new(tile_id, connections, cell.path_index);
+            moved.road_id = cell.road_id;
+            let q = GridTransform::Rot90.apply(p, grid_size);
+            rotated[q.row as usize][q.col as usize] = Some(moved);
+        }
+    }
+    Some(rotated)
+}
+
+/// Representative of (start, end) under the grid's symmetries: the image
+/// with the lexicographically smallest (start.row, start.col, end.row,
+/// end.col). Start and end keep their roles, so a pair and its reverse
+/// canonicalize separately.
+fn canonicalize_endpoints(start: Point, end: Point, grid_size: GridSize) -> EndpointPair {
+    GridTransform::symmetries(grid_size)
+        .into_iter()
+        .map(|t| EndpointPair {
+            start: t.apply(start, grid_size),
+            end: t.apply(end, grid_size),
+        })
+        .min_by_key(|pair| (pair.start.row, pair.start.col, pair.end.row, pair.end.col))
+        .unwrap_or(EndpointPair { start, end })
+}
+
+// ============================================================================
+// Compact Grid Format
+// ============================================================================
+//
+// Rows are separated by '/', cells by '|'. An occupied cell is
+// `tile_id:path_index:conns` where conns is a comma-separated list of a
+// direction letter (u/r/d/l) followed by the ports, e.g. `curve-0A:3:u23,r23`.
+// Corners use the keys around 's' on a QWERTY keyboard: e (up-right),
+// c (down-right), z (down-left), q (up-left). A run of n empty cells is
+// written `*n`.
+
+const COMPACT_SEPARATORS: [char; 5] = ['/', '|', ':', ',', '*'];
+
+fn direction_letter(direction: Direction) -> char {
+    match direction {
+        Direction::Up => 'u',
+        Direction::Right => 'r',
+        Direction::Down => 'd',
+        Direction::Left => 'l',
+        Direction::UpRight => 'e',
+        Direction::DownRight => 'c',
+        Direction::DownLeft => 'z',
+        Direction::UpLeft => 'q',
+    }
+}
+
+fn letter_direction(letter: char) -> Option<Direction> {
+    Direction::with_diagonals().into_iter().find(|&dir| direction_letter(dir) == letter)
+}
+
+/// Encode a grid in the compact format. Returns None when a tile id or port
+/// string contains a separator, or a direction name is unknown.
+fn encode_compact_grid(grid: &[Vec<Option<CellData>>]) -> Option<String> {
+    let has_separator = |s: &str| s.contains(COMPACT_SEPARATORS);
+    let mut rows = Vec::with_capacity(grid.len());
+
+    for row in grid {
+        let mut cells: Vec<String> = Vec::new();
+        let mut empty_run = 0;
+        for cell in row {
+            let Some(cell) = cell else {
+                empty_run += 1;
+                continue;
+            };
+            if empty_run > 0 {
+                cells.push(format!("*{}", empty_run));
+                empty_run = 0;
+            }
+            if cell.tile_id.is_empty() || has_separator(&cell.tile_id) {
+                return None;
+            }
+            let mut conns = Vec::with_capacity(cell.connections.len());
+            for conn in &cell.connections {
+                if has_separator(&conn.ports) {
+                    return None;
+                }
+                let direction = conn.direction.parse().ok()?;
+                conns.push(format!("{}{}", direction_letter(direction), conn.ports));
+            }
+            cells.push(format!("{}:{}:{}", cell.tile_id, cell.path_index, conns.join(",")));
+        }
+        if empty_run > 0 {
+            cells.push(format!("*{}", empty_run));
+        }
+        rows.push(cells.join("|"));
+    }
+
+    Some(rows.join("/"))
+}
+
+/// Parse the compact format back into a grid; None on malformed input
+fn decode_compact_grid(compact: &str) -> Option<Vec<Vec<Option<CellData>>>> {
+    if compact.is_empty() {
+        return Some(Vec::new());
+    }
+
+    let mut grid = Vec::new();
+    for row_str in compact.split('/') {
+        let mut row = Vec::new();
+        if !row_str.is_empty() {
+            for cell_str in row_str.split('|') {
+                if let Some(run) = cell_str.strip_prefix('*') {
+                    let n: usize = run.parse().ok().filter(|&n| n > 0)?;
+                    row.extend(std::iter::repeat_n(None, n));
+                    continue;
+                }
+                let mut parts = cell_str.splitn(3, ':');
+                let tile_id = parts.next().filter(|id| !id.is_empty())?;
+                let path_index = parts.next()?.parse().ok()?;
+                let conns = parts.next()?;
+                let connections = if conns.is_empty() {
+                    Vec::new()
+                } else {
+                    conns
+                        .split(',')
+                        .map(|conn| {
+                            let mut chars = conn.chars();
+                            let direction = letter_direction(chars.next()?)?;
+                            Some(Connection {
+                                direction: direction.to_string().to_string(),
+                                ports: chars.as_str().to_string(),
+                            })
+                        })
+                        .collect::<Option<Vec<_>>>()?
+                };
+                row.push(Some(CellData::new(tile_id.to_string(), connections, path_index)));
+            }
+        }
+        grid.push(row);
+    }
+
+    Some(grid)
+}
+
+// ============================================================================
+// Tile Placements
+// ============================================================================
+
+/// One occupied cell as a tilemap engine places it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TilePlacement {
+    pub row: i32,
+    pub col: i32,
+    pub tile_id: String,
+    /// Clockwise rotation (degrees) of the base sprite
+    pub rotation: u16,
+}
+
+/// Occupied cells of `grid` in placement order: by path index, then road id
+/// for multi-road grids. None for a cell outside `grid_size`.
+fn grid_placements(
+    grid: &[Vec<Option<CellData>>],
+    grid_size: GridSize,
+) -> Option<Vec<TilePlacement>> {
+    let mut cells = Vec::new();
+    for (row, cells_in_row) in grid.iter().enumerate() {
+        for (col, cell) in cells_in_row.iter().enumerate() {
+            let Some(cell) = cell else {
+                continue;
+            };
+            let p = Point::new(row as i32, col as i32);
+            if !grid_size.contains(p) {
+                return None;
+            }
+            cells.push((p, cell));
+        }
+    }
+    cells.sort_by_key(|(_, cell)| (cell.path_index, cell.road_id));
+    let placements = cells
+        .into_iter()
+        .map(|(p, cell)| TilePlacement {
+            row: p.row,
+            col: p.col,
+            tile_id: cell.tile_id.clone(),
+            rotation: cell.rotation,
+        })
+        .collect();
+    Some(placements)
+}
+
+// ============================================================================
+// Grid Diff
+// ============================================================================
+
+/// A cell whose tile differs between two grids; None is an empty cell
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CellChange {
+    pub row: i32,
+    pub col: i32,
+    pub old_tile: Option<CellData>,
+    pub new_tile: Option<CellData>,
+}
+
+/// Cells whose tile or connections differ from `old` to `new`, row by row.
+/// Grids of different shapes compare as if padded with empty cells.
+fn diff_grids_internal(
+    old: &[Vec<Option<CellData>>],
+    new: &[Vec<Option<CellData>>],
+) -> Vec<CellChange> {
+    let cell = |grid: &[Vec<Option<CellData>>], row: usize, col: usize| {
+        grid.get(row).and_then(|r| r.get(col)).cloned().flatten()
+    };
+    let width = |grid: &[Vec<Option<CellData>>], row: usize| grid.get(row).map_or(0, Vec::len);
+
+    let mut changes = Vec::new();
+    for row in 0..old.len().max(new.len()) {
+        for col in 0..width(old, row).max(width(new, row)) {
+            let (old_tile, new_tile) = (cell(old, row, col), cell(new, row, col));
+            let same = match (&old_tile, &new_tile) {
+                (Some(a), Some(b)) => a.same_tile(b),
+                (a, b) => a.is_none() && b.is_none(),
+            };
+            if !same {
+                changes.push(CellChange {
+                    row: row as i32,
+                    col: col as i32,
+                    old_tile,
+                    new_tile,
+                });
+            }
+        }
+    }
+    changes
+}
+
+// ============================================================================
+// WASM Exports
+// ============================================================================
+
+/// Parse an optional JS array of Points (`undefined`/`null` become empty)
+fn parse_points(points_js: JsValue) -> Option<Vec<Point>> {
+    serde_wasm_bindgen::from_value::<Option<Vec<Point>>>(points_js)
+        .ok()
+        .map(Option::unwrap_or_default)
+}
+
+/// Why a search from `start` can't run on `grid_size` at all: a size
+/// `dimension_error` rejects or a start outside the grid. Checked before any
+/// PathState is built, since JS callers may pass arbitrary numbers and
+/// indexing the visited grid with them would panic.
+fn start_error(start: Point, grid_size: GridSize) -> Option<InputError> {
+    if grid_size.dimension_error().is_some() {
+        return Some(InputError {
+            kind: "invalid_dimensions".to_string(),
+            point: None,
+            cell: None,
+            grid_size,
+        });
+    }
+    (!grid_size.contains(start)).then(|| InputError::out_of_bounds("start", start, grid_size))
+}
+
+/// Like `start_error`, also rejecting an end outside the grid and an end
+/// equal to the start on a grid of more than one cell (a path visits each
+/// cell once, so it can't return to its start)
+fn endpoint_error(start: Point, end: Point, grid_size: GridSize) -> Option<InputError> {
+    if let Some(error) = start_error(start, grid_size) {
+        return Some(error);
+    }
+    if !grid_size.contains(end) {
+        return Some(InputError::out_of_bounds("end", end, grid_size));
+    }
+    (start == end && grid_size.cell_count().is_some_and(|n| n > 1)).then(|| InputError {
+        kind: "same_endpoints".to_string(),
+        point: None,
+        cell: Some(start),
+        grid_size,
+    })
+}
+
+/// Run the Hamiltonian search and package the outcome as a PathResult
+fn solve_road_path(
+    start: Point,
+    end: Point,
+    grid_size: GridSize,
+    max_iterations: u32,
+    options: SearchOptions,
+) -> PathResult {
+    let input_error = if options.open_end {
+        start_error(start, grid_size)
+    } else {
+        endpoint_error(start, end, grid_size)
+    };
+    if let Some(error) = input_error {
+        return PathResult::invalid(error);
+    }
+    let plain = options.blocked.is_empty()
+        && options.waypoints.is_empty()
+        && options.cell_mask.is_none()
+        && options.target_length.is_none()
+        && !options.diagonals
+        && options.locked_tiles.is_empty()
+        && !options.wrap;
+    if options.strategy == SearchStrategy::Snake && plain {
+        let snake = [false, true]
+            .into_iter()
+            .filter_map(|by_columns| build_snake_path(start, grid_size, by_columns))
+            .find(|path| path.last() == Some(&end));
+        if let Some(path) = snake {
+            return PathResult {
+                found: true,
+                path,
+                iterations: 0,
+                backtracks: 0,
+                cancelled: false,
+                timed_out: false,
+                depth_counts: None,
+                reason: None,
+                best_partial: None,
+                visited_grid: None,
+                input_error: None,
+            };
+        }
+    }
+    let mut state = PathState::with_options(grid_size, max_iterations, options);
+    search_path(&mut state, start, end)
+}
+
+/// Hamiltonian search that routes through `locks`, tiling the result with
+/// the locked tiles in place. None for an unknown tile id or a lock outside
+/// the grid.
+fn solve_locked_path(
+    start: Point,
+    end: Point,
+    grid_size: GridSize,
+    max_iterations: u32,
+    locks: &[TileLock],
+) -> Option<LockedPathResult> {
+    let locked_tiles = locks
+        .iter()
+        .map(|lock| {
+            let p = Point::new(lock.row, lock.col);
+            if !grid_size.contains(p) {
+                return None;
+            }
+            Some((p, find_tile(&lock.tile_id)?))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let options = SearchOptions {
+        locked_tiles: locked_tiles.clone(),
+        ..Default::default()
+    };
+    let result = solve_road_path(start, end, grid_size, max_iterations, options);
+    let grid = result.found.then(|| {
+        let mut tiled = path_to_tiles(&result.path, grid_size, TileOptions::default());
+        for (p, tile) in locked_tiles {
+            let cell = &mut tiled.grid[p.row as usize][p.col as usize];
+            if let Some(old) = cell.take() {
+                *cell = Some(CellData::new(tile.id, old.connections, old.path_index));
+            }
+        }
+        tiled
+    });
+    Some(LockedPathResult { result, grid })
+}
+
+/// Grid dimensions and both endpoints as (row, col), the smaller first
+type SolvabilityKey = (i32, i32, (i32, i32), (i32, i32));
+
+/// A path reversed is a path, so both orders of a pair share one key
+fn solvability_key(start: Point, end: Point, grid_size: GridSize) -> SolvabilityKey {
+    let (a, b) = ((start.row, start.col), (end.row, end.col));
+    (grid_size.rows, grid_size.cols, a.min(b), a.max(b))
+}
+
+/// Endpoint pairs whose plain search ran to exhaustion without a path in
+/// this session. Solvability is fixed by the dimensions and endpoints, so
+/// entries never go stale; `clear_solvability_cache` frees them.
+fn unsolvable_pairs() -> &'static Mutex<HashSet<SolvabilityKey>> {
+    static PAIRS: OnceLock<Mutex<HashSet<SolvabilityKey>>> = OnceLock::new();
+    PAIRS.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+/// Run one search on a fresh or reset state
+fn search_path(state: &mut PathState, start: Point, end: Point) -> PathResult {
+    let input_error = if state.open_end {
+        start_error(start, state.grid_size)
+    } else {
+        endpoint_error(start, end, state.grid_size)
+    };
+    let key = match input_error {
+        Some(_) => None,
+        None => state.solvability_key(start, end),
+    };
+    let known = key.is_some_and(|key| unsolvable_pairs().lock().unwrap().contains(&key));
+    let reason = match &input_error {
+        Some(error) => Some(error.message()),
+        None if known => Some("the pair was already proven unsolvable in this session".to_string()),
+        None => state.infeasibility_reason(start, end),
+    };
+    let found = reason.is_none() && find_path_internal(state, start, end);
+    #[cfg(feature = "fuzz-checks")]
+    state.check_invariants();
+    // An exhausted search proves there is no path; a suspended one doesn't
+    let exhausted = !found && !state.cancelled && state.iterations < state.max_iterations;
+    if let Some(key) = key.filter(|_| reason.is_none() && exhausted) {
+        unsolvable_pairs().lock().unwrap().insert(key);
+    }
+
+    PathResult {
+        found,
+        path: if found { state.path.clone() } else { vec![] },
+        iterations: state.iterations,
+        backtracks: state.backtracks,
+        cancelled: state.cancelled && !state.timed_out,
+        timed_out: state.timed_out,
+        depth_counts: state.depth_counts.clone(),
+        best_partial: (!found && reason.is_none()).then(|| state.best_path.clone()),
+        visited_grid: (state.record_visited && !found && reason.is_none())
+            .then(|| state.deepest_visited_grid()),
+        reason,
+        input_error,
+    }
+}
+
+/// Everything needed to continue a suspended search with obstacles: the
+/// visited cells are rebuilt from `blocked` and `path`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SearchSnapshot {
+    grid_size: GridSize,
+    end: Point,
+    blocked: Vec<Point>,
+    path: Vec<Point>,
+    frames: Vec<SearchFrame>,
+    pending: Option<Point>,
+    iterations: u32,
+    #[serde(default)]
+    backtracks: u32,
+}
+
+/// Outcome of a resumable search call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ResumableResult {
+    result: PathResult,
+    /// Set when the iteration budget ran out before the search finished
+    snapshot: Option<SearchSnapshot>,
+}
+
+/// Run the stack until it finishes or the budget runs out
+fn run_resumable(mut state: PathState, mut stack: SearchStack, end: Point, blocked: Vec<Point>) -> ResumableResult {
+    let status = run_search(&mut state, &mut stack, end);
+    let found = status == SearchStatus::Found;
+
+    let result = PathResult {
+        found,
+        path: if found { state.path.clone() } else { vec![] },
+        iterations: state.iterations,
+        backtracks: state.backtracks,
+        cancelled: false,
+        timed_out: false,
+        depth_counts: None,
+        reason: None,
+        best_partial: (!found).then(|| state.best_path.clone()),
+        visited_grid: None,
+        input_error: None,
+    };
+    let snapshot = (status == SearchStatus::Suspended).then(|| SearchSnapshot {
+        grid_size: state.grid_size,
+        end,
+        blocked,
+        path: state.path.clone(),
+        frames: stack.frames,
+        pending: stack.pending,
+        iterations: state.iterations,
+        backtracks: state.backtracks,
+    });
+
+    ResumableResult { result, snapshot }
+}
+
+/// Start a search that can be suspended after `max_iterations` and resumed
+fn start_resumable_search(
+    start: Point,
+    end: Point,
+    grid_size: GridSize,
+    max_iterations: u32,
+    blocked: Vec<Point>,
+) -> ResumableResult {
+    let rejected = |result| ResumableResult {
+        result,
+        snapshot: None,
+    };
+    if let Some(error) = endpoint_error(start, end, grid_size) {
+        return rejected(PathResult::invalid(error));
+    }
+    let options = SearchOptions {
+        blocked: blocked.clone(),
+        ..Default::default()
+    };
+    let state = PathState::with_options(grid_size, max_iterations, options);
+    if let Some(reason) = state.infeasibility_reason(start, end) {
+        return rejected(PathResult::rejected(reason));
+    }
+
+    run_resumable(state, SearchStack::new(start), end, blocked)
+}
+
+/// Continue a suspended search with `extra_iterations` more budget. Returns
+/// None if the snapshot is inconsistent (e.g. edited or truncated).
+fn resume_search(snapshot: SearchSnapshot, extra_iterations: u32) -> Option<ResumableResult> {
+    let grid_size = snapshot.grid_size;
+    if grid_size.dimension_error().is_some() || snapshot.frames.len() != snapshot.path.len() {
+        return None;
+    }
+
+    let options = SearchOptions {
+        blocked: snapshot.blocked.clone(),
+        ..Default::default()
+    };
+    let max_iterations = snapshot.iterations.saturating_add(extra_iterations);
+    let mut state = PathState::with_options(grid_size, max_iterations, options);
+    state.iterations = snapshot.iterations;
+    state.backtracks = snapshot.backtracks;
+
+    if !state.is_valid(snapshot.end) {
+        return None;
+    }
+    for (i, &p) in snapshot.path.iter().enumerate() {
+        if state.is_visited(p) || (i > 0 && !is_adjacent(snapshot.path[i - 1], p)) {
+            return None;
+        }
+        state.visit(p);
+    }
+    // Each frame lists neighbors of its path cell, and a pending cell
+    // extends the path from its last cell
+    let path = &snapshot.path;
+    let frames_ok = snapshot.frames.iter().zip(path).all(|(f, &p)| {
+        f.next <= f.candidates.len()
+            && f.candidates.iter().all(|&c| state.is_valid(c) && is_adjacent(p, c))
+    });
+    let pending_ok = snapshot.pending.is_none_or(|p| {
+        state.is_valid(p) && path.last().is_none_or(|&last| is_adjacent(last, p))
+    });
+    if !frames_ok || !pending_ok {
+        return None;
+    }
+
+    let stack = SearchStack {
+        frames: snapshot.frames,
+        pending: snapshot.pending,
+    };
+    Some(run_resumable(state, stack, snapshot.end, snapshot.blocked))
+}
+
+/// Start and end of one search in a batch
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct EndpointPair {
+    start: Point,
+    end: Point,
+}
+
+/// Solve every endpoint pair on the same grid, reusing one PathState
+#[cfg(not(all(feature = "parallel", not(target_arch = "wasm32"))))]
+fn solve_road_paths_batch(
+    pairs: &[EndpointPair],
+    grid_size: GridSize,
+    max_iterations: u32,
+    memoize_unsolvable: bool,
+) -> Vec<PathResult> {
+    let mut state = PathState::new(grid_size, max_iterations);
+    state.memoize_unsolvable = memoize_unsolvable;
+    pairs
+        .iter()
+        .map(|pair| {
+            state.reset();
+            search_path(&mut state, pair.start, pair.end)
+        })
+        .collect()
+}
+
+/// Solve every endpoint pair on the same grid across rayon's thread pool.
+///
+/// Each worker thread keeps its own PathState and resets it per pair, so
+/// pairs never share search state; since the search is deterministic the
+/// results match the sequential version regardless of thread count or
+/// scheduling, and rayon keeps them in input order.
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+fn solve_road_paths_batch(
+    pairs: &[EndpointPair],
+    grid_size: GridSize,
+    max_iterations: u32,
+    memoize_unsolvable: bool,
+) -> Vec<PathResult> {
+    use rayon::prelude::*;
+
+    pairs
+        .par_iter()
+        .map_init(
+            || {
+                let mut state = PathState::new(grid_size, max_iterations);
+                state.memoize_unsolvable = memoize_unsolvable;
+                state
+            },
+            |state, pair| {
+                state.reset();
+                search_path(state, pair.start, pair.end)
+            },
+        )
+        .collect()
+}
+
+/// Every unordered endpoint pair of the grid that has a Hamiltonian path
+///
+/// Each pair is listed once with `start` before `end` in row-major order (a
+/// path reversed serves the other direction). Pairs the parity pre-check
+/// rules out are skipped without searching; the rest are searched with
+/// `max_iterations` each, so pairs whose search runs out of budget are
+/// missing from the result.
+fn solvable_endpoint_pairs(grid_size: GridSize, max_iterations: u32) -> Vec<SolvableEndpoints> {
+    if grid_size.dimension_error().is_some() {
+        return Vec::new();
+    }
+    let probe = PathState::new(grid_size, 0);
+    let cells: Vec<Point> = (0..grid_size.rows)
+        .flat_map(|row| (0..grid_size.cols).map(move |col| Point::new(row, col)))
+        .collect();
+    let pairs: Vec<EndpointPair> = cells
+        .iter()
+        .enumerate()
+        .flat_map(|(i, &start)| cells[i + 1..].iter().map(move |&end| EndpointPair { start, end }))
+        .filter(|pair| probe.parity_allows_path(pair.start, pair.end))
+        .collect();
+
+    solve_road_paths_batch(&pairs, grid_size, max_iterations, false)
+        .into_iter()
+        .zip(&pairs)
+        .filter(|(result, _)| result.found)
+        .map(|(result, pair)| SolvableEndpoints {
+            start: pair.start,
+            end: pair.end,
+            iterations: result.iterations,
+        })
+        .collect()
+}
+
+/// Heuristic difficulty of searching from `start` to `end`.
+///
+/// The score is an estimated log10 of the iterations needed: it grows with the
+/// grid area, somewhat with elongation (aspect ratio), and with how far the
+/// endpoints sit from the border; corner endpoints are the easiest. The
+/// coefficients were fitted against the mean log10 iterations of the plain
+/// search over all endpoint pairs on 3x3 to 6x6, 2x10, 3x8, 4x6, 3x12, 4x10
+/// and 5x8 grids; `test_difficulty_estimate_calibration` rechecks the
+/// smaller ones.
+fn estimate_difficulty(start: Point, end: Point, grid_size: GridSize) -> DifficultyEstimate {
+    let state = PathState::new(grid_size, 0);
+    let invalid = endpoint_error(start, end, grid_size).is_some();
+    if invalid || state.infeasibility_reason(start, end).is_some() {
+        return DifficultyEstimate {
+            feasible: false,
+            score: 0.0,
+            suggested_max_iterations: 0,
+        };
+    }
+
+    let (rows, cols) = (grid_size.rows as f64, grid_size.cols as f64);
+    let area = rows * cols;
+    let aspect = rows.max(cols) / rows.min(cols);
+
+    // 0 on the border, 1 in the middle of the shorter side
+    let max_depth = ((grid_size.rows.min(grid_size.cols) - 1) / 2) as f64;
+    let centrality = |p: Point| {
+        let depth = p.row.min(grid_size.rows - 1 - p.row).min(p.col).min(grid_size.cols - 1 - p.col);
+        if max_depth > 0.0 { depth as f64 / max_depth } else { 0.0 }
+    };
+    let is_corner = |p: Point| {
+        (p.row == 0 || p.row == grid_size.rows - 1) && (p.col == 0 || p.col == grid_size.cols - 1)
+    };
+    let corners = is_corner(start) as u8 + is_corner(end) as u8;
+
+    let endpoint_factor = 1.0 + 0.5 * (centrality(start) + centrality(end)) / 2.0 - 0.1 * corners as f64;
+    let aspect_factor = 1.0 + 0.15 * (aspect - 1.0);
+    let score = 0.5 + 0.06 * area * endpoint_factor * aspect_factor;
+
+    // Individual searches scatter widely around the estimate, so leave two
+    // orders of magnitude of headroom
+    let suggested = 10f64.powf(score + 2.0).clamp(10_000.0, 50_000_000.0);
+
+    DifficultyEstimate {
+        feasible: true,
+        score,
+        suggested_max_iterations: suggested as u32,
+    }
+}
+
+/// Corner a Hamiltonian path from (0, 0) can end on: the opposite one when
+/// the checkerboard parity allows it, otherwise the bottom-left or, failing
+/// that, the top-right one
+fn benchmark_end(grid_size: GridSize) -> Point {
+    let (last_row, last_col) = (grid_size.rows - 1, grid_size.cols - 1);
+    let start = Point::new(0, 0);
+    let state = PathState::new(grid_size, 0);
+    [Point::new(last_row, last_col), Point::new(last_row, 0), Point::new(0, last_col)]
+        .into_iter()
+        .find(|&end| end != start && state.infeasibility_reason(start, end).is_none())
+        .unwrap_or(Point::new(last_row, last_col))
+}
+
+/// Run the plain search corner to corner on each size, timing it with `clock`
+fn run_benchmark(
+    sizes: &[GridSize],
+    max_iterations: u32,
+    clock: &mut ClockFn,
+) -> Vec<BenchmarkEntry> {
+    let start = Point::new(0, 0);
+    sizes
+        .iter()
+        .map(|&grid_size| {
+            let end = benchmark_end(grid_size);
+            let started = clock();
+            let options = SearchOptions::default();
+            let result = solve_road_path(start, end, grid_size, max_iterations, options);
+            BenchmarkEntry {
+                rows: grid_size.rows,
+                cols: grid_size.cols,
+                end,
+                found: result.found,
+                iterations: result.iterations,
+                elapsed_ms: clock() - started,
+            }
+        })
+        .collect()
+}
+
+/// Lower bounds and names of the `BatchStats` histogram buckets
+const ITERATION_BUCKETS: [(&str, u32); 5] = [
+    ("<1k", 0),
+    ("1k-10k", 1_000),
+    ("10k-100k", 10_000),
+    ("100k-1M", 100_000),
+    ("1M+", 1_000_000),
+];
+
+/// Summarize a batch of path results: how many found a path and how the
+/// iteration counts are distributed
+fn batch_stats(results: &[PathResult]) -> BatchStats {
+    let mut iterations: Vec<u32> = results.iter().map(|r| r.iterations).collect();
+    iterations.sort_unstable();
+    let count = results.len();
+    let found = results.iter().filter(|r| r.found).count();
+    let ratio = |total: f64| if count == 0 { 0.0 } else { total / count as f64 };
+    let median = match count {
+        0 => 0.0,
+        n if n % 2 == 1 => iterations[n / 2] as f64,
+        n => (iterations[n / 2 - 1] as f64 + iterations[n / 2] as f64) / 2.0,
+    };
+
+    let histogram = ITERATION_BUCKETS
+        .iter()
+        .enumerate()
+        .map(|(i, &(name, min))| {
+            let max = ITERATION_BUCKETS.get(i + 1).map(|&(_, next)| next);
+            IterationBucket {
+                name: name.to_string(),
+                min,
+                max,
+                count: iterations
+                    .iter()
+                    .filter(|&&n| n >= min && max.is_none_or(|max| n < max))
+                    .count(),
+            }
+        })
+        .collect();
+
+    BatchStats {
+        count,
+        found,
+        found_fraction: ratio(found as f64),
+        mean_iterations: ratio(iterations.iter().map(|&n| n as f64).sum()),
+        median_iterations: median,
+        histogram,
+    }
+}
+
+/// Estimate how hard a path search between two cells will be, without
+/// running it
+///
+/// Returns `{ feasible, score, suggested_max_iterations }`. `score` is a
+/// rough log10 of the iterations a plain search needs; it only orders inputs
+/// by difficulty and individual searches can differ by orders of magnitude.
+#[wasm_bindgen]
+pub fn estimate_search_difficulty(
+    start_row: i32,
+    start_col: i32,
+    end_row: i32,
+    end_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+) -> JsValue {
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+    let result = estimate_difficulty(Point::new(start_row, start_col), Point::new(end_row, end_col), grid_size);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Time the solver on a list of grid sizes, for profiling from the browser
+///
+/// `sizes_js` is an array of `{ rows, cols }`. Each size gets one plain
+/// search from the top-left corner to a corner a path can end on (the
+/// opposite one when parity allows) with the same `max_iterations`, and an
+/// entry `{ rows, cols, end, found, iterations, elapsed_ms }`, timed with
+/// `performance.now()` (or `Date.now()` without the performance API).
+/// Returns null when `sizes_js` can't be parsed.
+#[wasm_bindgen]
+pub fn benchmark(sizes_js: JsValue, max_iterations: u32) -> JsValue {
+    let sizes: Vec<GridSize> = match serde_wasm_bindgen::from_value(sizes_js) {
+        Ok(s) => s,
+        Err(_) => return JsValue::NULL,
+    };
+
+    let mut clock = performance_clock().unwrap_or_else(|| Box::new(js_sys::Date::now));
+    let entries = run_benchmark(&sizes, max_iterations, &mut clock);
+    serde_wasm_bindgen::to_value(&entries).unwrap_or(JsValue::NULL)
+}
+
+/// Summarize a batch of PathResults, e.g. from a puzzle generation run
+///
+/// Returns `{ count, found, found_fraction, mean_iterations,
+/// median_iterations, histogram }`, where `histogram` has one bucket
+/// `{ name, min, max, count }` per decade of iterations ("<1k", "1k-10k",
+/// "10k-100k", "100k-1M", "1M+"; `max` is exclusive and null for the last).
+/// Returns null when `results_js` can't be parsed.
+#[wasm_bindgen]
+pub fn summarize_results(results_js: JsValue) -> JsValue {
+    let results: Vec<PathResult> = match serde_wasm_bindgen::from_value(results_js) {
+        Ok(r) => r,
+        Err(_) => return JsValue::NULL,
+    };
+
+    serde_wasm_bindgen::to_value(&batch_stats(&results)).unwrap_or(JsValue::NULL)
+}
+
+/// Shape metrics of a path
+///
+/// Returns `{ length, turns, straights, corners, longest_straight_run }`.
+/// A turn is a change of direction between consecutive steps; straights and
+/// corners count interior cells only, since the endpoints carry markers.
+/// Returns null if the path has non-adjacent steps.
+#[wasm_bindgen]
+pub fn path_stats(path_js: JsValue) -> JsValue {
+    let path: Vec<Point> = match serde_wasm_bindgen::from_value(path_js) {
+        Ok(p) => p,
+        Err(_) => return JsValue::NULL,
+    };
+
+    match compute_path_stats(&path) {
+        Some(stats) => serde_wasm_bindgen::to_value(&stats).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+/// Straighten a found path by reversing segments where that removes turns
+///
+/// The result covers exactly the same cells between the same endpoints, so
+/// a Hamiltonian path stays Hamiltonian. Each move reverses a stretch, or
+/// cuts one out as a loop and splices it back in beside another step, and is
+/// kept only when the path gets fewer turns; the outcome is locally, not
+/// globally, least wiggly.
+/// Returns `{ path, turns_before, turns_after, swaps }`, or null if the path
+/// has non-adjacent steps or visits a cell twice.
+#[wasm_bindgen]
+pub fn simplify_path(path_js: JsValue) -> JsValue {
+    let Ok(path) = serde_wasm_bindgen::from_value::<Vec<Point>>(path_js) else {
+        return JsValue::NULL;
+    };
+    match simplify_turns(&path) {
+        Some(simplified) => serde_wasm_bindgen::to_value(&simplified).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+/// Find a path and count the moves along it that were forced
+///
+/// Returns `{ result, forced, forced_moves }`: the usual PathResult, one flag
+/// per step telling whether the cell left had only one unvisited neighbor,
+/// and how many did. A higher share of forced moves means an easier puzzle.
+#[wasm_bindgen]
+pub fn forced_move_count(
+    start_row: i32,
+    start_col: i32,
+    end_row: i32,
+    end_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+    max_iterations: u32,
+) -> JsValue {
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+    let start = Point::new(start_row, start_col);
+    let report = forced_moves(start, Point::new(end_row, end_col), grid_size, max_iterations);
+    serde_wasm_bindgen::to_value(&report).unwrap_or(JsValue::NULL)
+}
+
+/// Mirror or rotate a path to make a sibling puzzle
+///
+/// `transform` is one of "flip_h" (left-right mirror), "flip_v" (top-bottom
+/// mirror), "rot90", "rot180" or "rot270" (clockwise). Returns
+/// `{ path, grid_size }`; quarter turns swap rows and cols. Returns null for
+/// an unknown transform or a point outside the grid.
+#[wasm_bindgen]
+pub fn transform_path(path_js: JsValue, grid_rows: i32, grid_cols: i32, transform: &str) -> JsValue {
+    let path: Vec<Point> = match serde_wasm_bindgen::from_value(path_js) {
+        Ok(p) => p,
+        Err(_) => return JsValue::NULL,
+    };
+
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+
+    let transform = match GridTransform::parse(transform) {
+        Some(t) => t,
+        None => return JsValue::NULL,
+    };
+
+    if !path.iter().all(|&p| grid_size.contains(p)) {
+        return JsValue::NULL;
+    }
+
+    let result = transform_path_internal(&path, grid_size, transform);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Canonical form of a start/end pair under the grid's symmetries
+///
+/// Rotations and reflections of the same puzzle return the same
+/// `{ start, end }`, so the result can be used as a dedupe key. Square grids
+/// use all eight symmetries; other grids only 180° and the axis mirrors.
+/// Returns null if either endpoint is outside the grid.
+#[wasm_bindgen]
+pub fn canonical_endpoints(
+    start_row: i32,
+    start_col: i32,
+    end_row: i32,
+    end_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+) -> JsValue {
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+    let start = Point::new(start_row, start_col);
+    let end = Point::new(end_row, end_col);
+    if !grid_size.contains(start) || !grid_size.contains(end) {
+        return JsValue::NULL;
+    }
+
+    let pair = canonicalize_endpoints(start, end, grid_size);
+    serde_wasm_bindgen::to_value(&pair).unwrap_or(JsValue::NULL)
+}
+
+/// Check whether a Hamiltonian path between two cells is possible at all
+///
+/// Applies the checkerboard parity argument: on a grid with an even number of
+/// cells the endpoints must have opposite parity, and on an odd-celled grid
+/// both must lie on the majority color. A `true` result is necessary but not
+/// sufficient for a path to exist.
+#[wasm_bindgen]
+pub fn has_hamiltonian_path_possibility(
+    start_row: i32,
+    start_col: i32,
+    end_row: i32,
+    end_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+) -> bool {
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+    PathState::new(grid_size, 0)
+        .parity_allows_path(Point::new(start_row, start_col), Point::new(end_row, end_col))
+}
+
+/// Find a path from start to end that visits all cells
+///
+/// Endpoint pairs ruled out by the parity pre-check (see
+/// `has_hamiltonian_path_possibility`) return immediately with `found: false`,
+/// `iterations: 0` and the coloring argument in `reason`.
+///
+/// `start == end` is rejected with an `input_error` of kind "same_endpoints"
+/// unless the grid is a single cell, whose path is then just that cell.
+/// Endpoints outside the grid are rejected the same way ("out_of_bounds").
+///
+/// `blocked_js` is an optional array of Points that are obstacles: the path
+/// skips them entirely and only the remaining cells must be covered.
+///
+/// A nonzero `seed` shuffles neighbors that tie under Warnsdorff's rule with
+/// a deterministic PRNG, giving different but reproducible paths per seed.
+/// A zero seed keeps the fixed `Direction::all()` tie order.
+///
+/// `strategy` is "warnsdorff" (default) or "snake". "snake" returns a row- or
+/// column-wise snake from a corner start with `iterations: 0` when its end is
+/// `end` and there are no obstacles or target length, and searches like
+/// "warnsdorff" otherwise. An unknown value returns null.
+///
+/// `pruning` skips states whose unvisited cells are split or contain a dead
+/// end, which helps most on long thin grids. It is a flag rather than an
+/// iterative-deepening strategy: every Hamiltonian path has exactly one cell
+/// per free cell, so each deepening pass would either stop short of any
+/// solution or repeat the full-depth search.
+///
+/// With `target_length`, the path only has to reach the end with exactly that
+/// many cells instead of covering the whole grid (general routing); omitted
+/// keeps the full-cover search.
+///
+/// `tie_break` orders neighbors that still tie under Warnsdorff's rule:
+/// "fixed" (default) keeps the direction order, "straight" tries continuing
+/// the last step first (fewer turns), "turn" tries turning first and
+/// "spacious" tries cells with the fewest visited neighbors first (fewer
+/// `self_adjacencies` on partial paths). An unknown value returns null.
+///
+/// `style` leans the path toward a shape, ranked after Warnsdorff's rule and
+/// before `tie_break`: "spiral" tries cells nearest the border first, so the
+/// road hugs the walls and winds inward, "snake" tries horizontal steps
+/// first, so it keeps running along rows, and "default" has no preference.
+/// It only reorders neighbors, so it never changes whether a path is found.
+/// An unknown value returns null.
+///
+/// With `memoize_unsolvable`, a pair whose search ran out of branches without
+/// a path is remembered for the session, and later memoizing searches of it
+/// (in either direction, on a grid of the same size without obstacles or a
+/// target length) return at once with a `reason`. See
+/// `clear_solvability_cache`.
+///
+/// `max_iterations: 0` means "auto": the budget becomes `rows * cols * 20_000`,
+/// clamped to `[100_000, 50_000_000]`. This applies to every export taking a
+/// `max_iterations`; values above `u32::MAX / 2` are clamped to it.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn find_road_path(
+    start_row: i32,
+    start_col: i32,
+    end_row: i32,
+    end_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+    max_iterations: u32,
+    blocked_js: JsValue,
+    seed: u32,
+    strategy: Option<String>,
+    target_length: Option<u32>,
+    tie_break: Option<String>,
+    memoize_unsolvable: Option<bool>,
+    style: Option<String>,
+    pruning: Option<bool>,
+) -> JsValue {
+    let start = Point::new(start_row, start_col);
+    let end = Point::new(end_row, end_col);
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+
+    let blocked = match parse_points(blocked_js) {
+        Some(b) => b,
+        None => return JsValue::NULL,
+    };
+
+    let strategy = match strategy.as_deref() {
+        None => SearchStrategy::default(),
+        Some(s) => match SearchStrategy::parse(s) {
+            Some(strategy) => strategy,
+            None => return JsValue::NULL,
+        },
+    };
+
+    let tie_break = match tie_break.as_deref() {
+        None => TieBreak::default(),
+        Some(s) => match TieBreak::parse(s) {
+            Some(tie_break) => tie_break,
+            None => return JsValue::NULL,
+        },
+    };
+
+    let style = match style.as_deref() {
+        None => PathStyle::default(),
+        Some(s) => match PathStyle::parse(s) {
+            Some(style) => style,
+            None => return JsValue::NULL,
+        },
+    };
+
+    let options = SearchOptions {
+        blocked,
+        seed,
+        strategy,
+        target_length: target_length.map(|l| l as usize),
+        style,
+        tie_break,
+        memoize_unsolvable: memoize_unsolvable.unwrap_or(false),
+        pruning: pruning.unwrap_or(false),
+        ..Default::default()
+    };
+    let result = solve_road_path(start, end, grid_size, max_iterations, options);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Find paths for many endpoint pairs in one call
+///
+/// `pairs_js` is an array of `{ start, end }` Points. Returns an array of
+/// PathResults in the same order; each search gets its own `max_iterations`
+/// budget. The visited grid is allocated once and reset between searches.
+///
+/// With `memoize_unsolvable`, pairs proven unsolvable earlier in the session
+/// (by any memoizing search) are answered without searching, with a
+/// `reason`. On a 3x8 grid, where 46 of the 144 parity-feasible pairs have
+/// no path, repeating a probe of all of them drops from about 622k to 104k
+/// iterations (roughly 6x faster); `clear_solvability_cache` frees the cache.
+#[wasm_bindgen]
+pub fn find_road_paths_batch(
+    pairs_js: JsValue,
+    grid_rows: i32,
+    grid_cols: i32,
+    max_iterations: u32,
+    memoize_unsolvable: Option<bool>,
+) -> JsValue {
+    let pairs: Vec<EndpointPair> = match serde_wasm_bindgen::from_value(pairs_js) {
+        Ok(p) => p,
+        Err(_) => return JsValue::NULL,
+    };
+
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+
+    let memoize = memoize_unsolvable.unwrap_or(false);
+    let results = solve_road_paths_batch(&pairs, grid_size, max_iterations, memoize);
+    serde_wasm_bindgen::to_value(&results).unwrap_or(JsValue::NULL)
+}
+
+/// Forget the endpoint pairs memoized as unsolvable, to reclaim memory in a
+/// long-lived session
+#[wasm_bindgen]
+pub fn clear_solvability_cache() {
+    unsolvable_pairs().lock().unwrap().clear();
+}
+
+/// List the endpoint pairs of a grid that admit a Hamiltonian path
+///
+/// Returns an array of `{ start, end, iterations }`, one per unordered pair
+/// (`start` comes first in row-major order), for offering only solvable
+/// puzzles. Parity-impossible pairs are skipped without searching; every
+/// other pair gets its own `max_iterations` budget, and pairs that exhaust it
+/// are left out. The number of pairs grows with the square of the area, so
+/// this is meant for small grids or a modest per-pair cap.
+#[wasm_bindgen]
+pub fn solvable_endpoints(grid_rows: i32, grid_cols: i32, max_iterations: u32) -> JsValue {
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+    let pairs = solvable_endpoint_pairs(grid_size, max_iterations);
+    serde_wasm_bindgen::to_value(&pairs).unwrap_or(JsValue::NULL)
+}
+
+/// Collect up to `max_solutions` distinct Hamiltonian paths
+///
+/// Uniqueness needs a second solution to be ruled out, so the enumeration
+/// always looks for at least two paths even when `max_solutions` is 1.
+fn solve_road_paths(
+    start: Point,
+    end: Point,
+    grid_size: GridSize,
+    max_iterations: u32,
+    max_solutions: usize,
+    options: SearchOptions,
+) -> MultiPathResult {
+    let mut state = PathState::with_options(grid_size, max_iterations, options);
+    let mut paths = Vec::new();
+    let limit = max_solutions.max(2);
+
+    if state.parity_allows_path(start, end) {
+        enumerate_paths_internal(&mut state, start, end, &mut |path| {
+            paths.push(path.to_vec());
+            paths.len() < limit
+        });
+    }
+
+    let exhausted = state.iterations <= state.max_iterations && paths.len() < limit;
+    let unique = exhausted && paths.len() == 1;
+    paths.truncate(max_solutions);
+
+    MultiPathResult {
+        found: !paths.is_empty(),
+        paths,
+        unique,
+        iterations: state.iterations,
+    }
+}
+
+/// Find up to `max_solutions` distinct paths from start to end that visit all cells
+///
+/// `unique` is true only when the search was exhaustive and found exactly one
+/// path; hitting `max_iterations` leaves it false.
+///
+/// `pruning` enables connectivity pruning, which skips branches that can no
+/// longer cover every cell; omitted means false.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn find_road_paths(
+    start_row: i32,
+    start_col: i32,
+    end_row: i32,
+    end_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+    max_iterations: u32,
+    max_solutions: u32,
+    blocked_js: JsValue,
+    pruning: Option<bool>,
+) -> JsValue {
+    let start = Point::new(start_row, start_col);
+    let end = Point::new(end_row, end_col);
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+
+    let blocked = match parse_points(blocked_js) {
+        Some(b) => b,
+        None => return JsValue::NULL,
+    };
+
+    let options = SearchOptions {
+        blocked,
+        pruning: pruning.unwrap_or(false),
+        ..Default::default()
+    };
+    let result = solve_road_paths(start, end, grid_size, max_iterations, max_solutions as usize, options);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Find a path from start to end that visits all cells and passes through
+/// `waypoints_js` (an array of Points) in the given order
+///
+/// Branches that enter a later waypoint before an earlier one are pruned, so
+/// `found: false` means no order-respecting full-cover path exists (or the
+/// iteration budget ran out).
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn find_road_path_with_waypoints(
+    start_row: i32,
+    start_col: i32,
+    end_row: i32,
+    end_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+    max_iterations: u32,
+    waypoints_js: JsValue,
+    blocked_js: JsValue,
+) -> JsValue {
+    let start = Point::new(start_row, start_col);
+    let end = Point::new(end_row, end_col);
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+
+    let (waypoints, blocked) = match (parse_points(waypoints_js), parse_points(blocked_js)) {
+        (Some(w), Some(b)) => (w, b),
+        _ => return JsValue::NULL,
+    };
+
+    let options = SearchOptions {
+        blocked,
+        waypoints,
+        ..Default::default()
+    };
+    let result = solve_road_path(start, end, grid_size, max_iterations, options);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Find a path from start to end that visits all cells and shows the given
+/// tiles at locked cells
+///
+/// `locks_js` is an array of `{ row, col, tile_id }`. The path must enter and
+/// leave each locked cell through its tile's two sides, and branches that
+/// can't are pruned during the search; a candidate path is only accepted if
+/// tiling it with the default options (`path_to_road_grid` without
+/// arguments) gives each locked tile's ports too. Returns `{ result, grid }`
+/// where `grid` is that tiling with the locked tile ids in place, or null
+/// when nothing was found. Returns null for an unknown tile id or a lock
+/// outside the grid.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn find_road_path_with_locked_tiles(
+    start_row: i32,
+    start_col: i32,
+    end_row: i32,
+    end_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+    max_iterations: u32,
+    locks_js: JsValue,
+) -> JsValue {
+    let start = Point::new(start_row, start_col);
+    let end = Point::new(end_row, end_col);
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+
+    let locks: Vec<TileLock> = match serde_wasm_bindgen::from_value(locks_js) {
+        Ok(l) => l,
+        Err(_) => return JsValue::NULL,
+    };
+
+    match solve_locked_path(start, end, grid_size, max_iterations, &locks) {
+        Some(result) => serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+/// Find a Hamiltonian cycle through `start`
+///
+/// A cycle is a Hamiltonian path from `start` to one of its neighbors (see
+/// `solve_near_loop`), closed by the edge between them.
+fn solve_road_cycle(start: Point, grid_size: GridSize, max_iterations: u32) -> PathResult {
+    solve_near_loop(start, grid_size, max_iterations).result
+}
+
+/// Find a Hamiltonian path from `start` to one of its neighbors, trying each
+/// neighbor as the end cell in `Direction::all()` order with one shared
+/// iteration budget
+fn solve_near_loop(start: Point, grid_size: GridSize, max_iterations: u32) -> NearLoopResult {
+    if let Some(error) = start_error(start, grid_size) {
+        return NearLoopResult {
+            result: PathResult::invalid(error),
+            end: None,
+        };
+    }
+    let area = grid_size.cell_count().unwrap_or(0);
+    if area < 4 || !area.is_multiple_of(2) {
+        let reason = format!(
+            "a cycle alternates checkerboard colors, so it needs an even number of cells \
+             (at least 4), but the grid has {}",
+            area
+        );
+        return NearLoopResult {
+            result: PathResult::rejected(reason),
+            end: None,
+        };
+    }
+
+    // Totals over the searches below; running them is no rejection
+    let mut result = PathResult {
+        reason: None,
+        ..PathResult::rejected(String::new())
+    };
+    let max_iterations = effective_max_iterations(max_iterations, grid_size);
+    let probe = PathState::new(grid_size, 0);
+    for (end, _dir) in probe.get_neighbors(start) {
+        let budget = max_iterations.saturating_sub(result.iterations);
+        if budget == 0 {
+            break;
+        }
+        let mut state = PathState::new(grid_size, budget);
+        let found = find_path_internal(&mut state, start, end);
+        result.iterations += state.iterations.min(budget);
+        result.backtracks += state.backtracks;
+        if found {
+            result.found = true;
+            result.path = state.path;
+            return NearLoopResult {
+                result,
+                end: Some(end),
+            };
+        }
+    }
+
+    NearLoopResult { result, end: None }
+}
+
+/// Find a closed-loop road covering every cell, passing through `start`
+///
+/// The returned `path` lists the cells in loop order; its last cell is
+/// adjacent to the first, closing the circuit. Grids with an odd number of
+/// cells have no Hamiltonian cycle and return `found: false` immediately.
+#[wasm_bindgen]
+pub fn find_road_cycle(
+    start_row: i32,
+    start_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+    max_iterations: u32,
+) -> JsValue {
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+    let result = solve_road_cycle(Point::new(start_row, start_col), grid_size, max_iterations);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Find a path covering every cell that ends next to its start
+///
+/// Each neighbor of the start is tried as the end, sharing one iteration
+/// budget; the edge from the end back to the start closes the path into a
+/// circuit. Returns `{ result, end }` with the neighbor used, or `end: null`
+/// when no neighbor works (always on grids with an odd number of cells).
+#[wasm_bindgen]
+pub fn find_near_loop_path(
+    start_row: i32,
+    start_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+    max_iterations: u32,
+) -> JsValue {
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+    let result = solve_near_loop(Point::new(start_row, start_col), grid_size, max_iterations);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Hamiltonian search on a torus, where each edge joins the opposite one
+fn solve_wrapped_path(
+    start: Point,
+    end: Point,
+    grid_size: GridSize,
+    max_iterations: u32,
+) -> PathResult {
+    let options = SearchOptions {
+        wrap: true,
+        ..SearchOptions::default()
+    };
+    solve_road_path(start, end, grid_size, max_iterations, options)
+}
+
+/// Find a path covering every cell of a wrap-around (toroidal) grid
+///
+/// Stepping off an edge enters the opposite edge, on each axis of three or
+/// more cells; the grid itself and the result shape are unchanged, only the
+/// neighbors differ. The checkerboard argument that rejects some endpoint
+/// pairs on a plain grid still holds when both wrapping dimensions are
+/// even, but an odd one joins two cells of the same color, so then any pair
+/// is searched. Tile the result with `wrapped_path_to_road_grid`.
+#[wasm_bindgen]
+pub fn find_wrapped_road_path(
+    start_row: i32,
+    start_col: i32,
+    end_row: i32,
+    end_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+    max_iterations: u32,
+) -> JsValue {
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+    let start = Point::new(start_row, start_col);
+    let end = Point::new(end_row, end_col);
+    let result = solve_wrapped_path(start, end, grid_size, max_iterations);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Convert a path from `find_wrapped_road_path` to a road grid
+///
+/// A step across an edge is tiled as leaving that edge, so the road meets
+/// its continuation on the opposite edge; those connections point off the
+/// grid. Uses the default tile options.
+#[wasm_bindgen]
+pub fn wrapped_path_to_road_grid(path_js: JsValue, grid_rows: i32, grid_cols: i32) -> JsValue {
+    let Ok(path) = serde_wasm_bindgen::from_value::<Vec<Point>>(path_js) else {
+        return JsValue::NULL;
+    };
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+    let options = TileOptions {
+        wrap: true,
+        ..TileOptions::default()
+    };
+    let result = path_to_tiles(&path, grid_size, options);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Find a route whose every step claims a 2x2 footprint, the footprints
+/// covering the grid without overlapping.
+///
+/// Footprints that cover everything without overlap line up on the 2x2
+/// blocks starting at the top-left cell, and each step moves by a whole
+/// block; so this is the plain search on the grid of blocks, with the
+/// result mapped back to cells. Endpoints are top-left cells of blocks.
+fn solve_thick_path(
+    start: Point,
+    end: Point,
+    grid_size: GridSize,
+    max_iterations: u32,
+) -> ThickPathResult {
+    let block_grid = GridSize {
+        rows: grid_size.rows.max(0) / 2,
+        cols: grid_size.cols.max(0) / 2,
+    };
+    let rejected = |result: PathResult| ThickPathResult {
+        result,
+        block_path: vec![],
+        block_grid,
+    };
+
+    if let Some(error) = endpoint_error(start, end, grid_size) {
+        return rejected(PathResult::invalid(error));
+    }
+    if grid_size.rows % 2 != 0 || grid_size.cols % 2 != 0 {
+        return rejected(PathResult::rejected(format!(
+            "2x2 footprints cover the grid only with even dimensions, got {} rows by {} cols",
+            grid_size.rows, grid_size.cols
+        )));
+    }
+    for (name, p) in [("start", start), ("end", end)] {
+        if p.row % 2 != 0 || p.col % 2 != 0 {
+            return rejected(PathResult::rejected(format!(
+                "the {} ({}, {}) is not the top-left cell of a 2x2 block",
+                name, p.row, p.col
+            )));
+        }
+    }
+
+    let to_block = |p: Point| Point::new(p.row / 2, p.col / 2);
+    let to_cells = |path: &[Point]| path.iter().map(|p| Point::new(p.row * 2, p.col * 2)).collect();
+    let mut result = solve_road_path(
+        to_block(start),
+        to_block(end),
+        block_grid,
+        max_iterations,
+        SearchOptions::default(),
+    );
+    let block_path = std::mem::take(&mut result.path);
+    result.path = to_cells(&block_path);
+    result.best_partial = result.best_partial.as_deref().map(to_cells);
+    ThickPathResult {
+        result,
+        block_path,
+        block_grid,
+    }
+}
+
+/// Find a wide-road route whose every step claims a 2x2 footprint
+///
+/// The footprints cover the grid without overlapping, which needs even grid
+/// dimensions and endpoints on the top-left cell of a 2x2 block; otherwise
+/// `found` is false with a `reason`. Returns `{ result, block_path,
+/// block_grid }`: `result.path` lists the top-left cell of each footprint,
+/// and `block_path` the same route on the half-size `block_grid`, which
+/// `path_to_road_grid` tiles for drawing at double scale.
+#[wasm_bindgen]
+pub fn find_thick_road_path(
+    start_row: i32,
+    start_col: i32,
+    end_row: i32,
+    end_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+    max_iterations: u32,
+) -> JsValue {
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+    let start = Point::new(start_row, start_col);
+    let result = solve_thick_path(start, Point::new(end_row, end_col), grid_size, max_iterations);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Build a boustrophedon (snake) Hamiltonian path of a full rectangular grid
+/// from the corner `start`, without searching.
+///
+/// The path sweeps the row (or with `by_columns`, the column) containing
+/// `start` away from it, steps to the next one and sweeps back, and so on
+/// across the grid. It ends on the far side, at a corner when the number of
+/// sweeps is odd. None when `start` is not a corner.
+fn build_snake_path(start: Point, grid_size: GridSize, by_columns: bool) -> Option<Vec<Point>> {
+    let GridSize { rows, cols } = grid_size;
+    let corner = (start.row == 0 || start.row == rows - 1) && (start.col == 0 || start.col == cols - 1);
+    if grid_size.dimension_error().is_some() || !corner {
+        return None;
+    }
+
+    let (sweeps, length) = if by_columns { (cols, rows) } else { (rows, cols) };
+    let mut path = Vec::with_capacity(grid_size.cell_count().unwrap_or(0));
+    for i in 0..sweeps {
+        for j in 0..length {
+            let j = if i % 2 == 0 { j } else { length - 1 - j };
+            let (row, col) = if by_columns { (j, i) } else { (i, j) };
+            let row = if start.row == 0 { row } else { rows - 1 - row };
+            let col = if start.col == 0 { col } else { cols - 1 - col };
+            path.push(Point::new(row, col));
+        }
+    }
+    Some(path)
+}
+
+/// Snake path over every cell of a rectangular grid from a corner
+///
+/// Sweeps the rows back and forth starting along the start's row, built in
+/// O(cells) without searching. Returns the Points in path order, or null
+/// when the start is not a corner of the grid. `find_road_path` with the
+/// "snake" strategy returns these (or their column-wise counterparts)
+/// directly when the end matches.
+#[wasm_bindgen]
+pub fn snake_path(start_row: i32, start_col: i32, grid_rows: i32, grid_cols: i32) -> JsValue {
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+    match build_snake_path(Point::new(start_row, start_col), grid_size, false) {
+        Some(path) => serde_wasm_bindgen::to_value(&path).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+/// Build a Hamiltonian cycle of a full rectangular grid without searching.
+///
+/// Row 0 runs right, the rest of the grid (minus column 0) is swept row by
+/// row in a snake, and column 0 carries the loop back up. That closes when
+/// the row count is even; an odd-by-even grid is built transposed. Returns
+/// None unless one side is even and both are at least 2, which is exactly
+/// when a rectangle has a Hamiltonian cycle.
+fn build_snake_cycle(grid_size: GridSize) -> Option<Vec<Point>> {
+    let GridSize { rows, cols } = grid_size;
+    if rows < 2 || cols < 2 {
+        return None;
+    }
+    if rows % 2 != 0 {
+        if cols % 2 != 0 {
+            return None;
+        }
+        let transposed = build_snake_cycle(GridSize { rows: cols, cols: rows })?;
+        return Some(transposed.into_iter().map(|p| Point::new(p.col, p.row)).collect());
+    }
+
+    let mut cycle: Vec<Point> = (0..cols).map(|col| Point::new(0, col)).collect();
+    for row in 1..rows {
+        if row % 2 == 1 {
+            cycle.extend((1..cols).rev().map(|col| Point::new(row, col)));
+        } else {
+            cycle.extend((1..cols).map(|col| Point::new(row, col)));
+        }
+    }
+    cycle.extend((1..rows).rev().map(|row| Point::new(row, 0)));
+    Some(cycle)
+}
+
+/// Closed loop over every cell of a rectangular grid, built in O(cells)
+///
+/// Returns the cycle as Points in loop order (the last cell is adjacent to
+/// the first, starting at the top-left corner), the same shape
+/// `find_road_cycle` returns. Only full rectangles are handled: the grid
+/// needs both sides at least 2 and one of them even, otherwise no cycle
+/// exists and the result is null.
+#[wasm_bindgen]
+pub fn snake_cycle(grid_rows: i32, grid_cols: i32) -> JsValue {
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+    match build_snake_cycle(grid_size) {
+        Some(cycle) => serde_wasm_bindgen::to_value(&cycle).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+/// Convert a closed loop (as returned by `find_road_cycle`) to a road grid
+///
+/// Every cell gets two connections and the lanes wrap around the closing edge.
+/// `valid` is false when the path is not a closed loop or cannot be tiled.
+#[wasm_bindgen]
+pub fn cycle_to_road_grid(path_js: JsValue, grid_rows: i32, grid_cols: i32) -> JsValue {
+    let path: Vec<Point> = match serde_wasm_bindgen::from_value(path_js) {
+        Ok(p) => p,
+        Err(_) => return JsValue::NULL,
+    };
+
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+
+    let result = cycle_to_tiles(&path, grid_size);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Append one cell to a path tiled by `path_to_road_grid`
+///
+/// Only the old goal (now a middle tile) and the new goal are retiled, so
+/// live editing stays cheap on long paths. Returns `{ grid, valid,
+/// required_entry_port }`, or null when the new cell is not adjacent to the
+/// last path cell, is out of bounds or already on the path, or when the
+/// inputs cannot be parsed.
+#[wasm_bindgen]
+pub fn append_cell_to_grid(
+    prev_grid_js: JsValue,
+    prev_path_js: JsValue,
+    new_row: i32,
+    new_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+) -> JsValue {
+    let grid: Vec<Vec<Option<CellData>>> = match serde_wasm_bindgen::from_value(prev_grid_js) {
+        Ok(g) => g,
+        Err(_) => return JsValue::NULL,
+    };
+    let path: Vec<Point> = match serde_wasm_bindgen::from_value(prev_path_js) {
+        Ok(p) => p,
+        Err(_) => return JsValue::NULL,
+    };
+
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+
+    match append_to_tiles(grid, &path, Point::new(new_row, new_col), grid_size) {
+        Some(result) => serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+/// Reverse the part of a path between two indices (inclusive) in a grid tiled
+/// by `path_to_road_grid`
+///
+/// Cells before the segment keep their tiles; the rest is retiled with the
+/// lanes carried on from them, and reversed cells get their new path index.
+/// Returns `{ path, result }` with the reversed path and its RoadGridResult,
+/// or null when the indices are out of order or past the end, the path is
+/// invalid, the reversed segment no longer joins its neighbors, the grid
+/// lacks the path's tiles or the inputs cannot be parsed.
+#[wasm_bindgen]
+pub fn reverse_segment(
+    grid_js: JsValue,
+    path_js: JsValue,
+    from_index: u32,
+    to_index: u32,
+    grid_rows: i32,
+    grid_cols: i32,
+) -> JsValue {
+    let grid: Vec<Vec<Option<CellData>>> = match serde_wasm_bindgen::from_value(grid_js) {
+        Ok(g) => g,
+        Err(_) => return JsValue::NULL,
+    };
+    let path: Vec<Point> = match serde_wasm_bindgen::from_value(path_js) {
+        Ok(p) => p,
+        Err(_) => return JsValue::NULL,
+    };
+
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+
+    let (from, to) = (from_index as usize, to_index as usize);
+    match reverse_segment_tiles(grid, &path, from, to, grid_size) {
+        Some(reversal) => serde_wasm_bindgen::to_value(&reversal).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+/// Count Hamiltonian paths from start to end without storing them
+fn count_paths(start: Point, end: Point, grid_size: GridSize, max_iterations: u32) -> PathCount {
+    let mut state = PathState::new(grid_size, max_iterations);
+    let mut count = 0u32;
+
+    if state.parity_allows_path(start, end) {
+        enumerate_paths_internal(&mut state, start, end, &mut |_| {
+            count += 1;
+            true
+        });
+    }
+
+    PathCount {
+        count,
+        iterations: state.iterations,
+        capped: state.iterations > state.max_iterations,
+    }
+}
+
+/// Search every Hamiltonian path for the one with the fewest turns
+///
+/// Neighbors are ordered to continue straight on ties and connectivity
+/// pruning is on, so smooth paths turn up early and tighten the bound.
+fn solve_smoothest_path(
+    start: Point,
+    end: Point,
+    grid_size: GridSize,
+    max_iterations: u32,
+    blocked: Vec<Point>,
+) -> SmoothestPathResult {
+    let mut result = SmoothestPathResult {
+        found: false,
+        path: vec![],
+        turns: 0,
+        iterations: 0,
+        optimal: true,
+    };
+    if endpoint_error(start, end, grid_size).is_some() {
+        return result;
+    }
+
+    let options = SearchOptions {
+        blocked,
+        pruning: true,
+        tie_break: TieBreak::PreferStraight,
+        ..Default::default()
+    };
+    let mut state = PathState::with_options(grid_size, max_iterations, options);
+    if state.infeasibility_reason(start, end).is_some() {
+        return result;
+    }
+
+    let mut best = None;
+    result.optimal = smoothest_path_internal(&mut state, start, end, &mut best);
+    result.iterations = state.iterations.min(state.max_iterations);
+    if let Some((path, turns)) = best {
+        result.found = true;
+        result.path = path;
+        result.turns = turns;
+    }
+    result
+}
+
+/// Find the Hamiltonian path with the fewest direction changes
+///
+/// Branch-and-bound over all paths: branches that already turn as often as
+/// the best path found are cut. Returns `{ found, path, turns, iterations,
+/// optimal }`; `optimal` is false when `max_iterations` ran out first, in
+/// which case `path` is the smoothest one seen. This is exponential in the
+/// worst case, so keep the budget bounded on larger grids. `blocked_js` is
+/// an optional array of obstacle cells; an invalid value returns null.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn find_smoothest_road_path(
+    start_row: i32,
+    start_col: i32,
+    end_row: i32,
+    end_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+    max_iterations: u32,
+    blocked_js: JsValue,
+) -> JsValue {
+    let blocked = match parse_points(blocked_js) {
+        Some(b) => b,
+        None => return JsValue::NULL,
+    };
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+    let result = solve_smoothest_path(
+        Point::new(start_row, start_col),
+        Point::new(end_row, end_col),
+        grid_size,
+        max_iterations,
+        blocked,
+    );
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Count the Hamiltonian paths between two cells
+///
+/// Enumerates every completion but keeps only a counter, so memory stays flat.
+/// When `capped` is true the iteration budget cut the enumeration short and
+/// `count` is a lower bound.
+#[wasm_bindgen]
+pub fn count_hamiltonian_paths(
+    start_row: i32,
+    start_col: i32,
+    end_row: i32,
+    end_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+    max_iterations: u32,
+) -> JsValue {
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+    let result = count_paths(
+        Point::new(start_row, start_col),
+        Point::new(end_row, end_col),
+        grid_size,
+        max_iterations,
+    );
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Weight of the search effort (log10 of the iterations to the first
+/// path, saturating at `RATING_EFFORT_SATURATION`) in a puzzle rating
+const RATING_EFFORT_WEIGHT: f64 = 0.4;
+/// Weight of solution scarcity: 1 for a unique path, falling with log10 of
+/// the count to 0 at `RATING_COUNT_SATURATION` paths. It is scaled by the
+/// normalized area below, since tiny grids have few paths but are trivial.
+const RATING_SCARCITY_WEIGHT: f64 = 0.35;
+/// Weight of the grid area, from 0 at 2x2 to 1 at `RATING_AREA_SATURATION`
+/// cells
+const RATING_AREA_WEIGHT: f64 = 0.25;
+const RATING_EFFORT_SATURATION: f64 = 1e5;
+const RATING_COUNT_SATURATION: f64 = 1e4;
+const RATING_AREA_SATURATION: f64 = 64.0;
+
+/// Rate `start`..`end` as a puzzle. The score is the weighted sum of the
+/// three metrics above, each normalized to [0, 1], and maps linearly onto
+/// 1-5 stars. Both the search and the count get `max_iterations` each; a
+/// search that runs out counts as maximal effort. None for invalid input.
+fn rate_puzzle_internal(
+    start: Point,
+    end: Point,
+    grid_size: GridSize,
+    max_iterations: u32,
+) -> Option<PuzzleRating> {
+    if endpoint_error(start, end, grid_size).is_some() {
+        return None;
+    }
+    let search = solve_road_path(start, end, grid_size, max_iterations, SearchOptions::default());
+    let count = count_paths(start, end, grid_size, max_iterations);
+    if !search.found && count.count == 0 {
+        return Some(PuzzleRating {
+            solvable: false,
+            stars: 0,
+            score: 0.0,
+            first_solution_iterations: search.iterations,
+            solution_count: 0,
+            count_capped: count.capped,
+        });
+    }
+
+    let normalized =
+        |value: f64, saturation: f64| (value.log10() / saturation.log10()).clamp(0.0, 1.0);
+    let effort = if search.found {
+        normalized(search.iterations as f64, RATING_EFFORT_SATURATION)
+    } else {
+        1.0
+    };
+    let scarcity = 1.0 - normalized(count.count.max(1) as f64, RATING_COUNT_SATURATION);
+    let area = grid_size.rows as f64 * grid_size.cols as f64;
+    let size = ((area - 4.0) / (RATING_AREA_SATURATION - 4.0)).clamp(0.0, 1.0);
+    let score = RATING_EFFORT_WEIGHT * effort
+        + RATING_SCARCITY_WEIGHT * scarcity * size
+        + RATING_AREA_WEIGHT * size;
+
+    Some(PuzzleRating {
+        solvable: true,
+        stars: 1 + (score * 4.0).round() as u8,
+        score,
+        first_solution_iterations: search.iterations,
+        solution_count: count.count,
+        count_capped: count.capped,
+    })
+}
+
+/// Rate a start/end pair as a puzzle, from 1 to 5 stars
+///
+/// Combines the iterations the search needs for its first path, the number of
+/// Hamiltonian paths (as `count_hamiltonian_paths` counts them) and the grid
+/// area; see `RATING_*_WEIGHT` for the weighting. Unsolvable pairs get
+/// `solvable: false` and 0 stars; invalid dimensions or endpoints return null.
+#[wasm_bindgen]
+pub fn rate_puzzle(
+    start_row: i32,
+    start_col: i32,
+    end_row: i32,
+    end_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+    max_iterations: u32,
+) -> JsValue {
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+    let start = Point::new(start_row, start_col);
+    let end = Point::new(end_row, end_col);
+    match rate_puzzle_internal(start, end, grid_size, max_iterations) {
+        Some(rating) => serde_wasm_bindgen::to_value(&rating).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+/// Find a path like `find_road_path`, reporting progress to JavaScript
+///
+/// `on_progress(iterations, depth)` is called every `progress_interval`
+/// iterations (0 picks a default of 1024) with the iteration count so far and
+/// the current path length. If the callback throws, the search stops as if
+/// cancelled (`cancelled: true`).
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn find_road_path_with_progress(
+    start_row: i32,
+    start_col: i32,
+    end_row: i32,
+    end_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+    max_iterations: u32,
+    blocked_js: JsValue,
+    on_progress: js_sys::Function,
+    progress_interval: u32,
+) -> JsValue {
+    let start = Point::new(start_row, start_col);
+    let end = Point::new(end_row, end_col);
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+
+    let blocked = match parse_points(blocked_js) {
+        Some(b) => b,
+        None => return JsValue::NULL,
+    };
+
+    let hook: ProgressFn = Box::new(move |iterations, depth| {
+        on_progress
+            .call2(&JsValue::NULL, &JsValue::from(iterations), &JsValue::from(depth as u32))
+            .is_ok()
+    });
+
+    let options = SearchOptions {
+        blocked,
+        on_progress: Some(hook),
+        progress_interval,
+        ..Default::default()
+    };
+    let result = solve_road_path(start, end, grid_size, max_iterations, options);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Find a path over a non-rectangular board
+///
+/// `mask_js` is a flat, row-major boolean array of length `grid_rows * grid_cols`
+/// marking which cells exist (L-shapes, donuts, ...). Only existing cells have
+/// to be covered. Returns null when the mask has the wrong length.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn find_road_path_masked(
+    start_row: i32,
+    start_col: i32,
+    end_row: i32,
+    end_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+    max_iterations: u32,
+    mask_js: JsValue,
+) -> JsValue {
+    let start = Point::new(start_row, start_col);
+    let end = Point::new(end_row, end_col);
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+
+    let mask: Vec<bool> = match serde_wasm_bindgen::from_value(mask_js) {
+        Ok(m) => m,
+        Err(_) => return JsValue::NULL,
+    };
+    // Bad dimensions are reported by the search itself
+    if grid_size.dimension_error().is_none() && grid_size.cell_count() != Some(mask.len()) {
+        return JsValue::NULL;
+    }
+
+    let options = SearchOptions {
+        cell_mask: Some(mask),
+        ..Default::default()
+    };
+    let result = solve_road_path(start, end, grid_size, max_iterations, options);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Find a path like `find_road_path`, but abortable from JavaScript
+///
+/// `should_cancel` is called with no arguments every 1024 iterations. When it
+/// returns a truthy value (or throws), the search stops and the result has
+/// `found: false` and `cancelled: true`, with `iterations` reporting the work
+/// done so far. From a Web Worker, the callback can read a flag in a
+/// SharedArrayBuffer via `Atomics.load` that the UI thread sets on "stop".
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn find_road_path_cancellable(
+    start_row: i32,
+    start_col: i32,
+    end_row: i32,
+    end_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+    max_iterations: u32,
+    blocked_js: JsValue,
+    should_cancel: js_sys::Function,
+) -> JsValue {
+    let start = Point::new(start_row, start_col);
+    let end = Point::new(end_row, end_col);
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+
+    let blocked = match parse_points(blocked_js) {
+        Some(b) => b,
+        None => return JsValue::NULL,
+    };
+
+    let hook: CancelFn = Box::new(move || {
+        should_cancel
+            .call0(&JsValue::NULL)
+            .map_or(true, |v| v.is_truthy())
+    });
+
+    let options = SearchOptions {
+        blocked,
+        should_cancel: Some(hook),
+        ..Default::default()
+    };
+    let result = solve_road_path(start, end, grid_size, max_iterations, options);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Find a path like `find_road_path` and report where the search spent its
+/// effort
+///
+/// The result's `depth_counts[d]` is the number of nodes expanded with `d`
+/// cells already on the path (0 is the start cell). Counting is skipped
+/// entirely by the other search functions.
+///
+/// With `debug: true`, a failed search also returns `visited_grid`, the
+/// visited flags at its deepest point, to draw next to `best_partial`.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn find_road_path_instrumented(
+    start_row: i32,
+    start_col: i32,
+    end_row: i32,
+    end_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+    max_iterations: u32,
+    blocked_js: JsValue,
+    seed: u32,
+    pruning: Option<bool>,
+    debug: Option<bool>,
+) -> JsValue {
+    let start = Point::new(start_row, start_col);
+    let end = Point::new(end_row, end_col);
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+
+    let blocked = match parse_points(blocked_js) {
+        Some(b) => b,
+        None => return JsValue::NULL,
+    };
+
+    let options = SearchOptions {
+        blocked,
+        seed,
+        pruning: pruning.unwrap_or(false),
+        record_depths: true,
+        record_visited: debug.unwrap_or(false),
+        ..Default::default()
+    };
+    let result = solve_road_path(start, end, grid_size, max_iterations, options);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Neighbors of `current` in the order the search would try them
+///
+/// `visited_js` lists the cells already on the path (and any obstacles).
+/// Returns `[{ row, col, direction, unvisited_neighbors }]` sorted exactly
+/// like the path search does (Warnsdorff's rule, ties in up, right, down,
+/// left order). Returns null if `current` is outside the grid.
+#[wasm_bindgen]
+pub fn next_candidates(
+    visited_js: JsValue,
+    current_row: i32,
+    current_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+) -> JsValue {
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+    let current = Point::new(current_row, current_col);
+    if !grid_size.contains(current) {
+        return JsValue::NULL;
+    }
+
+    let visited = match parse_points(visited_js) {
+        Some(v) => v,
+        None => return JsValue::NULL,
+    };
+
+    let candidates = next_candidates_internal(&visited, current, grid_size);
+    serde_wasm_bindgen::to_value(&candidates).unwrap_or(JsValue::NULL)
+}
+
+/// Cells within `steps` moves of the start, for UI highlighting
+///
+/// Breadth-first over free cells (skipping `blocked_js`), without the
+/// full-cover constraint. Returns `[{ row, col, distance }]` including the
+/// start at distance 0; empty when the start is blocked or out of bounds.
+#[wasm_bindgen]
+pub fn reachable_cells(
+    start_row: i32,
+    start_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+    steps: u32,
+    blocked_js: JsValue,
+) -> JsValue {
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+
+    let blocked = match parse_points(blocked_js) {
+        Some(b) => b,
+        None => return JsValue::NULL,
+    };
+
+    let options = SearchOptions {
+        blocked,
+        ..Default::default()
+    };
+    let state = PathState::with_options(grid_size, 0, options);
+    let cells = reachable_within(&state, Point::new(start_row, start_col), steps);
+    serde_wasm_bindgen::to_value(&cells).unwrap_or(JsValue::NULL)
+}
+
+/// Shortest orthogonal route from start to end, for hints
+///
+/// Breadth-first over free cells (skipping `blocked_js`) without the
+/// full-cover constraint. Returns `{ found, path, length }` where `length`
+/// counts steps; `found` is false when the end can't be reached or either
+/// endpoint is blocked or out of bounds. Returns null for invalid grid
+/// dimensions.
+#[wasm_bindgen]
+pub fn shortest_path(
+    start_row: i32,
+    start_col: i32,
+    end_row: i32,
+    end_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+    blocked_js: JsValue,
+) -> JsValue {
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+    if grid_size.dimension_error().is_some() {
+        return JsValue::NULL;
+    }
+
+    let blocked = match parse_points(blocked_js) {
+        Some(b) => b,
+        None => return JsValue::NULL,
+    };
+
+    let options = SearchOptions {
+        blocked,
+        ..Default::default()
+    };
+    let state = PathState::with_options(grid_size, 0, options);
+    let start = Point::new(start_row, start_col);
+    let end = Point::new(end_row, end_col);
+    let result = shortest_path_between(&state, start, end);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Cells that split the free part of a board, to explain why it has no path
+///
+/// Returns `{ components, cut_cells, ruled_out }`: the number of connected
+/// groups of free cells (skipping `blocked_js`), the articulation cells as
+/// `{ row, col, pieces }` in row-major order, and whether the board alone
+/// makes a path over every free cell impossible (disconnected free cells, or
+/// a cell leaving three or more pieces). Returns null for invalid grid
+/// dimensions or an unparsable blocked list.
+#[wasm_bindgen]
+pub fn cut_cells(grid_rows: i32, grid_cols: i32, blocked_js: JsValue) -> JsValue {
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+    if grid_size.dimension_error().is_some() {
+        return JsValue::NULL;
+    }
+
+    let blocked = match parse_points(blocked_js) {
+        Some(b) => b,
+        None => return JsValue::NULL,
+    };
+
+    let options = SearchOptions {
+        blocked,
+        ..Default::default()
+    };
+    let state = PathState::with_options(grid_size, 0, options);
+    serde_wasm_bindgen::to_value(&find_cut_cells(&state)).unwrap_or(JsValue::NULL)
+}
+
+/// Cells a partial path has not covered yet, for a live "stranded cells"
+/// warning while drawing
+///
+/// Returns `{ cells, all_reachable }`: the grid cells neither on the path nor
+/// in `blocked_js` (row-major), and whether all of them are still reachable
+/// from the last path cell. Returns null when `is_valid_path` would reject
+/// the path, a path cell is blocked or the blocked list can't be parsed.
+#[wasm_bindgen]
+pub fn remaining_cells(
+    path_js: JsValue,
+    grid_rows: i32,
+    grid_cols: i32,
+    blocked_js: JsValue,
+) -> JsValue {
+    let path: Vec<Point> = match serde_wasm_bindgen::from_value(path_js) {
+        Ok(p) => p,
+        Err(_) => return JsValue::NULL,
+    };
+
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+
+    let blocked = match parse_points(blocked_js) {
+        Some(b) => b,
+        None => return JsValue::NULL,
+    };
+
+    let options = SearchOptions {
+        blocked,
+        ..Default::default()
+    };
+    let mut state = PathState::with_options(grid_size, 0, options);
+    match remaining_after(&mut state, &path) {
+        Some(remaining) => serde_wasm_bindgen::to_value(&remaining).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+/// `performance.now()` of the JS host, if it has the performance API
+fn performance_clock() -> Option<ClockFn> {
+    let performance = js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("performance"))
+        .ok()
+        .filter(JsValue::is_object)?;
+    let now: js_sys::Function = js_sys::Reflect::get(&performance, &JsValue::from_str("now"))
+        .ok()?
+        .dyn_into()
+        .ok()?;
+    Some(Box::new(move || {
+        now.call0(&performance)
+            .ok()
+            .and_then(|v| v.as_f64())
+            .unwrap_or(f64::INFINITY)
+    }))
+}
+
+/// Start a path search that can be continued across calls
+///
+/// Returns `{ result, snapshot }`. When `max_iterations` runs out before the
+/// search finishes, `snapshot` is an opaque object holding the visited cells
+/// and the explicit DFS stack; pass it to `resume_road_path` to continue
+/// from exactly that node. Otherwise `snapshot` is null and `result` is
+/// final. Only obstacles are supported as constraints.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn find_road_path_resumable(
+    start_row: i32,
+    start_col: i32,
+    end_row: i32,
+    end_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+    max_iterations: u32,
+    blocked_js: JsValue,
+) -> JsValue {
+    let start = Point::new(start_row, start_col);
+    let end = Point::new(end_row, end_col);
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+
+    let blocked = match parse_points(blocked_js) {
+        Some(b) => b,
+        None => return JsValue::NULL,
+    };
+
+    let result = start_resumable_search(start, end, grid_size, max_iterations, blocked);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Continue a search suspended by `find_road_path_resumable` or an earlier
+/// `resume_road_path`, with `extra_iterations` more budget
+///
+/// Returns `{ result, snapshot }` like `find_road_path_resumable`, with
+/// `result.iterations` counting every call so far. Splitting a search into
+/// slices this way visits the same nodes in the same order as one call with
+/// the combined budget. Returns null for a malformed snapshot.
+#[wasm_bindgen]
+pub fn resume_road_path(snapshot_js: JsValue, extra_iterations: u32) -> JsValue {
+    let snapshot: SearchSnapshot = match serde_wasm_bindgen::from_value(snapshot_js) {
+        Ok(s) => s,
+        Err(_) => return JsValue::NULL,
+    };
+
+    match resume_search(snapshot, extra_iterations) {
+        Some(result) => serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+/// Find a path like `find_road_path`, bounded by wall-clock time
+///
+/// The elapsed time is checked with `performance.now()` every 1024
+/// iterations; once `time_budget_ms` has passed the search stops with
+/// `found: false` and `timed_out: true`. Hosts without the performance API
+/// fall back to the `max_iterations` bound alone.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn find_road_path_timed(
+    start_row: i32,
+    start_col: i32,
+    end_row: i32,
+    end_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+    max_iterations: u32,
+    blocked_js: JsValue,
+    time_budget_ms: f64,
+) -> JsValue {
+    let start = Point::new(start_row, start_col);
+    let end = Point::new(end_row, end_col);
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+
+    let blocked = match parse_points(blocked_js) {
+        Some(b) => b,
+        None => return JsValue::NULL,
+    };
+
+    let options = SearchOptions {
+        blocked,
+        clock: performance_clock(),
+        time_budget_ms,
+        ..Default::default()
+    };
+    let result = solve_road_path(start, end, grid_size, max_iterations, options);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Find a path like `find_road_path`, also allowing diagonal steps
+///
+/// Diagonal steps never cross each other, so the result tiles without
+/// intersections; `path_to_road_grid` uses the `diagonal-*` tiles for them.
+/// The checkerboard parity pre-check doesn't apply here, so endpoints of the
+/// same color are fine.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn find_road_path_diagonal(
+    start_row: i32,
+    start_col: i32,
+    end_row: i32,
+    end_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+    max_iterations: u32,
+    blocked_js: JsValue,
+) -> JsValue {
+    let start = Point::new(start_row, start_col);
+    let end = Point::new(end_row, end_col);
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+
+    let blocked = match parse_points(blocked_js) {
+        Some(b) => b,
+        None => return JsValue::NULL,
+    };
+
+    let options = SearchOptions {
+        blocked,
+        diagonals: true,
+        ..Default::default()
+    };
+    let result = solve_road_path(start, end, grid_size, max_iterations, options);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Hamiltonian path from `start` that may end on any cell
+fn solve_open_end_path(start: Point, grid_size: GridSize, max_iterations: u32) -> PathResult {
+    let options = SearchOptions {
+        open_end: true,
+        ..Default::default()
+    };
+    solve_road_path(start, start, grid_size, max_iterations, options)
+}
+
+/// Find a path like `find_road_path` that covers every cell from the start
+/// and ends wherever it runs out of cells
+///
+/// The end cell is the last point of `path`. Only the start has to pass the
+/// parity check (on an odd number of cells it must be on the majority
+/// color), and since any full cover counts, the search usually finishes
+/// much sooner than one with a fixed end.
+#[wasm_bindgen]
+pub fn find_road_path_open_end(
+    start_row: i32,
+    start_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+    max_iterations: u32,
+) -> JsValue {
+    let start = Point::new(start_row, start_col);
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+    let result = solve_open_end_path(start, grid_size, max_iterations);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Find a path like `find_road_path`, biased by per-cell weights
+///
+/// `weights_js` is a `number[][]` indexed `[row][col]`. Among neighbors that
+/// tie under Warnsdorff's rule, lower-weight cells are tried first, e.g. high
+/// weights on the border keep the route away from it where possible. This is
+/// a soft preference: every cell is still covered exactly once and a path is
+/// found whenever the unweighted search would find one. Missing entries
+/// count as 0; malformed weights return null.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn find_road_path_weighted(
+    start_row: i32,
+    start_col: i32,
+    end_row: i32,
+    end_col: i32,
+    grid_rows: i32,
+    grid_cols: i32,
+    max_iterations: u32,
+    weights_js: JsValue,
+    blocked_js: JsValue,
+) -> JsValue {
+    let start = Point::new(start_row, start_col);
+    let end = Point::new(end_row, end_col);
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+
+    let weights: Vec<Vec<i32>> = match serde_wasm_bindgen::from_value(weights_js) {
+        Ok(w) => w,
+        Err(_) => return JsValue::NULL,
+    };
+
+    let blocked = match parse_points(blocked_js) {
+        Some(b) => b,
+        None => return JsValue::NULL,
+    };
+
+    let options = SearchOptions {
+        blocked,
+        weights: Some(weights),
+        ..Default::default()
+    };
+    let result = solve_road_path(start, end, grid_size, max_iterations, options);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Convert a path to a road grid with tile assignments
+///
+/// A single-cell path yields one "start" marker with no connections (the
+/// start is also the goal) and `valid: true`; an empty path is invalid.
+///
+/// With `allow_lane_change`, tiles that switch between the inner and outer
+/// lane (e.g. `straight-h-84`) may be used; omitted means false.
+///
+/// `variant_preference` is one of "curve" (default), "sharp" or "alternate"
+/// and picks between the equivalent curve and sharp corner tiles.
+///
+/// `start_port` ("12", "23" or "34") is the port set the start cell exits on;
+/// omitted means "23" (outer lane). "12" starts on the inner lane and "34" on
+/// the third lane of a 3-lane road.
+///
+/// `output_order` is "row_major" (default, `grid[row][col]`) or
+/// "column_major" (`grid[col][row]`). It only changes how `grid` is nested;
+/// cells, connections and the `failure` cell keep their row/col meaning.
+///
+/// `lane_lock` ("12", "23" or "34") keeps every connection of the route on
+/// that port set, for art with a single lane family: the start exits on it,
+/// lane-changing tiles are never used, and a cell whose turn forces a lane
+/// change (such as a step onto a diagonal) makes the result `valid: false`
+/// with that cell in `failure` and the lock named in `reason`. A
+/// `start_port` other than the locked lane returns null.
+///
+/// With `with_alternatives`, every cell also lists in `alternatives` the
+/// other tiles with the same connections (such as the sharp twin of a
+/// curve), for editors that let the user cycle through them; omitted means
+/// false and empty lists.
+/// An unknown value for any of the strings returns null.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn path_to_road_grid(
+    path_js: JsValue,
+    grid_rows: i32,
+    grid_cols: i32,
+    allow_lane_change: Option<bool>,
+    variant_preference: Option<String>,
+    start_port: Option<String>,
+    output_order: Option<String>,
+    lane_lock: Option<String>,
+    with_alternatives: Option<bool>,
+) -> JsValue {
+    let path: Vec<Point> = match serde_wasm_bindgen::from_value(path_js) {
+        Ok(p) => p,
+        Err(_) => return JsValue::NULL,
+    };
+
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+
+    let Some(mut options) = parse_tile_options(allow_lane_change, variant_preference, start_port)
+    else {
+        return JsValue::NULL;
+    };
+    if let Some(lane) = lane_lock.as_deref() {
+        let Some(lane) = PortSet::parse(lane) else {
+            return JsValue::NULL;
+        };
+        if options.start_port.is_some_and(|port| port != lane) {
+            return JsValue::NULL;
+        }
+        options.lane_lock = Some(lane);
+    }
+    options.alternatives = with_alternatives.unwrap_or(false);
+    let order = match output_order.as_deref() {
+        None => GridOrder::default(),
+        Some(s) => match GridOrder::parse(s) {
+            Some(order) => order,
+            None => return JsValue::NULL,
+        },
+    };
+    let mut result = path_to_tiles(&path, grid_size, options);
+    result.grid = order.arrange(result.grid);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// The tiling options of `path_to_road_grid`; None for an unknown name
+fn parse_tile_options(
+    allow_lane_change: Option<bool>,
+    variant_preference: Option<String>,
+    start_port: Option<String>,
+) -> Option<TileOptions> {
+    let variant_preference = match variant_preference.as_deref() {
+        None => VariantPreference::default(),
+        Some(s) => VariantPreference::parse(s)?,
+    };
+    let start_port = match start_port.as_deref() {
+        None => None,
+        Some(s) => Some(PortSet::parse(s)?),
+    };
+    Some(TileOptions {
+        allow_lane_change: allow_lane_change.unwrap_or(false),
+        variant_preference,
+        start_port,
+        lane_lock: None,
+        alternatives: false,
+        wrap: false,
+    })
+}
+
+/// Tile several roads that must not cross onto one grid
+///
+/// `paths_js` is an array of paths (`Point[][]`). Each road is tiled like
+/// `path_to_road_grid`, with its own start and goal markers and lane
+/// propagation, and every cell gets the `road_id` of its path (its index in
+/// `paths_js`). If two roads share a cell, nothing is tiled and `conflict`
+/// names the cell and both roads. The options apply to every road; unknown
+/// values, like malformed paths, return null.
+#[wasm_bindgen]
+pub fn paths_to_road_grid(
+    paths_js: JsValue,
+    grid_rows: i32,
+    grid_cols: i32,
+    allow_lane_change: Option<bool>,
+    variant_preference: Option<String>,
+    start_port: Option<String>,
+) -> JsValue {
+    let paths: Vec<Vec<Point>> = match serde_wasm_bindgen::from_value(paths_js) {
+        Ok(p) => p,
+        Err(_) => return JsValue::NULL,
+    };
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+    let Some(options) = parse_tile_options(allow_lane_change, variant_preference, start_port) else {
+        return JsValue::NULL;
+    };
+    let result = paths_to_tiles(&paths, grid_size, options);
+    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
+}
+
+/// Upgrade a saved RoadGridResult to the current shape
+///
+/// `from_version` is the `schema_version` the payload was saved with (0 for
+/// payloads from before the field existed). Fields added since are filled in
+/// from each cell's tile and connections, or with what a grid of that time
+/// meant (a single road), and `schema_version` is set to the current version.
+/// Returns null when the payload doesn't parse or comes from a newer version.
+#[wasm_bindgen]
+pub fn migrate_grid(json: JsValue, from_version: u32) -> JsValue {
+    let result: RoadGridResult = match serde_wasm_bindgen::from_value(json) {
+        Ok(r) => r,
+        Err(_) => return JsValue::NULL,
+    };
+    match result.migrate(from_version) {
+        Some(migrated) => serde_wasm_bindgen::to_value(&migrated).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+/// Check port continuity of a (possibly hand-edited) road grid
+///
+/// Returns an array of mismatches `{ row, col, direction, kind }`, one per
+/// connection whose neighbor does not connect back on the same ports. An
+/// empty array means the grid is consistent.
+#[wasm_bindgen]
+pub fn validate_road_grid(grid_js: JsValue, grid_rows: i32, grid_cols: i32) -> JsValue {
+    let grid: Vec<Vec<Option<CellData>>> = match serde_wasm_bindgen::from_value(grid_js) {
+        Ok(g) => g,
+        Err(_) => return JsValue::NULL,
+    };
+
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+
+    let mismatches = validate_grid(&grid, grid_size);
+    serde_wasm_bindgen::to_value(&mismatches).unwrap_or(JsValue::NULL)
+}
+
+/// Check a user-supplied path before converting it
+///
+/// Returns `{ valid, first_bad_index, reason }`: the path must be non-empty,
+/// stay inside the grid, move between orthogonally adjacent cells and never
+/// revisit a cell. `first_bad_index` points at the first offending point.
+#[wasm_bindgen]
+pub fn is_valid_path(path_js: JsValue, grid_rows: i32, grid_cols: i32) -> JsValue {
+    let path: Vec<Point> = match serde_wasm_bindgen::from_value(path_js) {
+        Ok(p) => p,
+        Err(_) => return JsValue::NULL,
+    };
+
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+
+    serde_wasm_bindgen::to_value(&check_path(&path, grid_size)).unwrap_or(JsValue::NULL)
+}
+
+/// Whether a path covers the grid exactly once: every cell outside
+/// `blocked_js` once, in bounds, each step to an orthogonally adjacent cell
+///
+/// Stricter than `is_valid_path`, which accepts partial paths. False as well
+/// when the path or the blocked list can't be parsed.
+#[wasm_bindgen]
+pub fn is_hamiltonian_path(
+    path_js: JsValue,
+    grid_rows: i32,
+    grid_cols: i32,
+    blocked_js: JsValue,
+) -> bool {
+    let Ok(path) = serde_wasm_bindgen::from_value::<Vec<Point>>(path_js) else {
+        return false;
+    };
+    let Some(blocked) = parse_points(blocked_js) else {
+        return false;
+    };
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+    is_hamiltonian(&path, grid_size, &blocked)
+}
+
+/// Draw a path as text for logs and snapshot tests
+///
+/// Returns one line per grid row joined by newlines: 'S' marks the start,
+/// 'G' the goal, ─ │ ┌ ┐ └ ┘ the road between them and '.' the cells off
+/// the path. Returns undefined when `is_valid_path` would reject the path.
+#[wasm_bindgen]
+pub fn render_ascii(path_js: JsValue, grid_rows: i32, grid_cols: i32) -> Option<String> {
+    let path: Vec<Point> = serde_wasm_bindgen::from_value(path_js).ok()?;
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+    render_path_ascii(&path, grid_size)
+}
+
+/// Trace a tiled road grid as an SVG `<path>` element for vector export
+///
+/// Follows the cells in `path_index` order (one subpath per road of a
+/// `paths_to_road_grid` grid) along the road centerline, with each cell
+/// `cell_size` units wide: curve tiles become quadratic Bézier segments,
+/// sharp tiles a corner at the cell center. The element has `fill="none"`
+/// and no stroke, so style it in the surrounding SVG. Returns undefined for
+/// a malformed grid or one whose path indices don't form a connected path.
+#[wasm_bindgen]
+pub fn grid_to_svg_path(
+    grid_js: JsValue,
+    grid_rows: i32,
+    grid_cols: i32,
+    cell_size: f64,
+) -> Option<String> {
+    let grid: Vec<Vec<Option<CellData>>> = serde_wasm_bindgen::from_value(grid_js).ok()?;
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+    grid_svg_path(&grid, grid_size, cell_size)
+}
+
+/// Rotate a tiled road grid by quarter turns
+///
+/// Turns the whole design `quarter_turns` times clockwise (negative values
+/// turn counterclockwise): cells move, connections are remapped and every
+/// tile is replaced by its rotated equivalent of the same variant. Returns a
+/// RoadGridResult whose grid has rows and cols swapped for an odd number of
+/// turns, with `valid` reporting whether `validate_road_grid` is satisfied.
+/// Returns null for invalid dimensions, a malformed grid, an unknown tile id
+/// or a tile that has no rotated counterpart.
+#[wasm_bindgen]
+pub fn rotate_grid(
+    grid_js: JsValue,
+    grid_rows: i32,
+    grid_cols: i32,
+    quarter_turns: i32,
+) -> JsValue {
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+    if grid_size.dimension_error().is_some() {
+        return JsValue::NULL;
+    }
+
+    let grid: Vec<Vec<Option<CellData>>> = match serde_wasm_bindgen::from_value(grid_js) {
+        Ok(g) => g,
+        Err(_) => return JsValue::NULL,
+    };
+
+    match rotate_road_grid(&grid, grid_size, quarter_turns) {
+        Some(result) => serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+/// Encode a road grid as a compact string (see "Compact Grid Format")
+///
+/// Returns null when the grid cannot be parsed or contains a tile id, port
+/// string or direction the format cannot represent.
+#[wasm_bindgen]
+pub fn grid_to_compact_string(grid_js: JsValue) -> JsValue {
+    let grid: Vec<Vec<Option<CellData>>> = match serde_wasm_bindgen::from_value(grid_js) {
+        Ok(g) => g,
+        Err(_) => return JsValue::NULL,
+    };
+
+    match encode_compact_grid(&grid) {
+        Some(compact) => JsValue::from_str(&compact),
+        None => JsValue::NULL,
+    }
+}
+
+/// Flatten a road grid into tile placements for a tilemap engine
+///
+/// Returns `[{ row, col, tile_id, rotation }, ...]` for the occupied cells,
+/// in path order (by `path_index`, then `road_id`) so placement can be
+/// animated. Returns null for a malformed grid or a cell outside it.
+#[wasm_bindgen]
+pub fn grid_to_placements(grid_js: JsValue, grid_rows: i32, grid_cols: i32) -> JsValue {
+    let grid: Vec<Vec<Option<CellData>>> = match serde_wasm_bindgen::from_value(grid_js) {
+        Ok(g) => g,
+        Err(_) => return JsValue::NULL,
+    };
+
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+
+    match grid_placements(&grid, grid_size) {
+        Some(placements) => serde_wasm_bindgen::to_value(&placements).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+/// Cells that changed between two grids (the `grid` of RoadGridResults), as
+/// `{ row, col, old_tile, new_tile }` with null for an empty cell. Only the
+/// tile id and connections count. Null if either grid is malformed.
+#[wasm_bindgen]
+pub fn diff_grids(old_js: JsValue, new_js: JsValue) -> JsValue {
+    let parse = |grid_js| serde_wasm_bindgen::from_value::<Vec<Vec<Option<CellData>>>>(grid_js);
+    let (Ok(old), Ok(new)) = (parse(old_js), parse(new_js)) else {
+        return JsValue::NULL;
+    };
+    serde_wasm_bindgen::to_value(&diff_grids_internal(&old, &new)).unwrap_or(JsValue::NULL)
+}
+
+/// Parse a string produced by `grid_to_compact_string` back into a grid;
+/// null when the string is malformed
+#[wasm_bindgen]
+pub fn compact_string_to_grid(compact: &str) -> JsValue {
+    match decode_compact_grid(compact) {
+        Some(grid) => serde_wasm_bindgen::to_value(&grid).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+/// Edge positions of a port set ("12" -> [10, 20], "23" -> [20, 30],
+/// "34" -> [30, 40]); null for an unknown port set
+#[wasm_bindgen]
+pub fn port_positions(ports: &str) -> JsValue {
+    match PortSet::parse(ports) {
+        Some(set) => serde_wasm_bindgen::to_value(&set.positions()).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+/// Pack `[{ direction, ports }]` connections into the tile `mask` format
+///
+/// Lets two grids be diffed by XORing masks instead of comparing connection
+/// lists. Returns null for unknown direction or port set names.
+#[wasm_bindgen]
+pub fn connections_to_mask(connections_js: JsValue) -> JsValue {
+    let connections: Vec<Connection> = match serde_wasm_bindgen::from_value(connections_js) {
+        Ok(c) => c,
+        Err(_) => return JsValue::NULL,
+    };
+
+    match encode_connections(&connections) {
+        Some(mask) => JsValue::from(mask),
+        None => JsValue::NULL,
+    }
+}
+
+/// Unpack a tile mask into `[{ direction, ports }]`, ordered up, right,
+/// down, left
+#[wasm_bindgen]
+pub fn mask_to_connections(mask: u16) -> JsValue {
+    serde_wasm_bindgen::to_value(&decode_connections(mask)).unwrap_or(JsValue::NULL)
+}
+
+/// List every tile definition (id, variant, mask and its connections)
+#[wasm_bindgen]
+pub fn list_tiles() -> JsValue {
+    let tiles: Vec<TileInfo> = get_all_tiles().iter().map(TileDefinition::to_info).collect();
+    serde_wasm_bindgen::to_value(&tiles).unwrap_or(JsValue::NULL)
+}
+
+/// List the tiles with a connection on one side, for a palette filtered by
+/// direction
+///
+/// `dir` is a direction name ("up", "right", "up-right", ...). Returns
+/// tiles in the `list_tiles` shape, or null for an unknown direction.
+#[wasm_bindgen]
+pub fn tiles_with_direction(dir: &str) -> JsValue {
+    let Some(dir) = Direction::parse(dir) else {
+        return JsValue::NULL;
+    };
+    let tiles: Vec<TileInfo> = tiles_toward(dir).iter().map(TileDefinition::to_info).collect();
+    serde_wasm_bindgen::to_value(&tiles).unwrap_or(JsValue::NULL)
+}
+
+/// Replace the tile set used by every lookup and tiler
+///
+/// Takes an array in the `list_tiles` shape; `rotation` and `base_id` may be
+/// left out and are then derived from the mask. Each mask must match its
+/// connections. Returns undefined once the set is active, or a message
+/// naming the first invalid tile, in which case the active set is kept.
+/// The set it replaces is freed.
+#[wasm_bindgen]
+pub fn set_custom_tiles(tiles_js: JsValue) -> Option<String> {
+    let infos: Vec<TileInfo> = match serde_wasm_bindgen::from_value(tiles_js) {
+        Ok(infos) => infos,
+        Err(_) => return Some("expected an array of tile definitions".to_string()),
+    };
+    install_custom_tiles(&infos).err()
+}
+
+/// Restore the built-in tile set after `set_custom_tiles`
+#[wasm_bindgen]
+pub fn reset_tiles() {
+    reset_tile_set();
+}
+
+/// Example values of the main result types for TypeScript type generation
+///
+/// Returns `{ path_result, cell_data, road_grid_result }`, serialized exactly
+/// like real results, with every optional field present so a generator sees
+/// its type. Cells off the road are null, as in any grid.
+#[wasm_bindgen]
+pub fn result_type_examples() -> JsValue {
+    serde_wasm_bindgen::to_value(&TypeExamples::build()).unwrap_or(JsValue::NULL)
+}
+
+/// The tile of a variant ("curve", "straight", ...) with exactly this mask
+///
+/// Returns the same `{ id, variant, mask, connections, rotation, base_id }`
+/// shape as `list_tiles`, or null when no tile matches.
+#[wasm_bindgen]
+pub fn tile_by_mask(variant: &str, mask: u16) -> JsValue {
+    match tile_with_mask(variant, mask) {
+        Some(tile) => serde_wasm_bindgen::to_value(&tile.to_info()).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+/// Whether `tile_b_id` can be placed on the `direction` side of `tile_a_id`
+///
+/// True when tile A has a connection toward B and B has the matching
+/// connection back with the same ports, so a road runs across the shared
+/// edge. Unknown tile ids (including the start/goal markers) or directions
+/// give false.
+#[wasm_bindgen]
+pub fn tiles_connect(tile_a_id: &str, tile_b_id: &str, direction: &str) -> bool {
+    match (find_tile(tile_a_id), find_tile(tile_b_id), Direction::parse(direction)) {
+        (Some(a), Some(b), Some(dir)) => tiles_fit(&a, &b, dir),
+        _ => false,
+    }
+}
+
+/// The ports of a tile on all four sides at once, for sprite renderers
+///
+/// Returns `{ up, right, down, left }`, each a port set name or null where
+/// the tile has no connection; null for an unknown tile id (including the
+/// start/goal markers).
+#[wasm_bindgen]
+pub fn tile_connections(tile_id: &str) -> JsValue {
+    match find_tile(tile_id) {
+        Some(tile) => serde_wasm_bindgen::to_value(&tile.side_ports()).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+/// Parse a direction name ("up", "down-left", ...)
+///
+/// Returns `{ value, error }`: the direction on success, otherwise null and
+/// a message listing the accepted names, for validating loaded data.
+#[wasm_bindgen]
+pub fn parse_direction(name: &str) -> JsValue {
+    let parsed = ParsedName::from_result(name.parse::<Direction>());
+    serde_wasm_bindgen::to_value(&parsed).unwrap_or(JsValue::NULL)
+}
+
+/// Parse a port set name ("12", "23" or "34"), reporting like
+/// `parse_direction`
+#[wasm_bindgen]
+pub fn parse_port_set(name: &str) -> JsValue {
+    let parsed = ParsedName::from_result(name.parse::<PortSet>());
+    serde_wasm_bindgen::to_value(&parsed).unwrap_or(JsValue::NULL)
+}
+
+/// The direction facing the other way ("up" → "down", "up-right" →
+/// "down-left")
+///
+/// Returns undefined for an unknown direction name.
+#[wasm_bindgen]
+pub fn opposite_direction(direction: &str) -> Option<String> {
+    Direction::parse(direction).map(|dir| dir.opposite().to_string().to_string())
+}
+
+/// One step in `direction` as `[dr, dc]`, e.g. `[-1, 0]` for "up" and
+/// `[1, 1]` for "down-right"
+///
+/// Returns null for an unknown direction name.
+#[wasm_bindgen]
+pub fn direction_delta(direction: &str) -> JsValue {
+    match Direction::parse(direction) {
+        Some(dir) => serde_wasm_bindgen::to_value(&dir.delta()).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+/// The tiling of a single turn, as resolved by `lookup_tile`. None for
+/// unknown names or a turn no tile makes (e.g. doubling back).
+fn resolve_turn_internal(
+    entry: &str,
+    exit: &str,
+    required_entry_port: Option<&str>,
+    allow_lane_change: bool,
+) -> Option<TurnTiling> {
+    let entry = Direction::parse(entry)?;
+    let exit = Direction::parse(exit)?;
+    let required = match required_entry_port {
+        Some(ports) => Some(PortSet::parse(ports)?),
+        None => None,
+    };
+    let (tile_id, ep, xp) = lookup_tile(entry, exit, required, allow_lane_change)?;
+    Some(TurnTiling {
+        tile_id,
+        entry_port: ep.to_string().to_string(),
+        exit_port: xp.to_string().to_string(),
+    })
+}
+
+/// Tile and ports `path_to_road_grid` would use for one cell of a path
+///
+/// `entry` is the direction of travel into the cell and `exit` the direction
+/// out of it (e.g. "up" then "right" is a turn from the bottom edge to the
+/// right edge). `required_entry_port` is the lane the previous tile exits on;
+/// omitted means any. Returns `{ tile_id, entry_port, exit_port }`, or null
+/// for unknown names or a turn that no tile makes.
+#[wasm_bindgen]
+pub fn resolve_turn(
+    entry: &str,
+    exit: &str,
+    required_entry_port: Option<String>,
+    allow_lane_change: Option<bool>,
+) -> JsValue {
+    let required = required_entry_port.as_deref();
+    match resolve_turn_internal(entry, exit, required, allow_lane_change.unwrap_or(false)) {
+        Some(turn) => serde_wasm_bindgen::to_value(&turn).unwrap_or(JsValue::NULL),
+        None => JsValue::NULL,
+    }
+}
+
+/// Direction name ("up", "down", "left", "right") of the step between two
+/// cells, or undefined when they are not orthogonally adjacent
+#[wasm_bindgen]
+pub fn direction_between(r1: i32, c1: i32, r2: i32, c2: i32) -> Option<String> {
+    get_direction(Point::new(r1, c1), Point::new(r2, c2)).map(|dir| dir.to_string().to_string())
+}
+
+/// Get parity of a cell (0 or 1 based on row+col)
+#[wasm_bindgen]
+pub fn cell_parity(row: i32, col: i32) -> i32 {
+    (row + col) % 2
+}
+
+/// Check if two cells have different parity
+#[wasm_bindgen]
+pub fn has_different_parity(r1: i32, c1: i32, r2: i32, c2: i32) -> bool {
+    cell_parity(r1, c1) != cell_parity(r2, c2)
+}
+
+/// Area and parity class counts of a grid, counted the same way as the
+/// parity pre-check of the search
+fn grid_parity_info(grid_size: GridSize) -> GridInfo {
+    let (even_cells, odd_cells) = PathState::new(grid_size, 0).free_parity_counts();
+    let area = even_cells + odd_cells;
+    GridInfo {
+        area,
+        is_even_area: area.is_multiple_of(2),
+        parity_balanced: even_cells == odd_cells,
+        even_cells,
+        odd_cells,
+    }
+}
+
+/// Summarize a grid for reasoning about Hamiltonian feasibility
+///
+/// Returns `{ area, is_even_area, parity_balanced, even_cells, odd_cells }`,
+/// where the counts are of cells with `cell_parity` 0 and 1. Balanced grids
+/// need endpoints of different parity; unbalanced ones (odd area) need both
+/// endpoints on the majority color. Null for non-positive dimensions or more
+/// than `MAX_GRID_CELLS` cells.
+#[wasm_bindgen]
+pub fn grid_info(grid_rows: i32, grid_cols: i32) -> JsValue {
+    let grid_size = GridSize {
+        rows: grid_rows,
+        cols: grid_cols,
+    };
+    if grid_size.dimension_error().is_some() {
+        return JsValue::NULL;
+    }
+    serde_wasm_bindgen::to_value(&grid_parity_info(grid_size)).unwrap_or(JsValue::NULL)
+}
+
+// ============================================================================
+// Fuzzing Entry Points
+// ============================================================================
+
+/// Native entry points for fuzz targets (`fuzz-checks` feature): the
+/// internals of the exports, without the JsValue conversions that need a JS
+/// host. The feature also turns on the invariant checks inside the search
+/// and the tiler, so a broken invariant panics instead of returning a
+/// quietly wrong result.
+#[cfg(feature = "fuzz-checks")]
+pub mod fuzzing {
+    use super::*;
+
+    /// `find_road_path` with default options
+    pub fn find_road_path(
+        start: Point,
+        end: Point,
+        grid_size: GridSize,
+        max_iterations: u32,
+    ) -> PathResult {
+        solve_road_path(start, end, grid_size, max_iterations, SearchOptions::default())
+    }
+
+    /// `path_to_road_grid` with default options
+    pub fn path_to_road_grid(path: &[Point], grid_size: GridSize) -> RoadGridResult {
+        path_to_tiles(path, grid_size, TileOptions::default())
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_path_small_grid() {
+        // For a 2x2 grid, start and end must have different parity for Hamiltonian path
+        // (0,0) has parity 0, (0,1) has parity 1
+        let start = Point::new(0, 0);
+        let end = Point::new(0, 1);
+        let grid_size = GridSize { rows: 2, cols: 2 };
+
+        let mut state = PathState::new(grid_size, 1000);
+        let found = find_path_internal(&mut state, start, end);
+
+        assert!(found);
+        assert_eq!(state.path.len(), 4);
+    }
+
+    #[test]
+    fn test_find_path_with_blocked_cells() {
+        // 3x3 grid with the center blocked: the 8 outer cells form a ring
+        let grid_size = GridSize { rows: 3, cols: 3 };
+        let options = SearchOptions {
+            blocked: vec![Point::new(1, 1)],
+            ..Default::default()
+        };
+        let start = Point::new(0, 0);
+        let end = Point::new(1, 0);
+
+        let mut state = PathState::with_options(grid_size, 1000, options);
+        assert!(state.parity_allows_path(start, end));
+        assert!(find_path_internal(&mut state, start, end));
+        assert_eq!(state.path.len(), 8);
+        assert!(!state.path.contains(&Point::new(1, 1)));
+    }
+
+    #[test]
+    fn test_blocked_cells_parity_check() {
+        // Blocking one corner of a 2x2 grid leaves 3 cells: two odd, one even.
+        // A path must start and end on the odd (majority) color.
+        let grid_size = GridSize { rows: 2, cols: 2 };
+        let options = SearchOptions {
+            blocked: vec![Point::new(1, 1)],
+            ..Default::default()
+        };
+        let state = PathState::with_options(grid_size, 1000, options);
+        assert!(state.parity_allows_path(Point::new(0, 1), Point::new(1, 0)));
+        assert!(!state.parity_allows_path(Point::new(0, 0), Point::new(0, 1)));
+        // Endpoints on an obstacle are never valid
+        assert!(!state.parity_allows_path(Point::new(1, 1), Point::new(0, 1)));
+    }
+
+    #[test]
+    fn test_cancel_hook_aborts_search() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        // Parity-feasible pair the search cannot settle quickly: cancel on the first poll
+        let polls = Rc::new(Cell::new(0));
+        let counter = polls.clone();
+        let hook: CancelFn = Box::new(move || {
+            counter.set(counter.get() + 1);
+            true
+        });
+        let grid_size = GridSize { rows: 6, cols: 6 };
+        let result = solve_road_path(
+            Point::new(0, 0),
+            Point::new(0, 5),
+            grid_size,
+            1_000_000,
+            SearchOptions {
+                should_cancel: Some(hook),
+                ..Default::default()
+            },
+        );
+
+        assert!(!result.found);
+        assert!(result.cancelled);
+        assert_eq!(polls.get(), 1);
+        assert_eq!(result.iterations, CANCEL_POLL_INTERVAL);
+    }
+
+    #[test]
+    fn test_clock_budget_times_out() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        // Fake clock advancing 10 ms per reading: the deadline (0 + 25 ms)
+        // passes on the third poll
+        let now = Rc::new(Cell::new(0.0));
+        let ticks = now.clone();
+        let clock: ClockFn = Box::new(move || {
+            let t = ticks.get();
+            ticks.set(t + 10.0);
+            t
+        });
+        let grid_size = GridSize { rows: 6, cols: 6 };
+        let result = solve_road_path(
+            Point::new(0, 0),
+            Point::new(0, 5),
+            grid_size,
+            1_000_000,
+            SearchOptions {
+                clock: Some(clock),
+                time_budget_ms: 25.0,
+                ..Default::default()
+            },
+        );
+
+        assert!(!result.found);
+        assert!(result.timed_out);
+        assert!(!result.cancelled);
+        assert_eq!(result.iterations, 3 * CANCEL_POLL_INTERVAL);
+
+        // A generous budget doesn't interfere
+        let clock: ClockFn = Box::new(|| 0.0);
+        let result = solve_road_path(
+            Point::new(0, 0),
+            Point::new(3, 0),
+            GridSize { rows: 4, cols: 4 },
+            100_000,
+            SearchOptions {
+                clock: Some(clock),
+                time_budget_ms: 2000.0,
+                ..Default::default()
+            },
+        );
+        assert!(result.found);
+        assert!(!result.timed_out);
+    }
+
+    #[test]
+    fn test_cancel_hook_not_triggered() {
+        let hook: CancelFn = Box::new(|| false);
+        let grid_size = GridSize { rows: 2, cols: 2 };
+        let result = solve_road_path(
+            Point::new(0, 0),
+            Point::new(0, 1),
+            grid_size,
+            1000,
+            SearchOptions {
+                should_cancel: Some(hook),
+                ..Default::default()
+            },
+        );
+
+        assert!(result.found);
+        assert!(!result.cancelled);
+    }
+
+    #[test]
+    fn test_find_multiple_paths() {
+        // 2x3 grid from (0,0) to (1,0): the only cover runs around the rim
+        let grid_size = GridSize { rows: 2, cols: 3 };
+        let result = solve_road_paths(Point::new(0, 0), Point::new(1, 0), grid_size, 10_000, 10, SearchOptions::default());
+
+        assert!(result.found);
+        assert!(result.unique);
+        assert_eq!(result.paths.len(), 1);
+        assert_eq!(result.paths[0].len(), 6);
+
+        // An exhausted iteration budget never claims uniqueness
+        let capped = solve_road_paths(Point::new(0, 0), Point::new(1, 0), grid_size, 3, 10, SearchOptions::default());
+        assert!(!capped.found);
+        assert!(!capped.unique);
+    }
+
+    #[test]
+    fn test_find_multiple_paths_distinct() {
+        let grid_size = GridSize { rows: 3, cols: 3 };
+        let result = solve_road_paths(Point::new(0, 0), Point::new(2, 2), grid_size, 100_000, 100, SearchOptions::default());
+
+        assert!(result.paths.len() > 1);
+        assert!(!result.unique);
+        for (i, a) in result.paths.iter().enumerate() {
+            assert_eq!(a.len(), 9);
+            for b in &result.paths[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+
+        // Limiting the count truncates the result but still detects multiplicity
+        let limited = solve_road_paths(Point::new(0, 0), Point::new(2, 2), grid_size, 100_000, 1, SearchOptions::default());
+        assert_eq!(limited.paths.len(), 1);
+        assert!(!limited.unique);
+    }
+
+    #[test]
+    fn test_hamiltonian_path_possibility() {
+        // Even-celled grid: endpoints need opposite parity
+        assert!(has_hamiltonian_path_possibility(0, 0, 1, 2, 2, 4));
+        assert!(!has_hamiltonian_path_possibility(0, 0, 1, 1, 2, 2));
+        // Odd-celled grid: both endpoints on the majority (even) color
+        assert!(has_hamiltonian_path_possibility(0, 0, 2, 2, 3, 3));
+        assert!(!has_hamiltonian_path_possibility(0, 1, 1, 0, 3, 3));
+    }
+
+    #[test]
+    fn test_infeasible_pair_skips_search() {
+        let grid_size = GridSize { rows: 4, cols: 4 };
+        let result = solve_road_path(Point::new(0, 0), Point::new(3, 3), grid_size, 10_000, SearchOptions::default());
+
+        assert!(!result.found);
+        assert_eq!(result.iterations, 0);
+        assert!(result.reason.unwrap().contains("different colors"));
+
+        let ok = solve_road_path(Point::new(0, 0), Point::new(0, 3), grid_size, 10_000, SearchOptions::default());
+        assert!(ok.found);
+        assert!(ok.reason.is_none());
+    }
+
+    #[test]
+    fn test_find_path_with_waypoints() {
+        let grid_size = GridSize { rows: 3, cols: 3 };
+        let start = Point::new(0, 0);
+        let end = Point::new(2, 2);
+        let waypoints = vec![Point::new(1, 1), Point::new(0, 2)];
+
+        let options = SearchOptions {
+            waypoints: waypoints.clone(),
+            ..Default::default()
+        };
+        let result = solve_road_path(start, end, grid_size, 10_000, options);
+        assert!(result.found);
+        assert_eq!(result.path.len(), 9);
+
+        let pos = |p: Point| result.path.iter().position(|&q| q == p).unwrap();
+        assert!(pos(waypoints[0]) < pos(waypoints[1]));
+    }
+
+    #[test]
+    fn test_waypoints_out_of_order_fail() {
+        // On a 1x4 strip the only path runs left to right, so (0,2) before (0,1) is impossible
+        let grid_size = GridSize { rows: 1, cols: 4 };
+        let options = SearchOptions {
+            waypoints: vec![Point::new(0, 2), Point::new(0, 1)],
+            ..Default::default()
+        };
+        let result = solve_road_path(Point::new(0, 0), Point::new(0, 3), grid_size, 10_000, options);
+        assert!(!result.found);
+    }
+
+    #[test]
+    fn test_seeded_search_is_reproducible() {
+        let grid_size = GridSize { rows: 4, cols: 4 };
+        let run = |seed| {
+            let options = SearchOptions {
+                seed,
+                ..Default::default()
+            };
+            solve_road_path(Point::new(0, 0), Point::new(3, 0), grid_size, 100_000, options)
+        };
+
+        let first = run(12345);
+        assert!(first.found);
+        assert_eq!(first.path, run(12345).path);
+
+        // Different seeds yield some variety, all still full covers
+        let paths: Vec<Vec<Point>> = (1..=8).map(|seed| run(seed).path).collect();
+        assert!(paths.iter().all(|p| p.len() == 16));
+        assert!(paths.iter().any(|p| *p != paths[0]));
+
+        // Seed 0 keeps the unseeded behaviour
+        let unseeded = solve_road_path(Point::new(0, 0), Point::new(3, 0), grid_size, 100_000, SearchOptions::default());
+        assert_eq!(run(0).path, unseeded.path);
+    }
+
+    #[test]
+    fn test_count_hamiltonian_paths() {
+        let grid_size = GridSize { rows: 2, cols: 3 };
+        let rim = count_paths(Point::new(0, 0), Point::new(1, 0), grid_size, 10_000);
+        assert_eq!(rim.count, 1);
+        assert!(!rim.capped);
+
+        // Counting agrees with full enumeration
+        let grid_size = GridSize { rows: 4, cols: 4 };
+        let counted = count_paths(Point::new(0, 0), Point::new(3, 0), grid_size, 1_000_000);
+        let listed = solve_road_paths(Point::new(0, 0), Point::new(3, 0), grid_size, 1_000_000, 1000, SearchOptions::default());
+        assert!(!counted.capped);
+        assert_eq!(counted.count as usize, listed.paths.len());
+        assert!(counted.count > 1);
+    }
+
+    #[test]
+    fn test_count_hamiltonian_paths_capped() {
+        let grid_size = GridSize { rows: 5, cols: 5 };
+        let result = count_paths(Point::new(0, 0), Point::new(4, 4), grid_size, 100);
+        assert!(result.capped);
+
+        // Parity-infeasible pairs count zero without searching
+        let none = count_paths(Point::new(0, 0), Point::new(0, 1), grid_size, 100);
+        assert_eq!(none.count, 0);
+        assert_eq!(none.iterations, 0);
+        assert!(!none.capped);
+    }
+
+    #[test]
+    fn test_find_path_masked_l_shape() {
+        // L-shape: left column plus bottom row of a 3x3 grid
+        #[rustfmt::skip]
+        let mask = vec![
+            true, false, false,
+            true, false, false,
+            true, true,  true,
+        ];
+        let grid_size = GridSize { rows: 3, cols: 3 };
+        let options = SearchOptions {
+            cell_mask: Some(mask),
+            ..Default::default()
+        };
+        let result = solve_road_path(Point::new(0, 0), Point::new(2, 2), grid_size, 1000, options);
+
+        assert!(result.found);
+        assert_eq!(
+            result.path,
+            vec![Point::new(0, 0), Point::new(1, 0), Point::new(2, 0), Point::new(2, 1), Point::new(2, 2)]
+        );
+    }
+
+    #[test]
+    fn test_cell_count() {
+        assert_eq!(GridSize { rows: 3, cols: 4 }.cell_count(), Some(12));
+        assert_eq!(GridSize { rows: -3, cols: -4 }.cell_count(), None);
+        assert_eq!(GridSize { rows: 0, cols: 4 }.cell_count(), None);
+        assert_eq!(GridSize { rows: 1024, cols: 1024 }.cell_count(), Some(1 << 20));
+        assert_eq!(GridSize { rows: 1025, cols: 1024 }.cell_count(), None);
+        assert_eq!(GridSize { rows: i32::MAX, cols: i32::MAX }.cell_count(), None);
+    }
+
+    #[test]
+    fn test_masked_cells_excluded_from_parity() {
+        // Donut: 3x3 without its center leaves 4 even and 4 odd cells
+        let mut mask = vec![true; 9];
+        mask[4] = false;
+        let grid_size = GridSize { rows: 3, cols: 3 };
+        let options = SearchOptions {
+            cell_mask: Some(mask),
+            ..Default::default()
+        };
+        let state = PathState::with_options(grid_size, 1000, options);
+
+        assert_eq!(state.free_cell_count(), 8);
+        assert_eq!(state.free_parity_counts(), (4, 4));
+        assert!(!state.is_valid(Point::new(1, 1)));
+        assert!(state.get_neighbors(Point::new(0, 1)).iter().all(|(p, _)| *p != Point::new(1, 1)));
+    }
+
+    #[test]
+    fn test_progress_hook_reports_and_aborts() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let reports = Rc::new(RefCell::new(Vec::new()));
+        let log = reports.clone();
+        let hook: ProgressFn = Box::new(move |iterations, depth| {
+            log.borrow_mut().push((iterations, depth));
+            iterations < 300
+        });
+        let grid_size = GridSize { rows: 6, cols: 6 };
+        let options = SearchOptions {
+            on_progress: Some(hook),
+            progress_interval: 100,
+            ..Default::default()
+        };
+        let result = solve_road_path(Point::new(0, 0), Point::new(0, 5), grid_size, 1_000_000, options);
+
+        let reports = reports.borrow();
+        assert_eq!(reports.iter().map(|r| r.0).collect::<Vec<_>>(), vec![100, 200, 300]);
+        assert!(reports.iter().all(|&(_, depth)| depth > 0 && depth <= 36));
+        assert!(result.cancelled);
+        assert!(!result.found);
+        assert_eq!(result.iterations, 300);
+    }
+
+    #[test]
+    fn test_parity() {
+        assert_eq!(cell_parity(0, 0), 0);
+        assert_eq!(cell_parity(0, 1), 1);
+        assert_eq!(cell_parity(1, 0), 1);
+        assert_eq!(cell_parity(1, 1), 0);
+    }
+
+    #[test]
+    fn test_different_parity() {
+        assert!(has_different_parity(0, 0, 0, 1));
+        assert!(!has_different_parity(0, 0, 1, 1));
+    }
+
+    #[test]
+    fn test_tile_definitions() {
+        let tiles = get_all_tiles();
+        // 16 curve + 16 sharp + 8 straight, plus 20 + 20 + 10 using the third
+        // lane, 32 edge-to-corner + 6 corner-to-corner diagonals and 8 tees +
+        // 2 crossings
+        assert_eq!(tiles.len(), 138);
+    }
+
+    #[test]
+    fn test_tile_masks_match_connections() {
+        for tile in get_all_tiles() {
+            let directions: std::collections::HashSet<_> = tile.connections.iter().map(|&(dir, _)| dir).collect();
+            assert_eq!(directions.len(), tile.connections.len(), "{}", tile.id);
+            assert_eq!(derive_mask(&tile.connections), tile.mask, "{}", tile.id);
+            assert_eq!(tile.mask.count_ones() as usize, tile.connections.len(), "{}", tile.id);
+        }
+    }
+
+    #[test]
+    fn test_tile_by_mask() {
+        assert_eq!(tile_with_mask("curve", 0x05).unwrap().id, "curve-05");
+        assert_eq!(tile_with_mask("sharp", 0x05).unwrap().id, "sharp-05");
+        assert!(tile_with_mask("straight", 0x05).is_none());
+        assert!(tile_with_mask("bridge", 0x05).is_none());
+        for tile in get_all_tiles() {
+            assert_eq!(tile_with_mask(tile.variant.to_string(), tile.mask).unwrap().id, tile.id);
+        }
+    }
+
+    #[test]
+    fn test_curve_rotations_distinct() {
+        let tiles = get_all_tiles();
+        let rotation = |id: &str| tiles.iter().find(|t| t.id == id).unwrap().rotation;
+
+        // Up+Right, Right+Down, Down+Left, Left+Up corners
+        let angles = [rotation("curve-05"), rotation("curve-14"), rotation("curve-50"), rotation("curve-41")];
+        assert_eq!(angles, [0, 90, 180, 270]);
+    }
+
+    #[test]
+    fn test_tile_base_ids() {
+        let tiles = get_all_tiles();
+        let get = |id: &str| tiles.iter().find(|t| t.id == id).unwrap();
+
+        // Rotating Up(12)+Right(23) clockwise lands on Right(12)+Down(12)
+        assert_eq!(get("curve-14").base_id, "curve-12-23");
+        assert_eq!(get("sharp-50").base_id, "sharp-23-23");
+        assert_eq!(get("straight-v-12").base_id, "straight-v-12");
+        assert_eq!(get("straight-h-48").base_id, "straight-v-21");
+        assert_eq!(get("straight-h-48").rotation, 90);
+        assert!(tiles[..40].iter().all(|t| !t.base_id.is_empty()));
+    }
+
+    #[test]
+    fn test_tile_info() {
+        let tiles = get_all_tiles();
+        let info = tiles.iter().find(|t| t.id == "curve-06").unwrap().to_info();
+
+        assert_eq!(info.variant, "curve");
+        assert_eq!(info.mask, 0x06);
+        assert_eq!(info.connections.len(), 2);
+        assert_eq!(info.connections[0].direction, "up");
+        assert_eq!(info.connections[0].ports, "23");
+        assert_eq!(info.connections[1].direction, "right");
+        assert_eq!(info.connections[1].ports, "12");
+        assert_eq!(tiles.iter().find(|t| t.id == "straight-h-44").unwrap().to_info().variant, "straight");
+    }
+
+    #[test]
+    fn test_path_to_tiles() {
+        // Test a simple 3-cell path
+        let path = vec![
+            Point::new(0, 0),
+            Point::new(0, 1),
+            Point::new(0, 2),
+        ];
+        let grid_size = GridSize { rows: 1, cols: 3 };
+        let result = path_to_tiles(&path, grid_size, TileOptions::default());
+
+        assert!(result.valid);
+        // First cell should be start
+        assert_eq!(result.grid[0][0].as_ref().unwrap().tile_id, "start");
+        // Last cell should be goal
+        assert_eq!(result.grid[0][2].as_ref().unwrap().tile_id, "goal");
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        let grid_size = GridSize { rows: 4, cols: 4 };
+        let result = solve_road_cycle(Point::new(0, 0), grid_size, 100_000);
+
+        assert!(result.found);
+        assert_eq!(result.path.len(), 16);
+        assert_eq!(result.path[0], Point::new(0, 0));
+        assert!(is_adjacent(result.path[15], result.path[0]));
+
+        let tiled = cycle_to_tiles(&result.path, grid_size);
+        assert!(tiled.valid);
+        assert!(validate_grid(&tiled.grid, grid_size).is_empty());
+        for cell in tiled.grid.iter().flatten() {
+            let cell = cell.as_ref().unwrap();
+            assert_eq!(cell.connections.len(), 2);
+            assert!(cell.tile_id != "start" && cell.tile_id != "goal");
+        }
+    }
+
+    #[test]
+    fn test_near_loop_path_reports_its_end() {
+        let grid_size = GridSize { rows: 3, cols: 4 };
+        for start in [Point::new(0, 0), Point::new(1, 1), Point::new(2, 3)] {
+            let near = solve_near_loop(start, grid_size, 0);
+            let end = near.end.unwrap();
+            assert!(near.result.found);
+            assert!(is_adjacent(start, end));
+            assert_eq!(near.result.path.first(), Some(&start));
+            assert_eq!(near.result.path.last(), Some(&end));
+            assert_eq!(near.result.path.len(), 12);
+        }
+
+        let odd = solve_near_loop(Point::new(0, 0), GridSize { rows: 3, cols: 3 }, 0);
+        assert!(!odd.result.found && odd.end.is_none());
+        assert!(odd.result.reason.is_some());
+    }
+
+    #[test]
+    fn test_no_cycle_on_odd_grid() {
+        let grid_size = GridSize { rows: 3, cols: 3 };
+        let result = solve_road_cycle(Point::new(0, 0), grid_size, 100_000);
+        assert!(!result.found);
+        assert_eq!(result.iterations, 0);
+        assert!(result.reason.is_some());
+
+        // An open path is rejected by the cycle tiler
+        let path = vec![Point::new(0, 0), Point::new(0, 1), Point::new(0, 2), Point::new(1, 2)];
+        assert!(!cycle_to_tiles(&path, grid_size).valid);
+    }
+
+    #[test]
+    fn test_direction_between() {
+        assert_eq!(direction_between(1, 1, 0, 1).as_deref(), Some("up"));
+        assert_eq!(direction_between(1, 1, 1, 2).as_deref(), Some("right"));
+        assert_eq!(direction_between(1, 1, 1, 1), None);
+        assert_eq!(direction_between(1, 1, 2, 2), None);
+        assert_eq!(direction_between(0, 0, 0, 2), None);
+    }
+
+    #[test]
+    fn test_lane_change_straight() {
+        let path = vec![Point::new(0, 0), Point::new(0, 1), Point::new(0, 2)];
+        let grid_size = GridSize { rows: 1, cols: 3 };
+
+        // Default keeps the outer lane all the way through
+        let same_lane = path_to_tiles(&path, grid_size, TileOptions::default());
+        assert_eq!(same_lane.grid[0][1].as_ref().unwrap().tile_id, "straight-h-88");
+
+        let options = TileOptions { allow_lane_change: true, ..Default::default() };
+        let result = path_to_tiles(&path, grid_size, options);
+        assert!(result.valid);
+        let middle = result.grid[0][1].as_ref().unwrap();
+        assert_eq!(middle.tile_id, "straight-h-84");
+        assert_eq!(middle.connections[0].ports, "23");
+        assert_eq!(middle.connections[1].ports, "12");
+
+        // The changed lane is threaded into the goal's entry
+        let goal = result.grid[0][2].as_ref().unwrap();
+        assert_eq!(goal.connections[0].ports, "12");
+        assert!(validate_grid(&result.grid, grid_size).is_empty());
+    }
+
+    #[test]
+    fn test_variant_preference() {
+        // Snake through a 3x3 grid: corners at path indices 2, 3, 5 and 6
+        let path = vec![
+            Point::new(0, 0), Point::new(0, 1), Point::new(0, 2),
+            Point::new(1, 2), Point::new(1, 1), Point::new(1, 0),
+            Point::new(2, 0), Point::new(2, 1), Point::new(2, 2),
+        ];
+        let grid_size = GridSize { rows: 3, cols: 3 };
+        let tile_at = |result: &RoadGridResult, i: usize| {
+            let p = path[i];
+            result.grid[p.row as usize][p.col as usize].as_ref().unwrap().tile_id.clone()
+        };
+
+        let curve = path_to_tiles(&path, grid_size, TileOptions::default());
+        let sharp = path_to_tiles(
+            &path,
+            grid_size,
+            TileOptions { variant_preference: VariantPreference::PreferSharp, ..Default::default() },
+        );
+        let alternate = path_to_tiles(
+            &path,
+            grid_size,
+            TileOptions { variant_preference: VariantPreference::Alternate, ..Default::default() },
+        );
+
+        for i in [2, 3, 5, 6] {
+            assert!(tile_at(&curve, i).starts_with("curve-"));
+            assert!(tile_at(&sharp, i).starts_with("sharp-"));
+            // Same connection signature either way
+            assert_eq!(tile_at(&curve, i)[6..], tile_at(&sharp, i)[6..]);
+            let expected = if i % 2 == 1 { "sharp-" } else { "curve-" };
+            assert!(tile_at(&alternate, i).starts_with(expected));
+        }
+        // Straights and markers are untouched
+        for i in [0, 1, 4, 7, 8] {
+            assert_eq!(tile_at(&sharp, i), tile_at(&curve, i));
+        }
+        assert!(validate_grid(&sharp.grid, grid_size).is_empty());
+        assert!(validate_grid(&alternate.grid, grid_size).is_empty());
+    }
+
+    #[test]
+    fn test_batch_matches_individual_searches() {
+        let grid_size = GridSize { rows: 4, cols: 4 };
+        let start = Point::new(0, 0);
+        let pairs: Vec<EndpointPair> = (0..4)
+            .flat_map(|row| (0..4).map(move |col| EndpointPair { start, end: Point::new(row, col) }))
+            .collect();
+
+        let batch = solve_road_paths_batch(&pairs, grid_size, 100_000, false);
+        assert_eq!(batch.len(), pairs.len());
+        for (pair, result) in pairs.iter().zip(&batch) {
+            let single = solve_road_path(pair.start, pair.end, grid_size, 100_000, SearchOptions::default());
+            assert_eq!(result.found, single.found);
+            assert_eq!(result.path, single.path);
+            assert_eq!(result.iterations, single.iterations);
+            assert_eq!(result.reason, single.reason);
+        }
+        // Same-parity endpoints are rejected, opposite-parity ones solved
+        assert!(batch.iter().any(|r| r.found));
+        assert!(batch.iter().any(|r| r.reason.is_some()));
+    }
+
+    #[test]
+    fn test_pruning_helps_corridors() {
+        let cases = [
+            (GridSize { rows: 2, cols: 12 }, Point::new(0, 5), Point::new(0, 6), true),
+            (GridSize { rows: 3, cols: 10 }, Point::new(0, 0), Point::new(2, 9), true),
+            (GridSize { rows: 2, cols: 10 }, Point::new(0, 3), Point::new(1, 3), false),
+        ];
+        for (grid_size, start, end, solvable) in cases {
+            let plain = solve_road_path(start, end, grid_size, 1_000_000, SearchOptions::default());
+            let pruned = solve_road_path(
+                start,
+                end,
+                grid_size,
+                1_000_000,
+                SearchOptions { pruning: true, ..Default::default() },
+            );
+            assert_eq!(plain.found, solvable);
+            assert_eq!(pruned.found, solvable);
+            assert!(pruned.iterations < plain.iterations);
+            if solvable {
+                assert_eq!(pruned.path.len(), (grid_size.rows * grid_size.cols) as usize);
+                assert!(pruned.path.windows(2).all(|w| is_adjacent(w[0], w[1])));
+            }
+        }
+        assert_eq!(SearchStrategy::parse("snake"), Some(SearchStrategy::Snake));
+        assert_eq!(SearchStrategy::parse("connectivity"), None);
+        assert_eq!(SearchStrategy::parse("astar"), None);
+    }
+
+    #[test]
+    fn test_pruning_keeps_found_result() {
+        for (rows, cols) in [(3, 3), (4, 4), (3, 5)] {
+            let grid_size = GridSize { rows, cols };
+            for row in 0..rows {
+                for col in 0..cols {
+                    let (start, end) = (Point::new(0, 0), Point::new(row, col));
+                    if start == end {
+                        continue;
+                    }
+                    let plain = solve_road_path(start, end, grid_size, 1_000_000, SearchOptions::default());
+                    let pruned = solve_road_path(
+                        start,
+                        end,
+                        grid_size,
+                        1_000_000,
+                        SearchOptions { pruning: true, ..Default::default() },
+                    );
+                    assert_eq!(pruned.found, plain.found, "{}x{} to {:?}", rows, cols, end);
+                    assert!(pruned.iterations <= plain.iterations);
+                }
+            }
+        }
+
+        // The hard 6x6 instance drops by far more than an order of magnitude
+        let grid_size = GridSize { rows: 6, cols: 6 };
+        let (start, end) = (Point::new(0, 0), Point::new(0, 5));
+        let plain = solve_road_path(start, end, grid_size, 2_000_000, SearchOptions::default());
+        let pruned =
+            solve_road_path(start, end, grid_size, 2_000_000, SearchOptions { pruning: true, ..Default::default() });
+        assert!(plain.found && pruned.found);
+        assert!(pruned.iterations * 10 < plain.iterations);
+
+        // Enumeration finds the same set of paths
+        let grid_size = GridSize { rows: 4, cols: 4 };
+        let all = solve_road_paths(start, Point::new(3, 0), grid_size, 1_000_000, 1000, SearchOptions::default());
+        let all_pruned = solve_road_paths(
+            start,
+            Point::new(3, 0),
+            grid_size,
+            1_000_000,
+            1000,
+            SearchOptions { pruning: true, ..Default::default() },
+        );
+        assert_eq!(all.paths, all_pruned.paths);
+        assert!(all_pruned.iterations < all.iterations);
+    }
+
+    #[test]
+    fn test_compact_grid_round_trip() {
+        let tiled = path_to_tiles(
+            &[Point::new(0, 0), Point::new(0, 1), Point::new(1, 1)],
+            GridSize { rows: 2, cols: 3 },
+            TileOptions::default(),
+        );
+        let compact = encode_compact_grid(&tiled.grid).unwrap();
+        assert_eq!(compact, "start:0:r23|curve-A0:1:l23,d23|*1/*1|goal:2:u23|*1");
+        assert_eq!(decode_compact_grid(&compact).unwrap(), tiled.grid);
+
+        // Random grids of random tiles, markers and empty cells
+        let tiles = get_all_tiles();
+        let mut rng = XorShift32::new(0x5EED);
+        for _ in 0..200 {
+            let rows = 1 + rng.next_u32() % 6;
+            let cols = 1 + rng.next_u32() % 6;
+            let grid: Vec<Vec<Option<CellData>>> = (0..rows)
+                .map(|_| {
+                    (0..cols)
+                        .map(|_| {
+                            let pick = rng.next_u32() as usize;
+                            if pick.is_multiple_of(3) {
+                                return None;
+                            }
+                            let path_index = rng.next_u32() as usize % 100;
+                            let (tile_id, connections) = if pick % 7 == 1 {
+                                let marker = if pick.is_multiple_of(2) { "start" } else { "goal" };
+                                (marker, decode_connections(1 << (pick % 8)))
+                            } else {
+                                let tile = &tiles[pick % tiles.len()];
+                                (tile.id.as_str(), decode_connections(tile.mask))
+                            };
+                            Some(CellData::new(tile_id.to_string(), connections, path_index))
+                        })
+                        .collect()
+                })
+                .collect();
+
+            let compact = encode_compact_grid(&grid).unwrap();
+            assert_eq!(decode_compact_grid(&compact).unwrap(), grid, "{}", compact);
+        }
+    }
+
+    #[test]
+    fn test_compact_grid_rejects_bad_input() {
+        assert_eq!(decode_compact_grid("").unwrap(), Vec::<Vec<Option<CellData>>>::new());
+        assert!(decode_compact_grid("start:0:x23").is_none());
+        assert!(decode_compact_grid("start:zero:r23").is_none());
+        assert!(decode_compact_grid("*0").is_none());
+        assert!(decode_compact_grid(":0:r23").is_none());
+
+        let cell = CellData::new("a|b".to_string(), Vec::new(), 0);
+        assert!(encode_compact_grid(&[vec![Some(cell)]]).is_none());
+    }
+
+    #[test]
+    fn test_third_lane_tiles() {
+        let tiles = get_all_tiles();
+        let p34: Vec<_> = tiles
+            .iter()
+            .filter(|t| t.connections.iter().any(|&(_, ports)| ports == PortSet::P34))
+            .collect();
+        assert_eq!(p34.len(), 50);
+        for tile in &p34 {
+            assert_eq!(tile.base_id, "");
+        }
+
+        // A snake tiled entirely on the third lane
+        let path = vec![
+            Point::new(0, 0), Point::new(0, 1), Point::new(0, 2),
+            Point::new(1, 2), Point::new(1, 1), Point::new(1, 0),
+        ];
+        let grid_size = GridSize { rows: 2, cols: 3 };
+        let options = TileOptions { start_port: Some(PortSet::P34), ..Default::default() };
+        let result = path_to_tiles(&path, grid_size, options);
+        assert!(result.valid);
+        for cell in result.grid.iter().flatten().flatten() {
+            assert!(cell.connections.iter().all(|c| c.ports == "34"), "{:?}", cell);
+        }
+        assert_eq!(result.grid[0][1].as_ref().unwrap().tile_id, "straight-h-A00");
+        assert_eq!(result.grid[0][2].as_ref().unwrap().tile_id, "curve-C00");
+        assert!(validate_grid(&result.grid, grid_size).is_empty());
+    }
+
+    #[test]
+    fn test_append_matches_full_retile() {
+        let grid_size = GridSize { rows: 4, cols: 4 };
+        let mut state = PathState::new(grid_size, 100_000);
+        assert!(find_path_internal(&mut state, Point::new(0, 0), Point::new(3, 0)));
+        let path = state.path.clone();
+
+        let mut grid = path_to_tiles(&path[..2], grid_size, TileOptions::default()).grid;
+        for n in 2..path.len() {
+            let result = append_to_tiles(grid, &path[..n], path[n], grid_size).unwrap();
+            assert!(result.valid);
+            let full = path_to_tiles(&path[..=n], grid_size, TileOptions::default());
+            assert_eq!(result.grid, full.grid);
+            let goal = full.grid[path[n].row as usize][path[n].col as usize].as_ref().unwrap();
+            assert_eq!(result.required_entry_port.as_deref(), Some(goal.connections[0].ports.as_str()));
+            grid = result.grid;
+        }
+    }
+
+    #[test]
+    fn test_append_rejects_bad_cells() {
+        let grid_size = GridSize { rows: 3, cols: 3 };
+        let path = [Point::new(0, 0), Point::new(0, 1)];
+        let grid = path_to_tiles(&path, grid_size, TileOptions::default()).grid;
+
+        // Not adjacent, already on the path, out of bounds
+        assert!(append_to_tiles(grid.clone(), &path, Point::new(2, 2), grid_size).is_none());
+        assert!(append_to_tiles(grid.clone(), &path, Point::new(0, 0), grid_size).is_none());
+        assert!(append_to_tiles(grid.clone(), &path, Point::new(-1, 1), grid_size).is_none());
+        assert!(append_to_tiles(grid.clone(), &[], Point::new(0, 0), grid_size).is_none());
+
+        let single = append_to_tiles(vec![vec![None; 3]; 3], &path[..1], path[1], grid_size).unwrap();
+        assert!(single.valid);
+        assert_eq!(single.grid, grid);
+    }
+
+    #[test]
+    fn test_difficulty_estimate_calibration() {
+        // Mean estimate error over every feasible pair from a corner and from
+        // an edge midpoint stays within one order of magnitude
+        for (rows, cols) in [(3, 3), (4, 4), (5, 5), (2, 10), (3, 8), (4, 6)] {
+            let grid_size = GridSize { rows, cols };
+            let mut errors = Vec::new();
+            for start in [Point::new(0, 0), Point::new(0, cols / 2)] {
+                for row in 0..rows {
+                    for col in 0..cols {
+                        let end = Point::new(row, col);
+                        let estimate = estimate_difficulty(start, end, grid_size);
+                        let result = solve_road_path(start, end, grid_size, 1_000_000, SearchOptions::default());
+                        assert_eq!(estimate.feasible, result.reason.is_none());
+                        if estimate.feasible {
+                            assert!(result.iterations <= estimate.suggested_max_iterations, "{:?}", end);
+                            errors.push(estimate.score - (result.iterations as f64).log10());
+                        }
+                    }
+                }
+            }
+            let mean = errors.iter().sum::<f64>() / errors.len() as f64;
+            assert!(mean.abs() < 1.0, "{}x{}: mean error {}", rows, cols, mean);
+        }
+    }
+
+    #[test]
+    fn test_difficulty_estimate_ordering() {
+        let grid_size = GridSize { rows: 6, cols: 6 };
+        let corner = estimate_difficulty(Point::new(0, 0), Point::new(5, 0), grid_size);
+        let center = estimate_difficulty(Point::new(2, 2), Point::new(3, 2), grid_size);
+        assert!(corner.score < center.score);
+        assert!(corner.suggested_max_iterations <= center.suggested_max_iterations);
+
+        let small = estimate_difficulty(Point::new(0, 0), Point::new(3, 0), GridSize { rows: 4, cols: 4 });
+        assert!(small.score < corner.score);
+
+        let infeasible = estimate_difficulty(Point::new(0, 0), Point::new(0, 2), grid_size);
+        assert!(!infeasible.feasible);
+        assert_eq!(infeasible.suggested_max_iterations, 0);
+    }
+
+    #[test]
+    fn test_target_length_paths() {
+        let grid_size = GridSize { rows: 4, cols: 4 };
+        let (start, end) = (Point::new(0, 0), Point::new(0, 3));
+        let with_length = |length| SearchOptions {
+            target_length: Some(length),
+            ..Default::default()
+        };
+
+        // Shortest path, a detour, and a full cover expressed as a length
+        for length in [4, 6, 16] {
+            let result = solve_road_path(start, end, grid_size, 100_000, with_length(length));
+            assert!(result.found, "length {}", length);
+            assert_eq!(result.path.len(), length);
+            assert_eq!((result.path[0], *result.path.last().unwrap()), (start, end));
+            assert!(result.path.windows(2).all(|w| is_adjacent(w[0], w[1])));
+        }
+
+        // Too short, wrong parity, too long
+        for length in [3, 5, 17] {
+            let result = solve_road_path(start, end, grid_size, 100_000, with_length(length));
+            assert!(!result.found);
+            assert!(result.reason.is_some(), "length {}", length);
+            assert_eq!(result.iterations, 0);
+        }
+
+        // Waypoints still have to be passed
+        let options = SearchOptions {
+            target_length: Some(6),
+            waypoints: vec![Point::new(1, 1)],
+            ..Default::default()
+        };
+        let result = solve_road_path(start, end, grid_size, 100_000, options);
+        assert!(result.found);
+        assert!(result.path.contains(&Point::new(1, 1)));
+    }
+
+    #[test]
+    fn test_depth_counts() {
+        let grid_size = GridSize { rows: 4, cols: 4 };
+        let (start, end) = (Point::new(0, 0), Point::new(3, 0));
+        let plain = solve_road_path(start, end, grid_size, 100_000, SearchOptions::default());
+        assert!(plain.depth_counts.is_none());
+
+        let options = SearchOptions {
+            record_depths: true,
+            ..Default::default()
+        };
+        let result = solve_road_path(start, end, grid_size, 100_000, options);
+        let counts = result.depth_counts.unwrap();
+        assert_eq!(counts.len(), 16);
+        assert_eq!(counts[0], 1);
+        // Every iteration expands exactly one node, and the winning path
+        // reaches the last depth once
+        assert_eq!(counts.iter().sum::<u32>(), result.iterations);
+        assert_eq!(counts[15], 1);
+        assert_eq!(result.path, plain.path);
+    }
+
+    #[test]
+    fn test_visited_grid_of_failed_search() {
+        // Around a blocked center the free cells form a ring, so a path
+        // covering them has to end next to its start; parity allows (1, 2)
+        let grid_size = GridSize { rows: 3, cols: 3 };
+        let (start, end) = (Point::new(0, 0), Point::new(1, 2));
+        let blocked = vec![Point::new(1, 1)];
+        let plain = SearchOptions {
+            blocked: blocked.clone(),
+            ..Default::default()
+        };
+        let result = solve_road_path(start, end, grid_size, 100_000, plain);
+        assert!(result.visited_grid.is_none());
+
+        let options = SearchOptions {
+            blocked,
+            record_visited: true,
+            ..Default::default()
+        };
+        let result = solve_road_path(start, end, grid_size, 100_000, options);
+        assert!(!result.found && result.reason.is_none());
+        let grid = result.visited_grid.unwrap();
+        let best = result.best_partial.unwrap();
+        for row in 0..3 {
+            for col in 0..3 {
+                let p = Point::new(row, col);
+                let expected = p == Point::new(1, 1) || best.contains(&p);
+                assert_eq!(grid[row as usize][col as usize], expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_port_positions() {
+        assert_eq!(PortSet::P12.positions(), (10, 20));
+        assert_eq!(PortSet::P23.positions(), (20, 30));
+        assert_eq!(PortSet::P34.positions(), (30, 40));
+        for set in PortSet::all() {
+            assert_eq!(PortSet::parse(set.to_string()), Some(set));
+        }
+    }
+
+    #[test]
+    fn test_tiling_failure_is_reported() {
+        // Doubling back has no tile: the partial grid and the failing cell are kept
+        let path = vec![Point::new(0, 0), Point::new(0, 1), Point::new(0, 2), Point::new(0, 1)];
+        let result = path_to_tiles(&path, GridSize { rows: 1, cols: 3 }, TileOptions::default());
+        assert!(!result.valid);
+        assert_eq!(result.failure, Some(TileFailure {
+            path_index: 2,
+            entry_direction: "right".to_string(),
+            exit_direction: "left".to_string(),
+            required_entry_port: Some("23".to_string()),
+        }));
+        assert_eq!(result.grid[0][0].as_ref().unwrap().tile_id, "start");
+        assert_eq!(result.grid[0][1].as_ref().unwrap().tile_id, "straight-h-88");
+        assert!(result.grid[0][2].is_none());
+
+        let ok = path_to_tiles(&path[..3], GridSize { rows: 1, cols: 3 }, TileOptions::default());
+        assert!(ok.valid);
+        assert!(ok.failure.is_none());
+    }
+
+    #[test]
+    fn test_tile_alternatives() {
+        let grid_size = GridSize { rows: 2, cols: 3 };
+        let path = [Point::new(0, 0), Point::new(0, 1), Point::new(0, 2), Point::new(1, 2)];
+        let options = TileOptions {
+            alternatives: true,
+            ..Default::default()
+        };
+        let tiled = path_to_tiles(&path, grid_size, options);
+        let cell = |p: Point| tiled.grid[p.row as usize][p.col as usize].as_ref().unwrap();
+
+        // The corner can swap for its sharp twin, which has the same ports
+        let corner = cell(Point::new(0, 2));
+        assert_eq!(corner.tile_id, "curve-A0");
+        assert!(!corner.alternatives.is_empty());
+        for id in &corner.alternatives {
+            let tile = find_tile(id).unwrap();
+            assert_eq!(tile.mask, find_tile("curve-A0").unwrap().mask);
+            assert_ne!(id, "curve-A0");
+        }
+        assert!(corner.alternatives.iter().any(|id| id.starts_with("sharp")));
+        assert!(cell(Point::new(0, 0)).alternatives.is_empty());
+        assert!(cell(Point::new(1, 2)).alternatives.is_empty());
+
+        // Off by default
+        let plain = path_to_tiles(&path, grid_size, TileOptions::default());
+        let corner = plain.grid[0][2].as_ref().unwrap();
+        assert!(corner.alternatives.is_empty());
+    }
+
+    #[test]
+    fn test_lane_lock() {
+        let grid_size = GridSize { rows: 3, cols: 3 };
+        let lanes = |result: &RoadGridResult| -> HashSet<String> {
+            let cells = result.grid.iter().flatten().flatten();
+            cells.flat_map(|cell| cell.connections.iter().map(|c| c.ports.clone())).collect()
+        };
+        let locked = |lane, allow_lane_change| TileOptions {
+            lane_lock: Some(lane),
+            allow_lane_change,
+            ..Default::default()
+        };
+
+        let snake = build_snake_path(Point::new(0, 0), grid_size, false).unwrap();
+        let inner = path_to_tiles(&snake, grid_size, locked(PortSet::P12, true));
+        assert!(inner.valid);
+        assert_eq!(lanes(&inner), HashSet::from(["12".to_string()]));
+
+        // Stepping onto a diagonal forces the inner lane out to 23
+        let path = [Point::new(0, 0), Point::new(0, 1), Point::new(1, 2), Point::new(2, 2)];
+        let options = TileOptions {
+            start_port: Some(PortSet::P12),
+            ..Default::default()
+        };
+        let free = path_to_tiles(&path, grid_size, options);
+        assert!(free.valid);
+        assert_eq!(lanes(&free).len(), 2);
+        let broken = path_to_tiles(&path, grid_size, locked(PortSet::P12, false));
+        assert!(!broken.valid);
+        assert_eq!(broken.failure.unwrap().path_index, 1);
+        assert!(broken.reason.unwrap().contains("lane lock to 12"));
+        assert!(path_to_tiles(&path, grid_size, locked(PortSet::P23, false)).valid);
+    }
+
+    #[test]
+    fn test_start_port_inner_lane() {
+        let grid_size = GridSize { rows: 3, cols: 3 };
+        let mut state = PathState::new(grid_size, 10_000);
+        assert!(find_path_internal(&mut state, Point::new(0, 0), Point::new(2, 2)));
+        let path = state.path;
+
+        let options = TileOptions { start_port: Some(PortSet::P12), ..Default::default() };
+        let result = path_to_tiles(&path, grid_size, options);
+        assert!(result.valid);
+        let start = result.grid[0][0].as_ref().unwrap();
+        assert_eq!(start.connections[0].ports, "12");
+        let second = result.grid[path[1].row as usize][path[1].col as usize].as_ref().unwrap();
+        assert_eq!(second.connections[0].ports, "12");
+        assert!(validate_grid(&result.grid, grid_size).is_empty());
+
+        // Omitted keeps the outer lane
+        let default = path_to_tiles(&path, grid_size, TileOptions::default());
+        let second = default.grid[path[1].row as usize][path[1].col as usize].as_ref().unwrap();
+        assert_eq!(second.connections[0].ports, "23");
+    }
+
+    #[test]
+    fn test_reachable_cells() {
+        let grid_size = GridSize { rows: 3, cols: 3 };
+        let state = PathState::new(grid_size, 0);
+        let cells = reachable_within(&state, Point::new(0, 0), 2);
+        assert_eq!(cells.len(), 6);
+        assert_eq!(cells[0], ReachableCell { row: 0, col: 0, distance: 0 });
+        assert!(cells.iter().all(|c| c.distance as i32 == c.row + c.col));
+
+        // A wall forces the detour: (0,2) is 2 steps away without it, 6 with
+        let options = SearchOptions {
+            blocked: vec![Point::new(0, 1), Point::new(1, 1)],
+            ..Default::default()
+        };
+        let state = PathState::with_options(grid_size, 0, options);
+        let cells = reachable_within(&state, Point::new(0, 0), 10);
+        assert_eq!(cells.len(), 7);
+        let far = cells.iter().find(|c| (c.row, c.col) == (0, 2)).unwrap();
+        assert_eq!(far.distance, 6);
+
+        assert!(reachable_within(&state, Point::new(0, 1), 3).is_empty());
+        assert_eq!(reachable_within(&state, Point::new(0, 0), 0).len(), 1);
+    }
+
+    #[test]
+    fn test_canonical_endpoints() {
+        let square = GridSize { rows: 4, cols: 4 };
+        let (start, end) = (Point::new(0, 0), Point::new(3, 3));
+        let expected = canonicalize_endpoints(start, end, square);
+
+        // The four rotations of a corner-to-corner pair share one form
+        let mut rotated = (start, end);
+        for _ in 0..4 {
+            let pair = canonicalize_endpoints(rotated.0, rotated.1, square);
+            assert_eq!((pair.start, pair.end), (expected.start, expected.end));
+            rotated = (
+                GridTransform::Rot90.apply(rotated.0, square),
+                GridTransform::Rot90.apply(rotated.1, square),
+            );
+        }
+        assert_eq!(rotated, (start, end));
+
+        // A rectangle cannot be transposed: these two stay distinct
+        let rect = GridSize { rows: 2, cols: 3 };
+        let a = canonicalize_endpoints(Point::new(0, 1), Point::new(1, 1), rect);
+        let b = canonicalize_endpoints(Point::new(1, 0), Point::new(1, 2), rect);
+        assert_ne!((a.start, a.end), (b.start, b.end));
+        // ...but the 180° image of a pair canonicalizes like the pair
+        let c = canonicalize_endpoints(Point::new(1, 2), Point::new(0, 0), rect);
+        let d = canonicalize_endpoints(Point::new(0, 0), Point::new(1, 2), rect);
+        assert_eq!((c.start, c.end), (d.start, d.end));
+        assert_eq!(GridTransform::symmetries(rect).len(), 4);
+    }
+
+    #[test]
+    fn test_path_stats() {
+        let path = vec![Point::new(0, 0), Point::new(0, 1), Point::new(0, 2)];
+        let stats = compute_path_stats(&path).unwrap();
+        assert_eq!(
+            stats,
+            PathStats {
+                length: 3,
+                turns: 0,
+                straights: 1,
+                corners: 0,
+                longest_straight_run: 3,
+                self_adjacencies: 0,
+            }
+        );
+
+        // Snake over a 3x3 grid: right, right, down, left, left, down, right, right
+        let snake = vec![
+            Point::new(0, 0),
+            Point::new(0, 1),
+            Point::new(0, 2),
+            Point::new(1, 2),
+            Point::new(1, 1),
+            Point::new(1, 0),
+            Point::new(2, 0),
+            Point::new(2, 1),
+            Point::new(2, 2),
+        ];
+        let stats = compute_path_stats(&snake).unwrap();
+        assert_eq!(stats.length, 9);
+        assert_eq!(stats.turns, 4);
+        assert_eq!(stats.corners, 4);
+        assert_eq!(stats.straights, 3);
+        assert_eq!(stats.longest_straight_run, 3);
+        // 12 grid edges, 8 of them on the path
+        assert_eq!(stats.self_adjacencies, 4);
+
+        assert_eq!(compute_path_stats(&[Point::new(0, 0), Point::new(1, 1)]), None);
+        assert_eq!(compute_path_stats(&[]).unwrap().length, 0);
+    }
+
+    #[test]
+    fn test_weighted_search() {
+        let grid_size = GridSize { rows: 4, cols: 4 };
+        let run = |weights: Vec<Vec<i32>>| {
+            let options = SearchOptions {
+                weights: Some(weights),
+                ..Default::default()
+            };
+            solve_road_path(Point::new(0, 0), Point::new(3, 0), grid_size, 100_000, options)
+        };
+
+        // Prefer going across the top row first vs. down the left column
+        let by_row = run((0..4).map(|r| vec![r; 4]).collect());
+        let by_col = run((0..4).map(|_| (0..4).collect()).collect());
+        for result in [&by_row, &by_col] {
+            assert!(result.found);
+            assert_eq!(result.path.len(), 16);
+            assert!(result.path.windows(2).all(|w| is_adjacent(w[0], w[1])));
+        }
+        assert_ne!(by_row.path, by_col.path);
+
+        // Uniform weights keep the unweighted order
+        let unweighted = solve_road_path(Point::new(0, 0), Point::new(3, 0), grid_size, 100_000, SearchOptions::default());
+        assert_eq!(run(vec![vec![7; 4]; 4]).path, unweighted.path);
+    }
+
+    #[test]
+    fn test_connection_mask_round_trip() {
+        for tile in get_all_tiles() {
+            let info = tile.to_info();
+            assert_eq!(encode_connections(&info.connections), Some(tile.mask));
+
+            // Decoding yields Direction::with_diagonals() order; the table
+            // lists e.g. straight-h as left then right
+            let mut expected = info.connections.clone();
+            expected.sort_by_key(|c| Direction::with_diagonals().iter().position(|d| d.to_string() == c.direction));
+            assert_eq!(decode_connections(tile.mask), expected);
+
+            let cell = CellData::new(tile.id.to_string(), decode_connections(tile.mask), 0);
+            assert_eq!(cell.connection_mask(), Some(tile.mask));
+        }
+
+        let bad = [Connection { direction: "north".to_string(), ports: "23".to_string() }];
+        assert_eq!(encode_connections(&bad), None);
+        assert_eq!(encode_connections(&[]), Some(0));
+    }
+
+    #[test]
+    fn test_transform_path() {
+        let grid_size = GridSize { rows: 2, cols: 3 };
+        let result = solve_road_path(Point::new(0, 0), Point::new(1, 0), grid_size, 10_000, SearchOptions::default());
+        assert!(result.found);
+
+        for name in ["flip_h", "flip_v", "rot90", "rot180", "rot270"] {
+            let transform = GridTransform::parse(name).unwrap();
+            let moved = transform_path_internal(&result.path, grid_size, transform);
+            assert!(moved.path.iter().all(|&p| moved.grid_size.contains(p)));
+            assert!(moved.path.windows(2).all(|w| is_adjacent(w[0], w[1])));
+        }
+
+        let rot90 = transform_path_internal(&result.path, grid_size, GridTransform::Rot90);
+        assert_eq!((rot90.grid_size.rows, rot90.grid_size.cols), (3, 2));
+        assert_eq!(rot90.path[0], Point::new(0, 1));
+
+        let once = transform_path_internal(&result.path, grid_size, GridTransform::Rot180);
+        let twice = transform_path_internal(&once.path, once.grid_size, GridTransform::Rot180);
+        assert_eq!(twice.path, result.path);
+
+        assert_eq!(GridTransform::parse("rot45"), None);
+    }
+
+    #[test]
+    fn test_diagonal_search() {
+        let diagonal = || SearchOptions {
+            diagonals: true,
+            ..Default::default()
+        };
+
+        // Same-colored corners of a 2x2 are out of reach orthogonally
+        let grid_size = GridSize { rows: 2, cols: 2 };
+        let (start, end) = (Point::new(0, 0), Point::new(1, 1));
+        assert!(!solve_road_path(start, end, grid_size, 1_000, SearchOptions::default()).found);
+        let result = solve_road_path(start, end, grid_size, 1_000, diagonal());
+        assert!(result.found);
+        assert_eq!(result.path.len(), 4);
+
+        let tiled = path_to_tiles(&result.path, grid_size, TileOptions::default());
+        assert!(tiled.valid);
+        assert!(validate_grid(&tiled.grid, grid_size).is_empty());
+        let diagonal_tiles = tiled.grid.iter().flatten().flatten().filter(|c| c.tile_id.starts_with("diagonal-"));
+        assert_eq!(diagonal_tiles.count(), 2);
+
+        // The two diagonals through one corner may not both be used
+        let mut state = PathState::with_options(grid_size, 0, diagonal());
+        state.visit(Point::new(0, 1));
+        state.visit(Point::new(1, 0));
+        assert!(state.crosses_path(Point::new(0, 0), Point::new(1, 1)));
+        assert!(!state.get_neighbors(Point::new(0, 0)).iter().any(|&(p, _)| p == Point::new(1, 1)));
+
+        let grid_size = GridSize { rows: 4, cols: 4 };
+        let result = solve_road_path(Point::new(0, 0), Point::new(2, 2), grid_size, 100_000, diagonal());
+        assert!(result.found);
+        assert_eq!(result.path.len(), 16);
+        assert!(result.path.windows(2).all(|w| step_direction(w[0], w[1]).is_some()));
+        // No step crosses an earlier diagonal step
+        for (i, w) in result.path.windows(2).enumerate() {
+            let (a, b) = (Point::new(w[1].row, w[0].col), Point::new(w[0].row, w[1].col));
+            let crossed = result.path[..=i].windows(2).any(|v| (v[0] == a && v[1] == b) || (v[0] == b && v[1] == a));
+            assert!(!crossed);
+        }
+        assert!(path_to_tiles(&result.path, grid_size, TileOptions::default()).valid);
+    }
+
+    #[test]
+    fn test_best_partial_path() {
+        // A tiny budget stops the search partway
+        let grid_size = GridSize { rows: 4, cols: 4 };
+        let result = solve_road_path(Point::new(0, 0), Point::new(3, 0), grid_size, 5, SearchOptions::default());
+        assert!(!result.found);
+        let partial = result.best_partial.unwrap();
+        assert_eq!(partial.len(), 5);
+        assert_eq!(partial[0], Point::new(0, 0));
+        assert!(partial.windows(2).all(|w| is_adjacent(w[0], w[1])));
+
+        // Exhausted searches report their deepest prefix too: parity allows
+        // the middle pair of a 1x4 strip, but one end is always left over
+        let strip = GridSize { rows: 1, cols: 4 };
+        let result = solve_road_path(Point::new(0, 1), Point::new(0, 2), strip, 10_000, SearchOptions::default());
+        assert!(!result.found);
+        assert!(result.reason.is_none());
+        assert_eq!(result.best_partial.unwrap(), vec![Point::new(0, 1), Point::new(0, 0)]);
+
+        // Found paths and skipped searches leave it unset
+        let found = solve_road_path(Point::new(0, 0), Point::new(3, 0), grid_size, 10_000, SearchOptions::default());
+        assert!(found.found && found.best_partial.is_none());
+        let skipped = solve_road_path(Point::new(0, 0), Point::new(0, 2), grid_size, 10_000, SearchOptions::default());
+        assert!(skipped.reason.is_some() && skipped.best_partial.is_none());
+    }
+
+    #[test]
+    fn test_resumable_search_matches_single_run() {
+        let grid_size = GridSize { rows: 5, cols: 5 };
+        let (start, end) = (Point::new(0, 0), Point::new(4, 3));
+        let blocked = vec![Point::new(2, 2)];
+        let options = SearchOptions {
+            blocked: blocked.clone(),
+            ..Default::default()
+        };
+        let single = solve_road_path(start, end, grid_size, 1_000_000, options);
+        assert!(single.found);
+        assert!(single.iterations > 100);
+
+        // Resume in slices of 37 iterations until the search finishes
+        let mut step = start_resumable_search(start, end, grid_size, 37, blocked);
+        let mut slices = 1;
+        while let Some(snapshot) = step.snapshot {
+            assert!(!step.result.found);
+            assert_eq!(snapshot.iterations, 37 * slices);
+            step = resume_search(snapshot, 37).unwrap();
+            slices += 1;
+        }
+        assert!(step.result.found);
+        assert_eq!(step.result.path, single.path);
+        assert_eq!(step.result.iterations, single.iterations);
+        assert!(slices > 2);
+    }
+
+    #[test]
+    fn test_resume_rejects_inconsistent_snapshot() {
+        let grid_size = GridSize { rows: 4, cols: 4 };
+        let step = start_resumable_search(Point::new(0, 0), Point::new(3, 0), grid_size, 3, vec![]);
+        let snapshot = step.snapshot.unwrap();
+        assert_eq!(snapshot.path.len(), snapshot.frames.len());
+
+        let mut gap = snapshot.clone();
+        gap.path[1] = Point::new(2, 2);
+        assert!(resume_search(gap, 100).is_none());
+
+        let mut stray = snapshot.clone();
+        stray.frames[0].candidates.push(Point::new(9, 9));
+        assert!(resume_search(stray, 100).is_none());
+
+        // In the grid, but not next to the cell the frame or pending cell
+        // would extend
+        let mut far = snapshot.clone();
+        far.frames[0].candidates.push(Point::new(3, 3));
+        assert!(resume_search(far, 100).is_none());
+        assert!(snapshot.pending.is_some());
+        let mut jump = snapshot.clone();
+        jump.pending = Some(Point::new(3, 3));
+        assert!(resume_search(jump, 100).is_none());
+
+        let mut short = snapshot.clone();
+        short.frames.pop();
+        assert!(resume_search(short, 100).is_none());
+
+        assert!(resume_search(snapshot, 1_000).unwrap().result.found);
+    }
+
+    #[test]
+    fn test_is_hamiltonian() {
+        let grid_size = GridSize { rows: 3, cols: 3 };
+        let snake = build_snake_path(Point::new(0, 0), grid_size, false).unwrap();
+        assert!(is_hamiltonian(&snake, grid_size, &[]));
+        // Valid but partial, so `check_path` alone would accept it
+        assert!(check_path(&snake[..8], grid_size).valid);
+        assert!(!is_hamiltonian(&snake[..8], grid_size, &[]));
+
+        // With the last cell blocked, the partial path is complete; through
+        // an obstacle it is not
+        let corner = Point::new(2, 2);
+        assert!(is_hamiltonian(&snake[..8], grid_size, &[corner, Point::new(5, 5)]));
+        assert!(!is_hamiltonian(&snake[..8], grid_size, &[Point::new(1, 1)]));
+        assert!(!is_hamiltonian(&snake, grid_size, &[corner]));
+
+        let mut jump = snake.clone();
+        jump.swap(2, 3);
+        assert!(!is_hamiltonian(&jump, grid_size, &[]));
+        assert!(!is_hamiltonian(&[], GridSize { rows: 0, cols: 0 }, &[]));
+    }
+
+    #[test]
+    fn test_check_path() {
+        let grid_size = GridSize { rows: 2, cols: 3 };
+        let path = [Point::new(0, 0), Point::new(0, 1), Point::new(1, 1)];
+        assert!(check_path(&path, grid_size).valid);
+
+        let index = |path: &[Point]| check_path(path, grid_size).first_bad_index;
+        assert_eq!(index(&[Point::new(0, 0), Point::new(0, 3)]), Some(1));
+        assert_eq!(index(&[Point::new(0, 0), Point::new(1, 1)]), Some(1));
+        assert_eq!(index(&[Point::new(0, 0), Point::new(0, 1), Point::new(0, 0)]), Some(2));
+        assert_eq!(index(&[Point::new(-1, 0)]), Some(0));
+        let empty = check_path(&[], grid_size);
+        assert!(!empty.valid && empty.first_bad_index.is_none());
+
+        // Out-of-bounds points make the tiling invalid instead of panicking
+        let off_grid = [Point::new(0, 2), Point::new(0, 3)];
+        assert!(!path_to_tiles(&off_grid, grid_size, TileOptions::default()).valid);
+    }
+
+    #[test]
+    fn test_junction_tiles() {
+        let tiles = get_all_tiles();
+        let get = |id: &str| tiles.iter().find(|t| t.id == id).unwrap();
+
+        let tee = get("tee-8A");
+        assert!(tee.is_junction());
+        assert_eq!(tee.to_info().connections.len(), 3);
+        assert_eq!(tee.get_connection(Direction::Left), Some(PortSet::P23));
+        assert!(!tee.has_direction(Direction::Down));
+        // Open at the bottom, left, top and right in turn
+        let rotations: Vec<u16> = ["tee-8A", "tee-2A", "tee-A8", "tee-A2"].iter().map(|id| get(id).rotation).collect();
+        assert_eq!(rotations, [0, 90, 180, 270]);
+
+        let cross = get("cross-55");
+        assert_eq!(cross.to_info().connections.len(), 4);
+        assert!(Direction::all().iter().all(|&dir| cross.get_connection(dir) == Some(PortSet::P12)));
+
+        // The path tiler only ever uses two-way pieces
+        for entry in Direction::all() {
+            for exit in Direction::all() {
+                if let Some((id, _, _)) = lookup_tile(entry, exit, None, true) {
+                    assert!(!get(&id).is_junction());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_next_candidates_follow_search_order() {
+        let grid_size = GridSize { rows: 3, cols: 3 };
+        // From the center with nothing visited, all four edge cells tie
+        let candidates = next_candidates_internal(&[], Point::new(1, 1), grid_size);
+        assert_eq!(candidates.len(), 4);
+        assert!(candidates.iter().all(|c| c.unvisited_neighbors == 2));
+        assert_eq!(candidates[0].direction, "up");
+
+        // Next to a visited corner, (0,1) has fewer options and comes first
+        let candidates = next_candidates_internal(&[Point::new(0, 0)], Point::new(1, 0), grid_size);
+        let order: Vec<(i32, i32, usize)> = candidates.iter().map(|c| (c.row, c.col, c.unvisited_neighbors)).collect();
+        assert_eq!(order, [(2, 0, 1), (1, 1, 3)]);
+
+        // Replaying the found path step by step, the search always took a
+        // candidate that the query lists
+        let result = solve_road_path(Point::new(0, 0), Point::new(2, 2), grid_size, 10_000, SearchOptions::default());
+        for i in 1..result.path.len() {
+            let candidates = next_candidates_internal(&result.path[..i - 1], result.path[i - 1], grid_size);
+            let next = result.path[i];
+            assert!(candidates.iter().any(|c| (c.row, c.col) == (next.row, next.col)));
+        }
+    }
+
+    #[test]
+    fn test_zero_max_iterations_picks_auto_budget() {
+        let small = GridSize { rows: 2, cols: 2 };
+        let medium = GridSize { rows: 6, cols: 6 };
+        let huge = GridSize { rows: 100, cols: 100 };
+        assert_eq!(effective_max_iterations(0, small), AUTO_ITERATIONS_MIN);
+        assert_eq!(effective_max_iterations(0, medium), 36 * AUTO_ITERATIONS_PER_CELL);
+        assert_eq!(effective_max_iterations(0, huge), AUTO_ITERATIONS_MAX);
+        assert_eq!(effective_max_iterations(500, medium), 500);
+        assert_eq!(effective_max_iterations(u32::MAX, medium), MAX_ITERATIONS_CAP);
+
+        let grid_size = GridSize { rows: 4, cols: 4 };
+        let result = solve_road_path(
+            Point::new(0, 0),
+            Point::new(3, 0),
+            grid_size,
+            0,
+            SearchOptions::default(),
+        );
+        assert!(result.found);
+        assert_eq!(result.path.len(), 16);
+        assert!(solve_road_cycle(Point::new(0, 0), grid_size, 0).found);
+    }
+
+    #[test]
+    fn test_invalid_dimensions_and_endpoints_are_rejected() {
+        for (rows, cols) in [(0, 4), (4, 0), (-1, 4), (i32::MIN, i32::MIN)] {
+            let grid_size = GridSize { rows, cols };
+            let result = solve_road_path(
+                Point::new(0, 0),
+                Point::new(0, 1),
+                grid_size,
+                1000,
+                SearchOptions::default(),
+            );
+            assert!(!result.found);
+            assert_eq!(result.iterations, 0);
+            assert!(result.reason.unwrap().contains("must be positive"));
+            assert!(solve_road_cycle(Point::new(0, 0), grid_size, 1000).reason.is_some());
+            let pair = EndpointPair {
+                start: Point::new(0, 0),
+                end: Point::new(0, 1),
+            };
+            assert!(solve_road_paths_batch(&[pair], grid_size, 1000, false)[0].reason.is_some());
+            assert!(PathState::new(grid_size, 0).grid.iter().all(Vec::is_empty));
+        }
+
+        // Past the area limit the size is rejected the same way, before allocating
+        let huge = GridSize { rows: 100_000, cols: 100_000 };
+        let result = solve_road_path(
+            Point::new(0, 0),
+            Point::new(0, 1),
+            huge,
+            1000,
+            SearchOptions::default(),
+        );
+        assert_eq!(result.iterations, 0);
+        assert!(result.reason.unwrap().contains("more than the 1048576 supported"));
+        assert_eq!(result.input_error.unwrap().kind, "invalid_dimensions");
+        assert!(huge.table(false).is_empty());
+
+        let grid_size = GridSize { rows: 3, cols: 3 };
+        let result = solve_road_path(
+            Point::new(0, 0),
+            Point::new(3, 0),
+            grid_size,
+            1000,
+            SearchOptions::default(),
+        );
+        assert_eq!(result.reason.as_deref(), Some("end cell (3, 0) is outside the 3x3 grid"));
+        let error = result.input_error.unwrap();
+        assert_eq!(error.kind, "out_of_bounds");
+        assert_eq!(error.point.as_deref(), Some("end"));
+        assert_eq!((error.cell, error.grid_size), (Some(Point::new(3, 0)), grid_size));
+        let resumable = start_resumable_search(Point::new(-1, 0), Point::new(2, 2), grid_size, 1000, vec![]);
+        assert!(resumable.snapshot.is_none());
+        assert!(resumable.result.reason.unwrap().contains("start cell (-1, 0)"));
+
+        // A path can't return to its start, except on a single cell
+        let same = solve_road_path(
+            Point::new(1, 1),
+            Point::new(1, 1),
+            grid_size,
+            1000,
+            SearchOptions::default(),
+        );
+        assert_eq!(same.input_error.unwrap().kind, "same_endpoints");
+        assert_eq!(same.iterations, 0);
+        let single = GridSize { rows: 1, cols: 1 };
+        let origin = Point::new(0, 0);
+        assert!(solve_road_path(origin, origin, single, 10, SearchOptions::default()).found);
+        assert!(solve_road_cycle(Point::new(1, 1), grid_size, 1000).input_error.is_none());
+    }
+
+    #[test]
+    fn test_solvable_endpoints_match_individual_searches() {
+        let grid_size = GridSize { rows: 3, cols: 4 };
+        let pairs = solvable_endpoint_pairs(grid_size, 100_000);
+        assert!(!pairs.is_empty());
+
+        let mut expected = Vec::new();
+        for a in 0..12 {
+            for b in a + 1..12 {
+                let start = Point::new(a / 4, a % 4);
+                let end = Point::new(b / 4, b % 4);
+                let options = SearchOptions::default();
+                let result = solve_road_path(start, end, grid_size, 100_000, options);
+                if result.found {
+                    expected.push(SolvableEndpoints {
+                        start,
+                        end,
+                        iterations: result.iterations,
+                    });
+                }
+            }
+        }
+        assert_eq!(pairs, expected);
+        assert!(pairs
+            .iter()
+            .all(|p| cell_parity(p.start.row, p.start.col) != cell_parity(p.end.row, p.end.col)));
+
+        assert!(solvable_endpoint_pairs(GridSize { rows: 0, cols: 3 }, 1000).is_empty());
+    }
+
+    #[test]
+    fn test_cells_carry_sprite_rotation() {
+        let grid_size = GridSize { rows: 2, cols: 2 };
+        let cell = |result: &RoadGridResult, row: usize, col: usize| result.grid[row][col].clone().unwrap();
+
+        // (1,0) connects up and right: the base corner, unrotated
+        let path = [Point::new(0, 0), Point::new(1, 0), Point::new(1, 1)];
+        let result = path_to_tiles(&path, grid_size, TileOptions::default());
+        assert!(result.valid);
+        assert_eq!(cell(&result, 1, 0).rotation, 0);
+        assert_eq!(cell(&result, 0, 0).rotation, 180); // start opens down
+        assert_eq!(cell(&result, 1, 1).rotation, 270); // goal opens left
+
+        // (0,1) connects left and down: the corner turned 180°
+        let path = [Point::new(0, 0), Point::new(0, 1), Point::new(1, 1)];
+        let result = path_to_tiles(&path, grid_size, TileOptions::default());
+        assert_eq!(cell(&result, 0, 1).rotation, 180);
+
+        let tiles = get_all_tiles();
+        for row in &result.grid {
+            for cell in row.iter().flatten() {
+                if let Some(tile) = tiles.iter().find(|t| t.id == cell.tile_id) {
+                    assert_eq!(cell.rotation, tile.rotation);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_path_iterator_yields_enumeration_lazily() {
+        let grid_size = GridSize { rows: 3, cols: 4 };
+        let (start, end) = (Point::new(0, 0), Point::new(2, 3));
+        let all: Vec<_> = PathIterator::new(start, end, grid_size, &[], 1_000_000).collect();
+        let options = SearchOptions::default();
+        let enumerated = solve_road_paths(start, end, grid_size, 1_000_000, 1000, options);
+        assert!(all.len() > 2);
+        assert_eq!(all, enumerated.paths);
+
+        let mut lazy = PathIterator::new(start, end, grid_size, &[], 1_000_000);
+        let first_two: Vec<_> = lazy.by_ref().take(2).collect();
+        assert_eq!(first_two, all[..2]);
+        let after_two = lazy.iterations();
+        assert_eq!(lazy.next().as_ref(), Some(&all[2]));
+        assert!(lazy.iterations() > after_two);
+
+        // Parity rules out same-colored endpoints on an even grid
+        assert_eq!(PathIterator::new(start, Point::new(0, 2), grid_size, &[], 1000).next(), None);
+        assert_eq!(PathIterator::new(start, Point::new(3, 0), grid_size, &[], 1000).next(), None);
+        // A small budget ends the iteration early
+        assert!(PathIterator::new(start, end, grid_size, &[], 50).count() < all.len());
+    }
+
+    #[test]
+    fn test_cells_carry_travel_directions() {
+        let grid_size = GridSize { rows: 2, cols: 2 };
+        let path = [Point::new(0, 0), Point::new(1, 0), Point::new(1, 1), Point::new(0, 1)];
+        let result = path_to_tiles(&path, grid_size, TileOptions::default());
+        assert!(result.valid);
+
+        let travel = |p: Point| {
+            let cell = result.grid[p.row as usize][p.col as usize].as_ref().unwrap();
+            (cell.entry_dir.as_deref(), cell.exit_dir.as_deref())
+        };
+        assert_eq!(travel(path[0]), (None, Some("down")));
+        assert_eq!(travel(path[1]), (Some("down"), Some("right")));
+        assert_eq!(travel(path[2]), (Some("right"), Some("up")));
+        assert_eq!(travel(path[3]), (Some("up"), None));
+
+        let compact = encode_compact_grid(&result.grid).unwrap();
+        assert_eq!(decode_compact_grid(&compact).unwrap(), result.grid);
+    }
+
+    #[test]
+    fn test_snake_cycle_covers_rectangles() {
+        for rows in 1..=7 {
+            for cols in 1..=7 {
+                let grid_size = GridSize { rows, cols };
+                let Some(cycle) = build_snake_cycle(grid_size) else {
+                    assert!(rows < 2 || cols < 2 || (rows * cols) % 2 != 0, "{}x{}", rows, cols);
+                    continue;
+                };
+                let n = cycle.len();
+                assert_eq!(n, (rows * cols) as usize);
+                assert!(check_path(&cycle, grid_size).valid, "{}x{}", rows, cols);
+                assert!(is_adjacent(cycle[n - 1], cycle[0]), "{}x{}", rows, cols);
+                assert!(cycle_to_tiles(&cycle, grid_size).valid, "{}x{}", rows, cols);
+            }
+        }
+    }
+
+    #[test]
+    fn test_snake_paths_from_corners() {
+        for (rows, cols) in [(1, 1), (1, 5), (3, 4), (4, 3), (5, 5)] {
+            let grid_size = GridSize { rows, cols };
+            for start in [
+                Point::new(0, 0),
+                Point::new(0, cols - 1),
+                Point::new(rows - 1, 0),
+                Point::new(rows - 1, cols - 1),
+            ] {
+                for by_columns in [false, true] {
+                    let path = build_snake_path(start, grid_size, by_columns).unwrap();
+                    assert_eq!(path[0], start);
+                    assert_eq!(path.len(), (rows * cols) as usize);
+                    assert!(check_path(&path, grid_size).valid, "{}x{} {:?}", rows, cols, start);
+                }
+            }
+        }
+        let grid_size = GridSize { rows: 3, cols: 4 };
+        assert!(build_snake_path(Point::new(0, 1), grid_size, false).is_none());
+
+        // Rows-first from the top-left of a 3x4 grid ends at the bottom-right
+        let snake = SearchOptions {
+            strategy: SearchStrategy::Snake,
+            ..Default::default()
+        };
+        let result = solve_road_path(Point::new(0, 0), Point::new(2, 3), grid_size, 1000, snake);
+        assert!(result.found);
+        assert_eq!(result.iterations, 0);
+        assert_eq!(result.path, build_snake_path(Point::new(0, 0), grid_size, false).unwrap());
+
+        // Ends no snake reaches fall back to the search
+        let snake = SearchOptions {
+            strategy: SearchStrategy::Snake,
+            ..Default::default()
+        };
+        let result = solve_road_path(Point::new(0, 0), Point::new(1, 0), grid_size, 100_000, snake);
+        assert!(result.found);
+        assert!(result.iterations > 0);
+        assert_eq!(SearchStrategy::parse("snake"), Some(SearchStrategy::Snake));
+    }
+
+    #[test]
+    fn test_tiles_connect_on_matching_edges() {
+        // A tiled path only places neighbors whose shared edge matches
+        let grid_size = GridSize { rows: 3, cols: 3 };
+        let path = build_snake_path(Point::new(0, 0), grid_size, false).unwrap();
+        let result = path_to_tiles(&path, grid_size, TileOptions::default());
+        let id = |p: Point| {
+            let cell = result.grid[p.row as usize][p.col as usize].as_ref().unwrap();
+            cell.tile_id.clone()
+        };
+        for w in path[1..path.len() - 1].windows(2) {
+            let dir = step_direction(w[0], w[1]).unwrap();
+            assert!(tiles_connect(&id(w[0]), &id(w[1]), dir.to_string()));
+            assert!(tiles_connect(&id(w[1]), &id(w[0]), dir.opposite().to_string()));
+        }
+
+        assert!(tiles_connect("straight-h-44", "straight-h-44", "right"));
+        assert!(!tiles_connect("straight-h-44", "straight-h-44", "up"));
+        assert!(!tiles_connect("straight-h-44", "straight-h-88", "right"));
+        assert!(!tiles_connect("straight-h-44", "start", "right"));
+        assert!(!tiles_connect("straight-h-44", "straight-h-44", "north"));
+    }
+
+    #[test]
+    fn test_tile_side_ports() {
+        let ports = find_tile("straight-h-44").unwrap().side_ports();
+        let expected = TileConnections {
+            up: None,
+            right: Some("12".to_string()),
+            down: None,
+            left: Some("12".to_string()),
+        };
+        assert_eq!(ports, expected);
+
+        let corner = find_tile("curve-A0").unwrap().side_ports();
+        assert_eq!((corner.up, corner.right), (None, None));
+        assert_eq!(corner.down.as_deref(), Some("23"));
+        assert_eq!(corner.left.as_deref(), Some("23"));
+    }
+
+    #[test]
+    fn test_migrate_unversioned_grid() {
+        let grid_size = GridSize { rows: 2, cols: 2 };
+        let path = [Point::new(0, 0), Point::new(1, 0), Point::new(1, 1), Point::new(0, 1)];
+        let current = path_to_tiles(&path, grid_size, TileOptions::default());
+        assert_eq!(current.schema_version, GRID_SCHEMA_VERSION);
+
+        // A version 0 payload has neither the derived cell fields nor the tag
+        let mut old = current.clone();
+        old.schema_version = 0;
+        for cell in old.grid.iter_mut().flatten().flatten() {
+            cell.rotation = 0;
+            cell.entry_dir = None;
+            cell.exit_dir = None;
+        }
+        let migrated = old.clone().migrate(0).unwrap();
+        assert_eq!(migrated.schema_version, GRID_SCHEMA_VERSION);
+        assert_eq!(migrated.grid, current.grid);
+
+        assert!(old.clone().migrate(GRID_SCHEMA_VERSION + 1).is_none());
+
+        // Before road ids every cell belonged to the one road
+        let mut single = current.clone();
+        single.schema_version = 1;
+        for cell in single.grid.iter_mut().flatten().flatten() {
+            cell.road_id = 3;
+        }
+        let migrated = single.migrate(1).unwrap();
+        assert_eq!(migrated.schema_version, GRID_SCHEMA_VERSION);
+        assert_eq!(migrated.grid, current.grid);
+
+        // Nor were alternatives listed
+        let mut unlisted = current.clone();
+        unlisted.schema_version = 2;
+        for cell in unlisted.grid.iter_mut().flatten().flatten() {
+            cell.alternatives = vec!["sharp-A0".to_string()];
+        }
+        assert_eq!(unlisted.migrate(2).unwrap().grid, current.grid);
+    }
+
+    #[test]
+    fn test_prefer_straight_tie_break_reduces_turns() {
+        let grid_size = GridSize { rows: 4, cols: 4 };
+        let turns = |start: Point, end: Point, tie_break| {
+            let options = SearchOptions {
+                tie_break,
+                ..Default::default()
+            };
+            let result = solve_road_path(start, end, grid_size, 100_000, options);
+            result.found.then(|| compute_path_stats(&result.path).unwrap().turns)
+        };
+
+        let (mut fixed_total, mut straight_total, mut turn_total) = (0, 0, 0);
+        for a in 0..16 {
+            for b in a + 1..16 {
+                let (start, end) = (Point::new(a / 4, a % 4), Point::new(b / 4, b % 4));
+                let Some(fixed) = turns(start, end, TieBreak::FixedOrder) else {
+                    continue;
+                };
+                let straight = turns(start, end, TieBreak::PreferStraight).unwrap();
+                if a == 0 {
+                    // From the corner, preferring straight never adds turns
+                    assert!(straight <= fixed, "{:?} {} > {}", end, straight, fixed);
+                }
+                fixed_total += fixed;
+                straight_total += straight;
+                turn_total += turns(start, end, TieBreak::PreferTurn).unwrap();
+            }
+        }
+        assert!(straight_total < fixed_total, "{} vs {}", straight_total, fixed_total);
+        assert!(turn_total > fixed_total, "{} vs {}", turn_total, fixed_total);
+        assert_eq!(TieBreak::parse("straight"), Some(TieBreak::PreferStraight));
+        assert_eq!(TieBreak::parse("zigzag"), None);
+    }
+
+    #[test]
+    fn test_render_ascii() {
+        let strip = GridSize { rows: 1, cols: 3 };
+        let path = [Point::new(0, 0), Point::new(0, 1), Point::new(0, 2)];
+        assert_eq!(render_path_ascii(&path, strip).unwrap(), "S─G");
+
+        let grid_size = GridSize { rows: 3, cols: 3 };
+        let path = build_snake_path(Point::new(0, 0), grid_size, false).unwrap();
+        assert_eq!(render_path_ascii(&path, grid_size).unwrap(), "S─┐\n┌─┘\n└─G");
+        assert_eq!(render_path_ascii(&path[..4], grid_size).unwrap(), "S─┐\n..G\n...");
+        assert!(render_path_ascii(&[Point::new(0, 0), Point::new(1, 1)], grid_size).is_none());
+    }
+
+    #[test]
+    fn test_single_cell_and_two_cell_grids() {
+        // 1x1: the start is the end and already covers the grid
+        let single = GridSize { rows: 1, cols: 1 };
+        let origin = Point::new(0, 0);
+        let result = solve_road_path(origin, origin, single, 100, SearchOptions::default());
+        assert!(result.found);
+        assert_eq!(result.path, vec![origin]);
+        assert_eq!(result.iterations, 1);
+
+        let tiled = path_to_tiles(&result.path, single, TileOptions::default());
+        assert!(tiled.valid);
+        let cell = tiled.grid[0][0].as_ref().unwrap();
+        assert_eq!(cell.tile_id, "start");
+        assert!(cell.connections.is_empty());
+        assert_eq!((cell.entry_dir.as_deref(), cell.exit_dir.as_deref()), (None, None));
+        assert!(!path_to_tiles(&[], single, TileOptions::default()).valid);
+
+        // 1x2: only the two distinct cells can be joined
+        let pair = GridSize { rows: 1, cols: 2 };
+        let result = solve_road_path(origin, Point::new(0, 1), pair, 100, SearchOptions::default());
+        assert_eq!(result.path, vec![origin, Point::new(0, 1)]);
+        let result = solve_road_path(origin, origin, pair, 100, SearchOptions::default());
+        assert!(!result.found);
+        assert!(result.reason.is_some());
+    }
+
+    #[test]
+    fn test_spacious_tie_break_reduces_self_adjacency() {
+        // Partial routes on a 5x5 grid from the corner to every cell
+        let grid_size = GridSize { rows: 5, cols: 5 };
+        let total = |tie_break| {
+            let mut sum = 0;
+            for length in [9, 11, 13, 15] {
+                for b in 1..25 {
+                    let options = SearchOptions {
+                        tie_break,
+                        target_length: Some(length),
+                        ..Default::default()
+                    };
+                    let end = Point::new(b / 5, b % 5);
+                    let result = solve_road_path(Point::new(0, 0), end, grid_size, 100_000, options);
+                    if result.found {
+                        assert_eq!(result.path.len(), length);
+                        sum += compute_path_stats(&result.path).unwrap().self_adjacencies;
+                    }
+                }
+            }
+            sum
+        };
+        assert!(total(TieBreak::Spacious) < total(TieBreak::FixedOrder));
+        assert_eq!(TieBreak::parse("spacious"), Some(TieBreak::Spacious));
+    }
+
+    #[test]
+    fn test_grid_parity_info() {
+        let info = grid_parity_info(GridSize { rows: 3, cols: 3 });
+        assert_eq!(
+            info,
+            GridInfo {
+                area: 9,
+                is_even_area: false,
+                parity_balanced: false,
+                even_cells: 5,
+                odd_cells: 4,
+            }
+        );
+        let info = grid_parity_info(GridSize { rows: 2, cols: 3 });
+        assert!(info.is_even_area && info.parity_balanced);
+        assert_eq!((info.even_cells, info.odd_cells), (3, 3));
+        assert_eq!(grid_parity_info(GridSize { rows: 0, cols: 3 }).area, 0);
+    }
+
+    #[test]
+    fn test_smoothest_path_has_fewest_turns() {
+        let grid_size = GridSize { rows: 3, cols: 4 };
+        for end in [Point::new(2, 3), Point::new(1, 0), Point::new(0, 3)] {
+            let start = Point::new(0, 0);
+            let options = SearchOptions::default();
+            let all = solve_road_paths(start, end, grid_size, 1_000_000, 10_000, options);
+            let fewest = all.paths.iter().map(|p| compute_path_stats(p).unwrap().turns).min();
+
+            let result = solve_smoothest_path(start, end, grid_size, 1_000_000, vec![]);
+            assert!(result.optimal);
+            assert_eq!(result.found.then_some(result.turns), fewest, "{:?}", end);
+            if result.found {
+                assert!(check_path(&result.path, grid_size).valid);
+                assert_eq!(result.path.len(), 12);
+                assert_eq!(compute_path_stats(&result.path).unwrap().turns, result.turns);
+            }
+        }
+
+        // Corner to corner on 4x4 the row snake (6 turns) is optimal
+        let square = GridSize { rows: 4, cols: 4 };
+        let (corner, other) = (Point::new(0, 0), Point::new(3, 0));
+        let result = solve_smoothest_path(corner, other, square, 1_000_000, vec![]);
+        assert_eq!((result.found, result.turns, result.optimal), (true, 6, true));
+
+        let capped = solve_smoothest_path(corner, other, square, 20, vec![]);
+        assert!(!capped.optimal);
+        assert_eq!(capped.iterations, 20);
+    }
+
+    #[test]
+    fn test_open_end_path() {
+        let square = GridSize { rows: 4, cols: 4 };
+        let result = solve_open_end_path(Point::new(1, 1), square, 100_000);
+        assert!(result.found);
+        assert_eq!(result.path[0], Point::new(1, 1));
+        assert_eq!(result.path.len(), 16);
+        assert!(check_path(&result.path, square).valid);
+
+        // Three 3x3 corners sit on the majority color; an edge middle doesn't
+        let odd = GridSize { rows: 3, cols: 3 };
+        assert!(solve_open_end_path(Point::new(0, 0), odd, 100_000).found);
+        let minority = solve_open_end_path(Point::new(0, 1), odd, 100_000);
+        assert!(!minority.found);
+        assert!(minority.reason.unwrap().contains("the start"));
+
+        let single = solve_open_end_path(Point::new(0, 0), GridSize { rows: 1, cols: 1 }, 10);
+        assert_eq!(single.path, vec![Point::new(0, 0)]);
+        assert!(solve_open_end_path(Point::new(5, 0), odd, 10).reason.is_some());
+    }
+
+    #[test]
+    fn test_rate_puzzle() {
+        let tiny = rate_puzzle_internal(
+            Point::new(0, 0),
+            Point::new(0, 1),
+            GridSize { rows: 2, cols: 2 },
+            100_000,
+        )
+        .unwrap();
+        assert!(tiny.solvable);
+        assert_eq!((tiny.stars, tiny.solution_count), (1, 1));
+
+        let square = GridSize { rows: 5, cols: 5 };
+        let (corner, opposite) = (Point::new(0, 0), Point::new(4, 4));
+        let larger = rate_puzzle_internal(corner, opposite, square, 1_000_000).unwrap();
+        let count = count_paths(corner, opposite, square, 1_000_000);
+        assert_eq!(larger.solution_count, count.count);
+        assert!(larger.score > tiny.score);
+        assert!((1..=5).contains(&larger.stars));
+
+        // Same-color endpoints on 3x3 fail the parity check
+        let odd = GridSize { rows: 3, cols: 3 };
+        let impossible =
+            rate_puzzle_internal(Point::new(0, 0), Point::new(0, 1), odd, 100_000).unwrap();
+        assert!(!impossible.solvable);
+        assert_eq!(impossible.stars, 0);
+        assert!(rate_puzzle_internal(Point::new(0, 0), Point::new(3, 3), odd, 100_000).is_none());
+    }
+
+    #[test]
+    fn test_generated_corners_match_literal_table() {
+        use Direction::*;
+        use PortSet::*;
+        use TileVariant::*;
+
+        // The table as it was written out by hand before being generated
+        let expected = vec![
+            // Curve tiles (16)
+            tile("curve-05", Curve, 0x05, (Up, P12), (Right, P12)),
+            tile("curve-06", Curve, 0x06, (Up, P23), (Right, P12)),
+            tile("curve-09", Curve, 0x09, (Up, P12), (Right, P23)),
+            tile("curve-0A", Curve, 0x0A, (Up, P23), (Right, P23)),
+            tile("curve-14", Curve, 0x14, (Right, P12), (Down, P12)),
+            tile("curve-18", Curve, 0x18, (Right, P23), (Down, P12)),
+            tile("curve-24", Curve, 0x24, (Right, P12), (Down, P23)),
+            tile("curve-28", Curve, 0x28, (Right, P23), (Down, P23)),
+            tile("curve-41", Curve, 0x41, (Up, P12), (Left, P12)),
+            tile("curve-42", Curve, 0x42, (Up, P23), (Left, P12)),
+            tile("curve-50", Curve, 0x50, (Down, P12), (Left, P12)),
+            tile("curve-60", Curve, 0x60, (Down, P23), (Left, P12)),
+            tile("curve-81", Curve, 0x81, (Up, P12), (Left, P23)),
+            tile("curve-82", Curve, 0x82, (Up, P23), (Left, P23)),
+            tile("curve-90", Curve, 0x90, (Down, P12), (Left, P23)),
+            tile("curve-A0", Curve, 0xA0, (Down, P23), (Left, P23)),
+
+            // Sharp tiles (16)
+            tile("sharp-05", Sharp, 0x05, (Up, P12), (Right, P12)),
+            tile("sharp-06", Sharp, 0x06, (Up, P23), (Right, P12)),
+            tile("sharp-09", Sharp, 0x09, (Up, P12), (Right, P23)),
+            tile("sharp-0A", Sharp, 0x0A, (Up, P23), (Right, P23)),
+            tile("sharp-14", Sharp, 0x14, (Right, P12), (Down, P12)),
+            tile("sharp-18", Sharp, 0x18, (Right, P23), (Down, P12)),
+            tile("sharp-24", Sharp, 0x24, (Right, P12), (Down, P23)),
+            tile("sharp-28", Sharp, 0x28, (Right, P23), (Down, P23)),
+            tile("sharp-41", Sharp, 0x41, (Up, P12), (Left, P12)),
+            tile("sharp-42", Sharp, 0x42, (Up, P23), (Left, P12)),
+            tile("sharp-50", Sharp, 0x50, (Down, P12), (Left, P12)),
+            tile("sharp-60", Sharp, 0x60, (Down, P23), (Left, P12)),
+            tile("sharp-81", Sharp, 0x81, (Up, P12), (Left, P23)),
+            tile("sharp-82", Sharp, 0x82, (Up, P23), (Left, P23)),
+            tile("sharp-90", Sharp, 0x90, (Down, P12), (Left, P23)),
+            tile("sharp-A0", Sharp, 0xA0, (Down, P23), (Left, P23)),
+        ];
+        let tiles = get_all_tiles();
+        assert_eq!(tiles[..32], expected[..]);
+        // The remaining corners all use the third lane
+        assert!(tiles[32..]
+            .iter()
+            .filter(|tile| matches!(tile.variant, Curve | Sharp))
+            .all(|tile| tile.connections.iter().any(|&(_, ports)| ports == P34)));
+    }
+
+    #[test]
+    fn test_resolve_turn_matches_path_tiling() {
+        // The middle cell of an L-shaped path
+        let path = [Point::new(1, 0), Point::new(0, 0), Point::new(0, 1)];
+        let grid_size = GridSize { rows: 2, cols: 2 };
+        let grid = path_to_tiles(&path, grid_size, TileOptions::default()).grid;
+        let corner = grid[0][0].as_ref().unwrap();
+        let turn = resolve_turn_internal("up", "right", Some("23"), false).unwrap();
+        assert_eq!(turn.tile_id, corner.tile_id);
+        assert_eq!((turn.entry_port.as_str(), turn.exit_port.as_str()), ("23", "23"));
+
+        let inner = resolve_turn_internal("up", "right", Some("12"), false).unwrap();
+        assert_eq!(inner.entry_port, "12");
+        assert!(resolve_turn_internal("up", "down", None, false).is_none());
+        assert!(resolve_turn_internal("up", "sideways", None, false).is_none());
+        assert!(resolve_turn_internal("up", "right", Some("99"), false).is_none());
+    }
+
+    #[test]
+    fn test_paths_to_tiles_two_roads() {
+        let grid_size = GridSize { rows: 2, cols: 3 };
+        let red = vec![Point::new(0, 0), Point::new(0, 1), Point::new(0, 2)];
+        let blue = vec![Point::new(1, 2), Point::new(1, 1), Point::new(1, 0)];
+        let roads = [red.clone(), blue.clone()];
+        let result = paths_to_tiles(&roads, grid_size, TileOptions::default());
+        assert!(result.valid);
+        assert!(result.conflict.is_none());
+        for (road_id, path) in [&red, &blue].into_iter().enumerate() {
+            let single = path_to_tiles(path, grid_size, TileOptions::default());
+            for p in path {
+                let cell = result.grid[p.row as usize][p.col as usize].clone().unwrap();
+                assert_eq!(cell.road_id, road_id);
+                let expected = single.grid[p.row as usize][p.col as usize].clone().unwrap();
+                assert_eq!(CellData { road_id: 0, ..cell }, expected);
+            }
+        }
+
+        let crossing = vec![Point::new(1, 1), Point::new(0, 1)];
+        let result = paths_to_tiles(&[red, blue, crossing], grid_size, TileOptions::default());
+        assert!(!result.valid);
+        let conflict = result.conflict.unwrap();
+        assert_eq!((conflict.row, conflict.col, conflict.road_ids), (1, 1, [1, 2]));
+        assert!(result.grid.iter().flatten().all(Option::is_none));
+    }
+
+    #[test]
+    fn test_unsolvable_pairs_are_memoized() {
+        let grid_size = GridSize { rows: 3, cols: 6 };
+        let mut pairs = vec![];
+        for a in 0..18 {
+            for b in a + 1..18 {
+                let (start, end) = (Point::new(a / 6, a % 6), Point::new(b / 6, b % 6));
+                if PathState::new(grid_size, 0).parity_allows_path(start, end) {
+                    pairs.push(EndpointPair { start, end });
+                }
+            }
+        }
+        let first = solve_road_paths_batch(&pairs, grid_size, 10_000_000, true);
+        let second = solve_road_paths_batch(&pairs, grid_size, 10_000_000, true);
+        let unsolvable: Vec<usize> = (0..pairs.len()).filter(|&i| !first[i].found).collect();
+        assert!(!unsolvable.is_empty());
+        for (a, b) in first.iter().zip(&second) {
+            assert_eq!(a.found, b.found);
+            assert_eq!(a.path, b.path);
+        }
+        for &i in &unsolvable {
+            assert!(first[i].iterations > 0);
+            assert_eq!(second[i].iterations, 0);
+            assert!(second[i].reason.as_ref().unwrap().contains("already proven"));
+        }
+
+        // Both orders share the entry; unmemoized searches ignore it
+        let EndpointPair { start, end } = pairs[unsolvable[0]];
+        let search = |memoize_unsolvable| {
+            let options = SearchOptions {
+                memoize_unsolvable,
+                ..Default::default()
+            };
+            solve_road_path(end, start, grid_size, 10_000_000, options).iterations
+        };
+        assert_eq!(search(true), 0);
+        assert!(search(false) > 0);
+
+        clear_solvability_cache();
+        assert!(search(true) > 0);
+    }
+
+    #[test]
+    fn test_grid_svg_path() {
+        let path = [Point::new(0, 0), Point::new(0, 1), Point::new(1, 1), Point::new(1, 0)];
+        let grid_size = GridSize { rows: 2, cols: 2 };
+        let curve = path_to_tiles(&path, grid_size, TileOptions::default()).grid;
+        assert_eq!(
+            grid_svg_path(&curve, grid_size, 10.0).as_deref(),
+            Some(r#"<path d="M 5 5 L 10 5 Q 15 5 15 10 Q 15 15 10 15 L 5 15" fill="none"/>"#)
+        );
+
+        let options = TileOptions {
+            variant_preference: VariantPreference::PreferSharp,
+            ..Default::default()
+        };
+        let sharp = path_to_tiles(&path, grid_size, options).grid;
+        assert_eq!(
+            grid_svg_path(&sharp, grid_size, 10.0).as_deref(),
+            Some(r#"<path d="M 5 5 L 10 5 L 15 5 L 15 10 L 15 15 L 10 15 L 5 15" fill="none"/>"#)
+        );
+
+        // Two roads become two subpaths
+        let roads = [
+            vec![Point::new(0, 0), Point::new(0, 1)],
+            vec![Point::new(1, 0), Point::new(1, 1)],
+        ];
+        let both = paths_to_tiles(&roads, grid_size, TileOptions::default()).grid;
+        let svg = grid_svg_path(&both, grid_size, 2.0).unwrap();
+        assert_eq!(svg, r#"<path d="M 1 1 L 2 1 L 3 1 M 1 3 L 2 3 L 3 3" fill="none"/>"#);
+
+        // Corners are told apart by variant, not by id
+        let renamed: Vec<TileInfo> = get_all_tiles()
+            .iter()
+            .map(|tile| TileInfo {
+                id: format!("alt-{}", tile.id),
+                ..tile.to_info()
+            })
+            .collect();
+        install_custom_tiles(&renamed).unwrap();
+        let custom = path_to_tiles(&path, grid_size, TileOptions::default()).grid;
+        let custom_svg = grid_svg_path(&custom, grid_size, 10.0);
+        reset_tile_set();
+        assert_eq!(custom_svg, grid_svg_path(&curve, grid_size, 10.0));
+
+        assert!(grid_svg_path(&curve, grid_size, 0.0).is_none());
+        assert!(grid_svg_path(&curve, GridSize { rows: 3, cols: 2 }, 10.0).is_none());
+        assert!(grid_svg_path(&vec![vec![None; 2]; 2], grid_size, 10.0).is_none());
+    }
+
+    #[test]
+    fn test_rotate_road_grid() {
+        let path = [
+            Point::new(0, 0),
+            Point::new(0, 1),
+            Point::new(0, 2),
+            Point::new(1, 2),
+            Point::new(1, 1),
+        ];
+        let grid_size = GridSize { rows: 2, cols: 3 };
+        for variant_preference in [VariantPreference::PreferCurve, VariantPreference::PreferSharp] {
+            let options = TileOptions {
+                variant_preference,
+                ..Default::default()
+            };
+            let original = path_to_tiles(&path, grid_size, options).grid;
+
+            let turned = rotate_road_grid(&original, grid_size, 1).unwrap();
+            let turned_size = GridSize { rows: 3, cols: 2 };
+            assert!(turned.valid);
+            assert!(validate_grid(&turned.grid, turned_size).is_empty());
+            for (i, &p) in path.iter().enumerate() {
+                let before = original[p.row as usize][p.col as usize].as_ref().unwrap();
+                let q = GridTransform::Rot90.apply(p, grid_size);
+                let after = turned.grid[q.row as usize][q.col as usize].as_ref().unwrap();
+                assert_eq!(after.path_index, i);
+                let variant = find_tile(&after.tile_id).map(|tile| tile.variant);
+                assert_eq!(variant, find_tile(&before.tile_id).map(|tile| tile.variant));
+                // Straights repeat every half turn
+                let period = if variant == Some(TileVariant::Straight) { 180 } else { 360 };
+                assert_eq!(after.rotation % period, (before.rotation + 90) % period);
+            }
+
+            // Four quarter turns, or one back, restore the original tiles
+            let ids = |grid: &[Vec<Option<CellData>>]| -> Vec<Option<String>> {
+                grid.iter().flatten().map(|c| c.as_ref().map(|c| c.tile_id.clone())).collect()
+            };
+            let full = rotate_road_grid(&original, grid_size, 4).unwrap();
+            assert_eq!(ids(&full.grid), ids(&original));
+            let back = rotate_road_grid(&turned.grid, turned_size, -1).unwrap();
+            assert_eq!(ids(&back.grid), ids(&original));
+        }
+
+        let mut unknown = path_to_tiles(&path, grid_size, TileOptions::default()).grid;
+        unknown[0][1].as_mut().unwrap().tile_id = "mystery".to_string();
+        assert!(rotate_road_grid(&unknown, grid_size, 1).is_none());
+
+        // Rejected before any table is allocated from the sizes
+        for (rows, cols) in [(-2, 3), (2, -3), (0, 3)] {
+            let size = GridSize { rows, cols };
+            assert!(rotate_road_grid(&[], size, 0).is_none());
+            assert!(rotate_road_grid(&[], size, 1).is_none());
+        }
+    }
+
+    #[test]
+    fn test_straight_through_and_turn_tiles() {
+        let tiles = get_all_tiles();
+        let variant = |id: &str| find_tile(id).unwrap().variant;
+        let find = |entry, exit, required, lane_change| {
+            find_tile_with_port_constraint(&tiles, entry, exit, required, lane_change)
+        };
+
+        // Heading right through a cell: in from the left, out to the right
+        let required = Some(PortSet::P23);
+        let (id, ep, xp) = find(Direction::Right, Direction::Right, required, false).unwrap();
+        assert_eq!((id.as_str(), ep, xp), ("straight-h-88", PortSet::P23, PortSet::P23));
+        assert_eq!(variant(&id), TileVariant::Straight);
+
+        // Heading down into the cell, then right: the Up→Right curve
+        let (id, ep, xp) = find(Direction::Down, Direction::Right, required, false).unwrap();
+        assert_eq!(variant(&id), TileVariant::Curve);
+        let tile = find_tile(&id).unwrap();
+        assert_eq!(tile.get_connection(Direction::Up), Some(ep));
+        assert_eq!(tile.get_connection(Direction::Right), Some(xp));
+        assert_eq!((ep, xp), (PortSet::P23, PortSet::P23));
+
+        // Doubling back has no tile at all
+        assert_eq!(find(Direction::Right, Direction::Left, None, true), None);
+    }
+
+    #[test]
+    fn test_type_examples_fill_optional_fields() {
+        let examples = TypeExamples::build();
+
+        let result = &examples.path_result;
+        assert!(result.depth_counts.is_some() && result.reason.is_some());
+        assert!(result.best_partial.is_some() && result.visited_grid.is_some());
+        let error = result.input_error.as_ref().unwrap();
+        assert!(error.point.is_some() && error.cell.is_some());
+
+        let cell = &examples.cell_data;
+        assert_eq!((cell.tile_id.as_str(), cell.connections.len()), ("curve-A0", 2));
+        assert!(cell.entry_dir.is_some() && cell.exit_dir.is_some());
+
+        let road = &examples.road_grid_result;
+        assert!(road.valid);
+        assert_eq!(road.schema_version, GRID_SCHEMA_VERSION);
+        assert!(road.failure.as_ref().unwrap().required_entry_port.is_some());
+        // Both occupied and empty cells appear
+        assert!(road.grid[0][1].is_some() && road.grid[0][2].is_none());
+    }
+
+    #[test]
+    fn test_locked_tiles() {
+        let grid_size = GridSize { rows: 3, cols: 3 };
+        let (start, end) = (Point::new(0, 0), Point::new(2, 2));
+        let lock = |row, col, tile_id: &str| TileLock { row, col, tile_id: tile_id.to_string() };
+        let try_solve = |locks: &[TileLock]| solve_locked_path(start, end, grid_size, 0, locks);
+        let solve = |locks: &[TileLock]| try_solve(locks).unwrap();
+        let tile_at = |result: &LockedPathResult, row: usize, col: usize| {
+            let grid = &result.grid.as_ref().unwrap().grid;
+            grid[row][col].as_ref().unwrap().tile_id.clone()
+        };
+
+        // The default search snakes through the middle row; a vertical
+        // straight in the center forces the column-first snake instead
+        let free = solve(&[]);
+        assert_eq!(tile_at(&free, 1, 1), "straight-h-88");
+        let vertical = solve(&[lock(1, 1, "straight-v-22")]);
+        assert!(vertical.result.found);
+        assert_eq!(vertical.result.path[1], Point::new(1, 0));
+        assert_eq!(tile_at(&vertical, 1, 1), "straight-v-22");
+
+        // A sharp lock keeps the route and replaces the curve the tiler picks
+        let sharp = solve(&[lock(0, 2, "sharp-A0")]);
+        assert_eq!(sharp.result.path, free.result.path);
+        assert_eq!(tile_at(&sharp, 0, 2), "sharp-A0");
+        let grid = sharp.grid.unwrap();
+        assert!(grid.valid && validate_grid(&grid.grid, grid_size).is_empty());
+
+        // The right sides but the other lane: the search runs dry
+        let lane = solve(&[lock(1, 1, "straight-h-84")]);
+        assert!(!lane.result.found && lane.result.reason.is_none());
+        assert!(lane.grid.is_none());
+
+        let on_start = solve(&[lock(0, 0, "straight-h-88")]);
+        assert!(on_start.result.reason.unwrap().contains("endpoint"));
+        assert!(try_solve(&[lock(1, 1, "mystery")]).is_none());
+        assert!(try_solve(&[lock(3, 1, "straight-h-88")]).is_none());
+    }
+
+    #[test]
+    fn test_opposite_direction() {
+        assert_eq!(opposite_direction("up").as_deref(), Some("down"));
+        assert_eq!(opposite_direction("down-right").as_deref(), Some("up-left"));
+        for dir in Direction::with_diagonals() {
+            let opposite = opposite_direction(dir.to_string()).unwrap();
+            assert_eq!(opposite_direction(&opposite).as_deref(), Some(dir.to_string()));
+            let (dr, dc) = Direction::parse(&opposite).unwrap().delta();
+            assert_eq!(dir.delta(), (-dr, -dc));
+        }
+        assert_eq!(opposite_direction("north"), None);
+        assert_eq!(opposite_direction("Up"), None);
+    }
+
+    #[test]
+    fn test_shortest_path() {
+        let grid_size = GridSize { rows: 3, cols: 4 };
+        let state = PathState::new(grid_size, 0);
+        let straight = shortest_path_between(&state, Point::new(0, 0), Point::new(2, 3));
+        assert!(straight.found);
+        assert_eq!(straight.length, 5);
+        assert_eq!(straight.path.len(), 6);
+        assert!(check_path(&straight.path, grid_size).valid);
+
+        // A wall with a gap at the bottom forces a detour
+        let options = SearchOptions {
+            blocked: vec![Point::new(0, 1), Point::new(1, 1)],
+            ..Default::default()
+        };
+        let walled = PathState::with_options(grid_size, 0, options);
+        let detour = shortest_path_between(&walled, Point::new(0, 0), Point::new(0, 2));
+        assert_eq!(detour.length, 6);
+        assert_eq!(detour.path[3], Point::new(2, 1));
+
+        let same = shortest_path_between(&state, Point::new(1, 1), Point::new(1, 1));
+        assert_eq!((same.found, same.path.len(), same.length), (true, 1, 0));
+
+        // Closing the gap cuts the end off, and blocked endpoints never match
+        let options = SearchOptions {
+            blocked: vec![Point::new(0, 1), Point::new(1, 1), Point::new(2, 1)],
+            ..Default::default()
+        };
+        let sealed = PathState::with_options(grid_size, 0, options);
+        assert!(!shortest_path_between(&sealed, Point::new(0, 0), Point::new(0, 2)).found);
+        assert!(!shortest_path_between(&sealed, Point::new(0, 1), Point::new(0, 2)).found);
+        assert!(!shortest_path_between(&state, Point::new(0, 0), Point::new(3, 0)).found);
+    }
+
+    #[test]
+    fn test_backtracks_are_counted() {
+        let grid_size = GridSize { rows: 4, cols: 4 };
+        let (start, end) = (Point::new(0, 0), Point::new(0, 3));
+        let solve = |pruning, max_iterations| {
+            let options = SearchOptions { pruning, ..Default::default() };
+            solve_road_path(start, end, grid_size, max_iterations, options)
+        };
+
+        // Every cell entered either stays on the path or is backtracked
+        let plain = solve(false, 0);
+        let pruned = solve(true, 0);
+        for result in [&plain, &pruned] {
+            assert!(result.found);
+            assert_eq!(result.iterations as usize, result.path.len() + result.backtracks as usize);
+        }
+        assert!(pruned.backtracks < plain.backtracks);
+
+        // An exhausted search takes back everything it entered
+        let dead_end = solve_road_path(
+            Point::new(1, 0),
+            Point::new(1, 1),
+            GridSize { rows: 3, cols: 4 },
+            0,
+            SearchOptions::default(),
+        );
+        assert!(!dead_end.found && dead_end.reason.is_none());
+        assert_eq!(dead_end.backtracks, dead_end.iterations);
+
+        // Unwinding a search that ran out of budget doesn't count
+        let suspended = solve(false, 30);
+        assert!(!suspended.found);
+        assert!(suspended.backtracks < suspended.iterations);
+    }
+
+    #[test]
+    fn test_custom_tile_set() {
+        let builtin: Vec<TileInfo> = get_all_tiles().iter().map(TileDefinition::to_info).collect();
+        let renamed: Vec<TileInfo> = builtin
+            .iter()
+            .map(|info| TileInfo {
+                id: format!("alt-{}", info.id),
+                base_id: String::new(),
+                ..info.clone()
+            })
+            .collect();
+        install_custom_tiles(&renamed).unwrap();
+        assert!(find_tile("curve-A0").is_none());
+        // Left-out orientation is derived as for the built-in tiles
+        let straight = find_tile("alt-straight-h-88").unwrap();
+        assert_eq!((straight.rotation, straight.base_id.as_str()), (90, "straight-v-22"));
+
+        let path = [Point::new(0, 0), Point::new(0, 1), Point::new(1, 1), Point::new(1, 0)];
+        let grid_size = GridSize { rows: 2, cols: 2 };
+        let tiled = path_to_tiles(&path, grid_size, TileOptions::default());
+        assert!(tiled.valid);
+        for cell in [&tiled.grid[0][1], &tiled.grid[1][1]] {
+            assert!(cell.as_ref().unwrap().tile_id.starts_with("alt-"));
         }
+
+        // Rejected sets leave the active one in place
+        let mut bad_mask = renamed.clone();
+        bad_mask[0].mask ^= 0x01;
+        assert!(install_custom_tiles(&bad_mask).unwrap_err().contains(&bad_mask[0].id));
+        let mut duplicate = renamed.clone();
+        duplicate.push(renamed[0].clone());
+        assert!(install_custom_tiles(&duplicate).is_err());
+        assert!(install_custom_tiles(&[]).is_err());
+        assert!(find_tile("alt-curve-A0").is_some());
+
+        // Replaced sets are freed
+        let installed = Rc::downgrade(&tile_table());
+        reset_tile_set();
+        assert!(installed.upgrade().is_none());
+        assert!(find_tile("alt-curve-A0").is_none());
+        assert_eq!(get_all_tiles().len(), builtin.len());
     }
 
-    state.unvisit(current);
-    false
-}
+    #[test]
+    fn test_forced_move_count() {
+        // A 1-wide corridor leaves no choice at any step
+        let run = |end: Point, rows, cols| {
+            forced_moves(Point::new(0, 0), end, GridSize { rows, cols }, 1000)
+        };
+        let corridor = run(Point::new(0, 4), 1, 5);
+        assert!(corridor.result.found);
+        assert_eq!(corridor.forced, vec![true; 4]);
+        assert_eq!(corridor.forced_moves, 4);
 
-fn count_unvisited_neighbors(state: &PathState, p: Point) -> usize {
-    Direction::all()
-        .iter()
-        .filter(|&&dir| {
-            let (dr, dc) = dir.delta();
-            let next = Point::new(p.row + dr, p.col + dc);
-            state.is_valid(next) && !state.is_visited(next)
-        })
-        .count()
-}
+        // Leaving a corner of a 2x2 grid there are two ways to go; after that,
+        // one
+        let square = run(Point::new(1, 0), 2, 2);
+        assert_eq!(square.forced, vec![false, true, true]);
 
-// ============================================================================
-// Grid to Tiles Conversion
-// ============================================================================
+        let none = run(Point::new(0, 1), 3, 3);
+        assert!(!none.result.found);
+        assert!(none.forced.is_empty());
+        assert_eq!(none.forced_moves, 0);
+    }
 
-/// Convert a path to a grid with tile assignments
-/// Uses port propagation to ensure smooth connections between tiles
-fn path_to_tiles(path: &[Point], grid_size: GridSize) -> RoadGridResult {
-    if path.len() < 2 {
-        return RoadGridResult {
-            grid: vec![vec![None; grid_size.cols as usize]; grid_size.rows as usize],
-            valid: false,
-        };
+    #[test]
+    fn test_grid_placements() {
+        let path = [Point::new(0, 1), Point::new(0, 0), Point::new(1, 0), Point::new(1, 1)];
+        let grid_size = GridSize { rows: 2, cols: 3 };
+        let tiled = path_to_tiles(&path, grid_size, TileOptions::default());
+        let placements = grid_placements(&tiled.grid, grid_size).unwrap();
+
+        // Empty cells are skipped and the order follows the path, not the grid
+        let cells: Vec<Point> = placements.iter().map(|p| Point::new(p.row, p.col)).collect();
+        assert_eq!(cells, path);
+        assert_eq!(placements[0].tile_id, "start");
+        assert_eq!(placements[3].tile_id, "goal");
+        let corner = tiled.grid[0][0].as_ref().unwrap();
+        assert_eq!(placements[1].tile_id, corner.tile_id);
+        assert_eq!(placements[1].rotation, corner.rotation);
+
+        assert!(grid_placements(&tiled.grid, GridSize { rows: 2, cols: 1 }).is_none());
     }
 
-    let tiles = get_all_tiles();
-    let mut grid: Vec<Vec<Option<CellData>>> =
-        vec![vec![None; grid_size.cols as usize]; grid_size.rows as usize];
+    #[test]
+    fn test_enumerating_searches_do_not_recurse_per_cell() {
+        // One frame per cell would overflow the test thread's stack here
+        let long = GridSize { rows: 1, cols: 50_000 };
+        let counted = count_paths(Point::new(0, 0), Point::new(0, 49_999), long, 1_000_000);
+        assert_eq!((counted.count, counted.capped), (1, false));
 
-    // Track the required entry port for the next tile (propagated from previous tile's exit)
-    let mut required_entry_port: Option<PortSet> = None;
+        let corridor = GridSize { rows: 1, cols: 1000 };
+        let end = Point::new(0, 999);
+        let smoothest = solve_smoothest_path(Point::new(0, 0), end, corridor, 100_000, vec![]);
+        assert!(smoothest.found && smoothest.optimal);
+        assert_eq!((smoothest.path.len(), smoothest.turns), (1000, 0));
+    }
 
-    // Process each cell in the path
-    for i in 0..path.len() {
-        let current = path[i];
+    #[test]
+    fn test_tiles_with_direction() {
+        let up = tiles_toward(Direction::Up);
+        assert!(up.iter().all(|tile| tile.has_direction(Direction::Up)));
+        // Half the corners of each kind, the vertical straights, the
+        // diagonals with their orthogonal end on top, and the junctions open
+        // upward
+        let count = |variant| up.iter().filter(|tile| tile.variant == variant).count();
+        assert_eq!((count(TileVariant::Curve), count(TileVariant::Sharp)), (18, 18));
+        assert_eq!((count(TileVariant::Straight), count(TileVariant::Diagonal)), (9, 8));
+        assert_eq!((count(TileVariant::Tee), count(TileVariant::Cross)), (6, 2));
+        assert_eq!(up.len(), 61);
+        for dir in Direction::all() {
+            assert_eq!(tiles_toward(dir).len(), 61);
+        }
+    }
 
-        // Determine entry and exit directions
-        let entry_dir = if i > 0 {
-            let prev = path[i - 1];
-            Some(get_direction(prev, current))
-        } else {
-            None
+    #[test]
+    fn test_column_major_grid_order() {
+        let path = [Point::new(0, 0), Point::new(0, 1), Point::new(0, 2), Point::new(1, 2)];
+        let grid_size = GridSize { rows: 2, cols: 3 };
+        let tiled = path_to_tiles(&path, grid_size, TileOptions::default());
+
+        assert_eq!(GridOrder::parse("row_major"), Some(GridOrder::RowMajor));
+        assert_eq!(GridOrder::parse("columns"), None);
+        assert_eq!(GridOrder::RowMajor.arrange(tiled.grid.clone()), tiled.grid);
+
+        let columns = GridOrder::ColumnMajor.arrange(tiled.grid.clone());
+        assert_eq!((columns.len(), columns[0].len()), (3, 2));
+        for p in path {
+            let (row, col) = (p.row as usize, p.col as usize);
+            assert_eq!(columns[col][row], tiled.grid[row][col]);
+        }
+        assert!(columns[0][1].is_none());
+    }
+
+    #[test]
+    fn test_cut_cells() {
+        let board = |rows, cols, blocked: Vec<Point>| {
+            let options = SearchOptions { blocked, ..Default::default() };
+            find_cut_cells(&PathState::with_options(GridSize { rows, cols }, 0, options))
         };
 
-        let exit_dir = if i < path.len() - 1 {
-            let next = path[i + 1];
-            Some(get_direction(current, next))
-        } else {
-            None
+        // An open rectangle has no cut cells
+        let open = board(3, 4, vec![]);
+        assert_eq!((open.components, open.cut_cells.len(), open.ruled_out), (1, 0, false));
+
+        // A corridor: every inner cell cuts it in two, which a path can use
+        let corridor = board(1, 4, vec![]);
+        let cells: Vec<(i32, i32, usize)> =
+            corridor.cut_cells.iter().map(|c| (c.row, c.col, c.pieces)).collect();
+        assert_eq!(cells, vec![(0, 1, 2), (0, 2, 2)]);
+        assert!(!corridor.ruled_out);
+
+        // A plus shape: the center joins four arms, more than a path can pass
+        let corners = vec![Point::new(0, 0), Point::new(0, 2), Point::new(2, 0), Point::new(2, 2)];
+        let plus = board(3, 3, corners);
+        assert_eq!(plus.cut_cells, vec![CutCell { row: 1, col: 1, pieces: 4 }]);
+        assert!(plus.ruled_out);
+
+        // A wall down the middle leaves two components
+        let wall = board(3, 3, vec![Point::new(0, 1), Point::new(1, 1), Point::new(2, 1)]);
+        assert_eq!(wall.components, 2);
+        assert!(wall.ruled_out);
+
+        // Long boards don't recurse once per cell
+        let long = board(1, 100_000, vec![]);
+        assert_eq!(long.cut_cells.len(), 99_998);
+    }
+
+    #[test]
+    fn test_remaining_cells() {
+        let remaining = |path: &[Point], blocked: Vec<Point>| {
+            let options = SearchOptions { blocked, ..Default::default() };
+            let mut state = PathState::with_options(GridSize { rows: 3, cols: 3 }, 0, options);
+            remaining_after(&mut state, path)
         };
 
-        // Find matching tile with port propagation
-        let (tile_id, entry_port, exit_port) = match (entry_dir, exit_dir) {
-            (Some(entry), Some(exit)) => {
-                // Middle cell: needs entry and exit with port matching
-                let result = find_tile_with_port_constraint(&tiles, entry, exit, required_entry_port);
-                match result {
-                    Some((id, ep, xp)) => (Some(id), Some(ep), Some(xp)),
-                    None => (None, None, None),
-                }
-            }
-            (None, Some(_exit)) => {
-                // Start cell: use marker, start with P23 (outer lane)
-                (Some("start".to_string()), None, Some(PortSet::P23))
-            }
-            (Some(_entry), None) => {
-                // End cell: use marker
-                (Some("goal".to_string()), required_entry_port, None)
-            }
-            (None, None) => {
-                (None, None, None)
-            }
+        // Down the middle column and right: the left column is cut off
+        let middle = [Point::new(0, 1), Point::new(1, 1), Point::new(2, 1), Point::new(2, 2)];
+        let split = remaining(&middle, vec![]).unwrap();
+        assert_eq!(split.cells.len(), 5);
+        assert!(!split.all_reachable);
+
+        // Along the top row everything left stays reachable, obstacles aside
+        let top = [Point::new(0, 0), Point::new(0, 1), Point::new(0, 2)];
+        let open = remaining(&top, vec![Point::new(2, 2)]).unwrap();
+        let row = |row, cols: &[i32]| -> Vec<Point> {
+            cols.iter().map(|&col| Point::new(row, col)).collect()
         };
+        assert_eq!(open.cells, [row(1, &[0, 1, 2]), row(2, &[0, 1])].concat());
+        assert!(open.all_reachable);
 
-        if let Some(id) = tile_id {
-            let connections = match (entry_dir, exit_dir) {
-                (Some(entry), Some(exit)) => {
-                    vec![
-                        Connection {
-                            direction: entry.opposite().to_string().to_string(),
-                            ports: entry_port.map(|p| p.to_string()).unwrap_or("23").to_string(),
-                        },
-                        Connection {
-                            direction: exit.to_string().to_string(),
-                            ports: exit_port.map(|p| p.to_string()).unwrap_or("23").to_string(),
-                        },
-                    ]
-                }
-                (None, Some(exit)) => {
-                    vec![Connection {
-                        direction: exit.to_string().to_string(),
-                        ports: "23".to_string(),
-                    }]
-                }
-                (Some(entry), None) => {
-                    vec![Connection {
-                        direction: entry.opposite().to_string().to_string(),
-                        ports: entry_port.map(|p| p.to_string()).unwrap_or("23").to_string(),
-                    }]
-                }
-                _ => vec![],
-            };
+        assert!(remaining(&top, vec![Point::new(0, 1)]).is_none());
+        assert!(remaining(&[Point::new(0, 0), Point::new(1, 1)], vec![]).is_none());
+        assert!(remaining(&[], vec![]).is_none());
+    }
 
-            grid[current.row as usize][current.col as usize] = Some(CellData {
-                tile_id: id,
-                connections,
-                path_index: i,
-            });
-        } else {
-            // 一つでもタイルが見つからなければ無効扱い
-            return RoadGridResult {
-                grid,
-                valid: false,
-            };
+    #[test]
+    fn test_direction_and_port_set_from_str() {
+        for dir in Direction::with_diagonals() {
+            assert_eq!(dir.to_string().parse(), Ok(dir));
+            assert_eq!(format!("{}", dir), dir.to_string());
         }
+        for ports in PortSet::all() {
+            assert_eq!(ports.to_string().parse(), Ok(ports));
+        }
+        let typo = "upp".parse::<Direction>().unwrap_err();
+        assert_eq!(typo.name, "upp");
+        let message = typo.to_string();
+        assert!(message.starts_with("unknown direction \"upp\", expected one of up, right, down"));
+        assert_eq!("2-3".parse::<PortSet>().unwrap_err().expected, vec!["12", "23", "34"]);
 
-        // Propagate exit port to next tile's required entry port
-        required_entry_port = exit_port;
+        let conn = |direction: &str, ports: &str| Connection {
+            direction: direction.to_string(),
+            ports: ports.to_string(),
+        };
+        assert_eq!(conn("left", "34").parse(), Ok((Direction::Left, PortSet::P34)));
+        assert_eq!(conn("lft", "34").parse().unwrap_err().kind, "direction");
+
+        assert_eq!(ParsedName::from_result("up".parse::<Direction>()).value.as_deref(), Some("up"));
+        assert!(ParsedName::from_result("x".parse::<PortSet>()).error.is_some());
+
+        // A typo'd port set in a loaded grid is reported as such
+        let mut grid = path_to_tiles(
+            &[Point::new(0, 0), Point::new(0, 1)],
+            GridSize { rows: 1, cols: 2 },
+            TileOptions::default(),
+        )
+        .grid;
+        grid[0][1].as_mut().unwrap().connections[0].ports = "32".to_string();
+        let kinds: Vec<String> = validate_grid(&grid, GridSize { rows: 1, cols: 2 })
+            .into_iter()
+            .map(|m| m.kind)
+            .collect();
+        assert_eq!(kinds, vec!["port_mismatch", "unknown_ports"]);
     }
 
-    RoadGridResult { grid, valid: true }
-}
+    #[test]
+    fn test_batch_stats() {
+        let run = |iterations: u32, found: bool| PathResult {
+            found,
+            iterations,
+            ..PathResult::rejected(String::new())
+        };
+        let results = [run(10, true), run(999, true), run(1_000, false), run(40_000, true)];
+        let stats = batch_stats(&results);
+        assert_eq!((stats.count, stats.found), (4, 3));
+        assert_eq!(stats.found_fraction, 0.75);
+        assert_eq!(stats.mean_iterations, 10_502.25);
+        assert_eq!(stats.median_iterations, 999.5);
+        let counts: Vec<(&str, usize)> =
+            stats.histogram.iter().map(|b| (b.name.as_str(), b.count)).collect();
+        let expected = [("<1k", 2), ("1k-10k", 1), ("10k-100k", 1), ("100k-1M", 0), ("1M+", 0)];
+        assert_eq!(counts, expected);
+        assert_eq!((stats.histogram[1].min, stats.histogram[1].max), (1_000, Some(10_000)));
+        assert_eq!(stats.histogram[4].max, None);
 
-/// Find tile with port constraint for smooth connections
-/// Returns (tile_id, entry_port, exit_port)
-fn find_tile_with_port_constraint(
-    tiles: &[TileDefinition],
-    entry: Direction,
-    exit: Direction,
-    required_entry_port: Option<PortSet>,
-) -> Option<(String, PortSet, PortSet)> {
-    let entry_from = entry.opposite();
+        let odd = batch_stats(&results[1..]);
+        assert_eq!(odd.median_iterations, 1_000.0);
+        let empty = batch_stats(&[]);
+        assert_eq!(empty.count, 0);
+        assert_eq!((empty.found_fraction, empty.mean_iterations), (0.0, 0.0));
+        assert_eq!(empty.median_iterations, 0.0);
+        assert!(empty.histogram.iter().all(|b| b.count == 0));
+    }
 
-    // 1) 「入るポート＝出るポート」で必ず同一レーンを維持するパターンのみ採用
-    for tile in tiles {
-        if tile.has_direction(entry_from) && tile.has_direction(exit) {
-            if let (Some(ep), Some(xp)) = (tile.get_connection(entry_from), tile.get_connection(exit)) {
-                if ep == xp && required_entry_port.map_or(true, |req| ep == req) {
-                    return Some((tile.id.to_string(), ep, xp));
-                }
-            }
+    #[test]
+    fn test_benchmark_entries() {
+        // Fake clock advancing 1 ms per reading
+        let mut now = 0.0;
+        let mut clock: ClockFn = Box::new(move || {
+            now += 1.0;
+            now
+        });
+        let sizes = [(3, 3), (4, 4), (1, 4)].map(|(rows, cols)| GridSize { rows, cols });
+        let entries = run_benchmark(&sizes, 100_000, &mut clock);
+
+        let ends: Vec<Point> = entries.iter().map(|e| e.end).collect();
+        assert_eq!(ends, vec![Point::new(2, 2), Point::new(3, 0), Point::new(0, 3)]);
+        for (entry, size) in entries.iter().zip(sizes) {
+            assert_eq!((entry.rows, entry.cols), (size.rows, size.cols));
+            assert!(entry.found && entry.iterations > 0);
+            assert_eq!(entry.elapsed_ms, 1.0);
         }
+
+        let invalid = run_benchmark(&[GridSize { rows: 0, cols: 3 }], 1000, &mut clock);
+        assert!(!invalid[0].found);
     }
 
-    None
-}
+    #[test]
+    fn test_thick_path() {
+        let solve = |end: Point, rows, cols| {
+            solve_thick_path(Point::new(0, 0), end, GridSize { rows, cols }, 100_000)
+        };
 
-fn get_direction(from: Point, to: Point) -> Direction {
-    let dr = to.row - from.row;
-    let dc = to.col - from.col;
+        let thick = solve(Point::new(2, 0), 4, 4);
+        assert!(thick.result.found);
+        assert_eq!(thick.block_grid, GridSize { rows: 2, cols: 2 });
+        let points = |cells: &[(i32, i32)]| -> Vec<Point> {
+            cells.iter().map(|&(r, c)| Point::new(r, c)).collect()
+        };
+        assert_eq!(thick.block_path, points(&[(0, 0), (0, 1), (1, 1), (1, 0)]));
+        assert_eq!(thick.result.path, points(&[(0, 0), (0, 2), (2, 2), (2, 0)]));
 
-    match (dr, dc) {
-        (-1, 0) => Direction::Up,
-        (1, 0) => Direction::Down,
-        (0, -1) => Direction::Left,
-        (0, 1) => Direction::Right,
-        _ => panic!("Invalid direction: from {:?} to {:?}", from, to),
+        // The footprints don't fit: odd dimensions or an unaligned endpoint
+        let odd = solve(Point::new(2, 0), 4, 5);
+        assert!(!odd.result.found && odd.result.reason.unwrap().contains("even dimensions"));
+        let unaligned = solve(Point::new(3, 0), 4, 4);
+        assert!(unaligned.result.reason.unwrap().contains("top-left cell"));
+        // Fits, but the block grid's parity rules the route out
+        let parity = solve(Point::new(2, 2), 4, 4);
+        assert!(!parity.result.found && parity.result.reason.is_some());
+        assert!(solve(Point::new(8, 0), 4, 4).result.input_error.is_some());
     }
-}
 
-// ============================================================================
-// WASM Exports
-// ============================================================================
+    #[test]
+    fn test_diff_grids() {
+        let path = [Point::new(0, 0), Point::new(0, 1), Point::new(1, 1)];
+        let grid_size = GridSize { rows: 2, cols: 2 };
+        let old = path_to_tiles(&path, grid_size, TileOptions::default()).grid;
+        assert!(diff_grids_internal(&old, &old).is_empty());
 
-/// Find a path from start to end that visits all cells
-#[wasm_bindgen]
-pub fn find_road_path(
-    start_row: i32,
-    start_col: i32,
-    end_row: i32,
-    end_col: i32,
-    grid_rows: i32,
-    grid_cols: i32,
-    max_iterations: u32,
-) -> JsValue {
-    let start = Point::new(start_row, start_col);
-    let end = Point::new(end_row, end_col);
-    let grid_size = GridSize {
-        rows: grid_rows,
-        cols: grid_cols,
-    };
+        // Same connections listed in the other order, and a new path index:
+        // still the same tile
+        let mut reordered = old.clone();
+        let cell = reordered[0][1].as_mut().unwrap();
+        cell.connections.reverse();
+        cell.path_index = 7;
+        assert!(diff_grids_internal(&old, &reordered).is_empty());
 
-    let mut state = PathState::new(grid_size, max_iterations);
-    let found = find_path_internal(&mut state, start, end);
+        let mut new = old.clone();
+        new[1][0] = new[1][1].take();
+        let changes = diff_grids_internal(&old, &new);
+        assert_eq!(changes.len(), 2);
+        assert_eq!((changes[0].row, changes[0].col), (1, 0));
+        assert!(changes[0].old_tile.is_none());
+        assert_eq!(changes[0].new_tile, old[1][1]);
+        assert_eq!((changes[1].row, changes[1].col), (1, 1));
+        assert_eq!((&changes[1].old_tile, &changes[1].new_tile), (&old[1][1], &None));
 
-    let result = PathResult {
-        found,
-        path: if found { state.path } else { vec![] },
-        iterations: state.iterations,
-    };
+        // A single changed cell
+        let mut one = old.clone();
+        one[0][0].as_mut().unwrap().tile_id = "goal".to_string();
+        let changes = diff_grids_internal(&old, &one);
+        assert_eq!(changes.len(), 1);
+        assert_eq!((changes[0].row, changes[0].col), (0, 0));
+        assert_eq!(changes[0].new_tile.as_ref().unwrap().tile_id, "goal");
+    }
 
-    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
-}
+    #[test]
+    fn test_path_styles() {
+        let grid_size = GridSize { rows: 5, cols: 5 };
+        let solve = |end: Point, style| {
+            let options = SearchOptions {
+                style,
+                ..Default::default()
+            };
+            solve_road_path(Point::new(0, 0), end, grid_size, 100_000, options)
+        };
 
-/// Convert a path to a road grid with tile assignments
-#[wasm_bindgen]
-pub fn path_to_road_grid(path_js: JsValue, grid_rows: i32, grid_cols: i32) -> JsValue {
-    let path: Vec<Point> = match serde_wasm_bindgen::from_value(path_js) {
-        Ok(p) => p,
-        Err(_) => return JsValue::NULL,
-    };
+        // Snake keeps to the rows; spiral covers the border ring, then the
+        // next one, before reaching the center
+        let snake = solve(Point::new(4, 4), PathStyle::Snake);
+        assert_eq!(snake.path, build_snake_path(Point::new(0, 0), grid_size, false).unwrap());
+        let spiral = solve(Point::new(2, 2), PathStyle::Spiral);
+        assert!(spiral.found);
+        let ring = |p: &Point| p.row.min(p.col).min(4 - p.row).min(4 - p.col);
+        assert!(spiral.path.windows(2).all(|w| ring(&w[0]) <= ring(&w[1])));
+        assert_ne!(spiral.path, solve(Point::new(2, 2), PathStyle::Snake).path);
+        assert_ne!(snake.path, solve(Point::new(4, 4), PathStyle::Spiral).path);
 
-    let grid_size = GridSize {
-        rows: grid_rows,
-        cols: grid_cols,
-    };
+        // Only the order changes, never which ends can be reached
+        for b in 1..25 {
+            let end = Point::new(b / 5, b % 5);
+            let found = solve(end, PathStyle::Default).found;
+            assert_eq!(solve(end, PathStyle::Spiral).found, found, "{:?}", end);
+            assert_eq!(solve(end, PathStyle::Snake).found, found, "{:?}", end);
+        }
+        assert_eq!(PathStyle::parse("spiral"), Some(PathStyle::Spiral));
+        assert_eq!(PathStyle::parse("zigzag"), None);
+    }
 
-    let result = path_to_tiles(&path, grid_size);
-    serde_wasm_bindgen::to_value(&result).unwrap_or(JsValue::NULL)
-}
+    #[test]
+    fn test_reverse_segment() {
+        let grid_size = GridSize { rows: 4, cols: 4 };
+        let paths = solve_road_paths(
+            Point::new(0, 0),
+            Point::new(0, 3),
+            grid_size,
+            1_000_000,
+            20,
+            SearchOptions::default(),
+        )
+        .paths;
 
-/// Get parity of a cell (0 or 1 based on row+col)
-#[wasm_bindgen]
-pub fn cell_parity(row: i32, col: i32) -> i32 {
-    (row + col) % 2
-}
+        // Every reversal whose ends still join tiles like the whole path
+        // would, while the cells before the segment are left alone
+        let (mut reversals, mut inner) = (0, None);
+        for path in &paths {
+            let mut tiled = path_to_tiles(path, grid_size, TileOptions::default()).grid;
+            tiled[0][0].as_mut().unwrap().tile_id = "kept".to_string();
+            for from in 0..path.len() {
+                for to in from + 1..path.len() {
+                    let reversal = reverse_segment_tiles(tiled.clone(), path, from, to, grid_size);
+                    let Some(reversal) = reversal else {
+                        continue;
+                    };
+                    reversals += 1;
+                    let mut full = path_to_tiles(&reversal.path, grid_size, TileOptions::default());
+                    assert_eq!(reversal.result.valid, full.valid);
+                    if from >= 2 {
+                        inner.get_or_insert((path, from, to));
+                        assert_eq!(reversal.result.grid[0][0].as_ref().unwrap().tile_id, "kept");
+                        full.grid[0][0] = tiled[0][0].clone();
+                    }
+                    assert_eq!(reversal.result.grid, full.grid, "{:?} {}..={}", path, from, to);
+                }
+            }
+        }
+        assert!(reversals > 20, "{}", reversals);
 
-/// Check if two cells have different parity
-#[wasm_bindgen]
-pub fn has_different_parity(r1: i32, c1: i32, r2: i32, c2: i32) -> bool {
-    cell_parity(r1, c1) != cell_parity(r2, c2)
-}
+        let (path, from, to) = inner.unwrap();
+        let tiled = path_to_tiles(path, grid_size, TileOptions::default()).grid;
+        assert!(reverse_segment_tiles(tiled.clone(), path, to, from, grid_size).is_none());
+        assert!(reverse_segment_tiles(tiled.clone(), path, from, 16, grid_size).is_none());
+        // The lanes resume from the tile before the retiled cells
+        let mut blank = tiled;
+        let kept = path[from - 2];
+        blank[kept.row as usize][kept.col as usize] = None;
+        assert!(reverse_segment_tiles(blank, path, from, to, grid_size).is_none());
+    }
 
-// ============================================================================
-// Tests
-// ============================================================================
+    #[test]
+    fn test_wrapped_path() {
+        // The wrap joins (0, 1) back to (0, 0) the long way round
+        let plain = solve_road_path(
+            Point::new(0, 0),
+            Point::new(0, 1),
+            GridSize { rows: 1, cols: 4 },
+            10_000,
+            SearchOptions::default(),
+        );
+        assert!(!plain.found);
+        let size = GridSize { rows: 1, cols: 4 };
+        let wrapped = solve_wrapped_path(Point::new(0, 0), Point::new(0, 1), size, 10_000);
+        assert!(wrapped.found);
+        let cols: Vec<i32> = wrapped.path.iter().map(|p| p.col).collect();
+        assert_eq!(cols, vec![0, 3, 2, 1]);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        // An odd wrapping dimension voids the coloring argument
+        let size = GridSize { rows: 3, cols: 3 };
+        let rejected = solve_road_path(
+            Point::new(0, 0),
+            Point::new(0, 1),
+            size,
+            10_000,
+            SearchOptions::default(),
+        );
+        assert!(rejected.reason.is_some());
+        let odd = solve_wrapped_path(Point::new(0, 0), Point::new(0, 1), size, 10_000);
+        assert!(odd.found, "{:?}", odd.reason);
+        assert_eq!(odd.path.len(), 9);
+        let joined = |w: &[Point]| torus_step_direction(w[0], w[1], size).is_some();
+        assert!(odd.path.windows(2).all(joined));
 
-    #[test]
-    fn test_find_path_small_grid() {
-        // For a 2x2 grid, start and end must have different parity for Hamiltonian path
-        // (0,0) has parity 0, (0,1) has parity 1
-        let start = Point::new(0, 0);
-        let end = Point::new(0, 1);
-        let grid_size = GridSize { rows: 2, cols: 2 };
+        // With even dimensions it still holds
+        let size = GridSize { rows: 4, cols: 4 };
+        let even = solve_wrapped_path(Point::new(0, 0), Point::new(1, 1), size, 10_000);
+        assert!(!even.found);
+        assert!(even.reason.is_some());
 
-        let mut state = PathState::new(grid_size, 1000);
-        let found = find_path_internal(&mut state, start, end);
+        let options = TileOptions {
+            wrap: true,
+            ..TileOptions::default()
+        };
+        let size = GridSize { rows: 1, cols: 4 };
+        let tiled = path_to_tiles(&wrapped.path, size, options);
+        assert!(tiled.valid);
+        let start = tiled.grid[0][0].as_ref().unwrap();
+        assert_eq!(start.connections[0].direction, "left");
+        let plain_tiles = path_to_tiles(&wrapped.path, size, TileOptions::default());
+        assert!(!plain_tiles.valid);
 
-        assert!(found);
-        assert_eq!(state.path.len(), 4);
+        // Locks see the wrapped neighbors too
+        let locked = |tile_id: &str| {
+            let options = SearchOptions {
+                wrap: true,
+                locked_tiles: vec![(Point::new(0, 3), find_tile(tile_id).unwrap())],
+                ..SearchOptions::default()
+            };
+            solve_road_path(Point::new(0, 0), Point::new(0, 1), size, 10_000, options)
+        };
+        assert_eq!(locked("straight-h-88").path, wrapped.path);
+        assert!(!locked("straight-v-22").found);
     }
 
     #[test]
-    fn test_parity() {
-        assert_eq!(cell_parity(0, 0), 0);
-        assert_eq!(cell_parity(0, 1), 1);
-        assert_eq!(cell_parity(1, 0), 1);
-        assert_eq!(cell_parity(1, 1), 0);
+    fn test_simplify_turns() {
+        let size = GridSize { rows: 6, cols: 6 };
+        let (start, end) = (Point::new(0, 0), Point::new(5, 4));
+        let found = solve_road_path(start, end, size, 100_000, SearchOptions::default());
+        let simplified = simplify_turns(&found.path).unwrap();
+        assert_eq!(simplified.turns_before, compute_path_stats(&found.path).unwrap().turns);
+        assert!(simplified.turns_after < simplified.turns_before);
+        assert!(simplified.swaps > 0);
+        assert_eq!(compute_path_stats(&simplified.path).unwrap().turns, simplified.turns_after);
+        assert!(is_hamiltonian(&simplified.path, size, &[]));
+        assert_eq!(simplified.path.first(), Some(&start));
+        assert_eq!(simplified.path.last(), Some(&end));
+
+        // The result is a local minimum, and so is a snake
+        assert_eq!(simplify_turns(&simplified.path).unwrap().swaps, 0);
+        let snake = build_snake_path(start, size, false).unwrap();
+        assert_eq!(simplify_turns(&snake).unwrap().path, snake);
+
+        let gap = [Point::new(0, 0), Point::new(0, 2)];
+        assert!(simplify_turns(&gap).is_none());
+        let repeat = [Point::new(0, 0), Point::new(0, 1), Point::new(0, 0)];
+        assert!(simplify_turns(&repeat).is_none());
     }
 
     #[test]
-    fn test_different_parity() {
-        assert!(has_different_parity(0, 0, 0, 1));
-        assert!(!has_different_parity(0, 0, 1, 1));
+    fn test_tile_index_matches_linear_scan() {
+        let tiles = get_all_tiles();
+        for entry in Direction::with_diagonals() {
+            for exit in Direction::with_diagonals() {
+                for required in [None, Some(PortSet::P12), Some(PortSet::P23), Some(PortSet::P34)] {
+                    for lane_change in [false, true] {
+                        assert_eq!(
+                            lookup_tile(entry, exit, required, lane_change),
+                            find_tile_with_port_constraint(&tiles, entry, exit, required, lane_change),
+                        );
+                    }
+                }
+            }
+        }
     }
 
     #[test]
-    fn test_tile_definitions() {
+    fn test_indexed_tiling_of_found_paths() {
         let tiles = get_all_tiles();
-        assert_eq!(tiles.len(), 40); // 16 curve + 16 sharp + 8 straight
+        for (rows, cols, end) in [(3, 3, Point::new(2, 2)), (4, 4, Point::new(3, 0)), (2, 5, Point::new(0, 1))] {
+            let grid_size = GridSize { rows, cols };
+            let mut state = PathState::new(grid_size, 100_000);
+            assert!(find_path_internal(&mut state, Point::new(0, 0), end));
+
+            let result = path_to_tiles(&state.path, grid_size, TileOptions::default());
+            assert!(result.valid);
+
+            // Re-derive every middle tile with the linear scan
+            let mut required = Some(PortSet::P23);
+            for w in state.path.windows(3) {
+                let entry = get_direction(w[0], w[1]).unwrap();
+                let exit = get_direction(w[1], w[2]).unwrap();
+                let (id, _, xp) = find_tile_with_port_constraint(&tiles, entry, exit, required, false).unwrap();
+                assert_eq!(result.grid[w[1].row as usize][w[1].col as usize].as_ref().unwrap().tile_id, id);
+                required = Some(xp);
+            }
+        }
     }
 
     #[test]
-    fn test_path_to_tiles() {
-        // Test a simple 3-cell path
-        let path = vec![
-            Point::new(0, 0),
-            Point::new(0, 1),
-            Point::new(0, 2),
-        ];
-        let grid_size = GridSize { rows: 1, cols: 3 };
-        let result = path_to_tiles(&path, grid_size);
+    fn test_path_to_tiles_rejects_gaps() {
+        // A jump from (0,0) to (0,2) used to panic in get_direction
+        let path = vec![Point::new(0, 0), Point::new(0, 2), Point::new(1, 2)];
+        let grid_size = GridSize { rows: 2, cols: 3 };
+        assert!(!path_to_tiles(&path, grid_size, TileOptions::default()).valid);
+    }
 
+    #[test]
+    fn test_validate_tiled_grid() {
+        let grid_size = GridSize { rows: 3, cols: 3 };
+        let mut state = PathState::new(grid_size, 10_000);
+        assert!(find_path_internal(&mut state, Point::new(0, 0), Point::new(2, 2)));
+
+        let result = path_to_tiles(&state.path, grid_size, TileOptions::default());
         assert!(result.valid);
-        // First cell should be start
-        assert_eq!(result.grid[0][0].as_ref().unwrap().tile_id, "start");
-        // Last cell should be goal
-        assert_eq!(result.grid[0][2].as_ref().unwrap().tile_id, "goal");
+        assert!(validate_grid(&result.grid, grid_size).is_empty());
+    }
+
+    #[test]
+    fn test_validate_grid_reports_mismatches() {
+        let path = vec![Point::new(0, 0), Point::new(0, 1), Point::new(0, 2)];
+        let grid_size = GridSize { rows: 1, cols: 3 };
+        let mut grid = path_to_tiles(&path, grid_size, TileOptions::default()).grid;
+
+        // Flip the lane on the goal's entry so it no longer matches its neighbor
+        grid[0][2].as_mut().unwrap().connections[0].ports = "12".to_string();
+        let mismatches = validate_grid(&grid, grid_size);
+        assert_eq!(mismatches.len(), 2);
+        assert!(mismatches.iter().all(|m| m.kind == "port_mismatch"));
+        assert!(mismatches.iter().any(|m| m.col == 1 && m.direction == "right"));
+        assert!(mismatches.iter().any(|m| m.col == 2 && m.direction == "left"));
+
+        // Removing a neighbor leaves a dangling connection
+        grid[0][2] = None;
+        let mismatches = validate_grid(&grid, grid_size);
+        assert_eq!(mismatches, vec![GridMismatch {
+            row: 0,
+            col: 1,
+            direction: "right".to_string(),
+            kind: "missing_neighbor".to_string(),
+        }]);
     }
 }
@@ -3,15 +3,16 @@
  *
  * Port system:
  * - Each side (U/D/L/R) has 4 ports (0-3)
- * - Roads use 2-lane connections (ports 1,2 or 2,3)
- * - Mask uses bit flags: U=0x01/0x02, R=0x04/0x08, D=0x10/0x20, L=0x40/0x80
+ * - Roads use 2-lane connections (ports 1,2 or 2,3); 3-lane roads add ports 3,4
+ * - Mask uses bit flags: U=0x01/0x02, R=0x04/0x08, D=0x10/0x20, L=0x40/0x80,
+ *   and U=0x100, R=0x200, D=0x400, L=0x800 for ports 3,4
  */
 
 // Direction types
 export type Direction = "up" | "down" | "left" | "right"
 
-// Port position within a side (0-3, but roads use 1-2 or 2-3)
-export type PortSet = "12" | "23" // ports 1,2 or ports 2,3
+// Port position within a side (0-3, but roads use 1-2, 2-3 or 3-4)
+export type PortSet = "12" | "23" | "34" // ports 1,2, ports 2,3 or ports 3,4
 
 // Tile variant type
 export type TileVariant = "curve" | "sharp" | "straight"
@@ -41,10 +42,10 @@ export interface MarkerDefinition {
 
 // Direction to mask bits mapping
 const DIRECTION_MASKS: Record<Direction, Record<PortSet, number>> = {
-  up: { "12": 0x01, "23": 0x02 },
-  right: { "12": 0x04, "23": 0x08 },
-  down: { "12": 0x10, "23": 0x20 },
-  left: { "12": 0x40, "23": 0x80 },
+  up: { "12": 0x01, "23": 0x02, "34": 0x100 },
+  right: { "12": 0x04, "23": 0x08, "34": 0x200 },
+  down: { "12": 0x10, "23": 0x20, "34": 0x400 },
+  left: { "12": 0x40, "23": 0x80, "34": 0x800 },
 }
 
 // Get mask for a connection
@@ -359,7 +360,7 @@ export const TILE_DEFINITIONS: TileDefinition[] = [
   {
     id: "straight-v-12",
     variant: "straight",
-    mask: 0x12,
+    mask: 0x21,
     connections: [
       { direction: "up", ports: "12" },
       { direction: "down", ports: "23" },
@@ -368,7 +369,7 @@ export const TILE_DEFINITIONS: TileDefinition[] = [
   {
     id: "straight-v-21",
     variant: "straight",
-    mask: 0x21,
+    mask: 0x12,
     connections: [
       { direction: "up", ports: "23" },
       { direction: "down", ports: "12" },
@@ -420,6 +421,460 @@ export const TILE_DEFINITIONS: TileDefinition[] = [
       { direction: "right", ports: "23" },
     ],
   },
+  // Curve tiles using the third lane (20 variations)
+  {
+    id: "curve-104",
+    variant: "curve",
+    mask: 0x104,
+    connections: [
+      { direction: "up", ports: "34" },
+      { direction: "right", ports: "12" },
+    ],
+  },
+  {
+    id: "curve-108",
+    variant: "curve",
+    mask: 0x108,
+    connections: [
+      { direction: "up", ports: "34" },
+      { direction: "right", ports: "23" },
+    ],
+  },
+  {
+    id: "curve-140",
+    variant: "curve",
+    mask: 0x140,
+    connections: [
+      { direction: "up", ports: "34" },
+      { direction: "left", ports: "12" },
+    ],
+  },
+  {
+    id: "curve-180",
+    variant: "curve",
+    mask: 0x180,
+    connections: [
+      { direction: "up", ports: "34" },
+      { direction: "left", ports: "23" },
+    ],
+  },
+  {
+    id: "curve-201",
+    variant: "curve",
+    mask: 0x201,
+    connections: [
+      { direction: "up", ports: "12" },
+      { direction: "right", ports: "34" },
+    ],
+  },
+  {
+    id: "curve-202",
+    variant: "curve",
+    mask: 0x202,
+    connections: [
+      { direction: "up", ports: "23" },
+      { direction: "right", ports: "34" },
+    ],
+  },
+  {
+    id: "curve-210",
+    variant: "curve",
+    mask: 0x210,
+    connections: [
+      { direction: "right", ports: "34" },
+      { direction: "down", ports: "12" },
+    ],
+  },
+  {
+    id: "curve-220",
+    variant: "curve",
+    mask: 0x220,
+    connections: [
+      { direction: "right", ports: "34" },
+      { direction: "down", ports: "23" },
+    ],
+  },
+  {
+    id: "curve-300",
+    variant: "curve",
+    mask: 0x300,
+    connections: [
+      { direction: "up", ports: "34" },
+      { direction: "right", ports: "34" },
+    ],
+  },
+  {
+    id: "curve-404",
+    variant: "curve",
+    mask: 0x404,
+    connections: [
+      { direction: "right", ports: "12" },
+      { direction: "down", ports: "34" },
+    ],
+  },
+  {
+    id: "curve-408",
+    variant: "curve",
+    mask: 0x408,
+    connections: [
+      { direction: "right", ports: "23" },
+      { direction: "down", ports: "34" },
+    ],
+  },
+  {
+    id: "curve-440",
+    variant: "curve",
+    mask: 0x440,
+    connections: [
+      { direction: "down", ports: "34" },
+      { direction: "left", ports: "12" },
+    ],
+  },
+  {
+    id: "curve-480",
+    variant: "curve",
+    mask: 0x480,
+    connections: [
+      { direction: "down", ports: "34" },
+      { direction: "left", ports: "23" },
+    ],
+  },
+  {
+    id: "curve-600",
+    variant: "curve",
+    mask: 0x600,
+    connections: [
+      { direction: "right", ports: "34" },
+      { direction: "down", ports: "34" },
+    ],
+  },
+  {
+    id: "curve-801",
+    variant: "curve",
+    mask: 0x801,
+    connections: [
+      { direction: "up", ports: "12" },
+      { direction: "left", ports: "34" },
+    ],
+  },
+  {
+    id: "curve-802",
+    variant: "curve",
+    mask: 0x802,
+    connections: [
+      { direction: "up", ports: "23" },
+      { direction: "left", ports: "34" },
+    ],
+  },
+  {
+    id: "curve-810",
+    variant: "curve",
+    mask: 0x810,
+    connections: [
+      { direction: "down", ports: "12" },
+      { direction: "left", ports: "34" },
+    ],
+  },
+  {
+    id: "curve-820",
+    variant: "curve",
+    mask: 0x820,
+    connections: [
+      { direction: "down", ports: "23" },
+      { direction: "left", ports: "34" },
+    ],
+  },
+  {
+    id: "curve-900",
+    variant: "curve",
+    mask: 0x900,
+    connections: [
+      { direction: "up", ports: "34" },
+      { direction: "left", ports: "34" },
+    ],
+  },
+  {
+    id: "curve-C00",
+    variant: "curve",
+    mask: 0xC00,
+    connections: [
+      { direction: "down", ports: "34" },
+      { direction: "left", ports: "34" },
+    ],
+  },
+  // Sharp tiles using the third lane (20 variations)
+  {
+    id: "sharp-104",
+    variant: "sharp",
+    mask: 0x104,
+    connections: [
+      { direction: "up", ports: "34" },
+      { direction: "right", ports: "12" },
+    ],
+  },
+  {
+    id: "sharp-108",
+    variant: "sharp",
+    mask: 0x108,
+    connections: [
+      { direction: "up", ports: "34" },
+      { direction: "right", ports: "23" },
+    ],
+  },
+  {
+    id: "sharp-140",
+    variant: "sharp",
+    mask: 0x140,
+    connections: [
+      { direction: "up", ports: "34" },
+      { direction: "left", ports: "12" },
+    ],
+  },
+  {
+    id: "sharp-180",
+    variant: "sharp",
+    mask: 0x180,
+    connections: [
+      { direction: "up", ports: "34" },
+      { direction: "left", ports: "23" },
+    ],
+  },
+  {
+    id: "sharp-201",
+    variant: "sharp",
+    mask: 0x201,
+    connections: [
+      { direction: "up", ports: "12" },
+      { direction: "right", ports: "34" },
+    ],
+  },
+  {
+    id: "sharp-202",
+    variant: "sharp",
+    mask: 0x202,
+    connections: [
+      { direction: "up", ports: "23" },
+      { direction: "right", ports: "34" },
+    ],
+  },
+  {
+    id: "sharp-210",
+    variant: "sharp",
+    mask: 0x210,
+    connections: [
+      { direction: "right", ports: "34" },
+      { direction: "down", ports: "12" },
+    ],
+  },
+  {
+    id: "sharp-220",
+    variant: "sharp",
+    mask: 0x220,
+    connections: [
+      { direction: "right", ports: "34" },
+      { direction: "down", ports: "23" },
+    ],
+  },
+  {
+    id: "sharp-300",
+    variant: "sharp",
+    mask: 0x300,
+    connections: [
+      { direction: "up", ports: "34" },
+      { direction: "right", ports: "34" },
+    ],
+  },
+  {
+    id: "sharp-404",
+    variant: "sharp",
+    mask: 0x404,
+    connections: [
+      { direction: "right", ports: "12" },
+      { direction: "down", ports: "34" },
+    ],
+  },
+  {
+    id: "sharp-408",
+    variant: "sharp",
+    mask: 0x408,
+    connections: [
+      { direction: "right", ports: "23" },
+      { direction: "down", ports: "34" },
+    ],
+  },
+  {
+    id: "sharp-440",
+    variant: "sharp",
+    mask: 0x440,
+    connections: [
+      { direction: "down", ports: "34" },
+      { direction: "left", ports: "12" },
+    ],
+  },
+  {
+    id: "sharp-480",
+    variant: "sharp",
+    mask: 0x480,
+    connections: [
+      { direction: "down", ports: "34" },
+      { direction: "left", ports: "23" },
+    ],
+  },
+  {
+    id: "sharp-600",
+    variant: "sharp",
+    mask: 0x600,
+    connections: [
+      { direction: "right", ports: "34" },
+      { direction: "down", ports: "34" },
+    ],
+  },
+  {
+    id: "sharp-801",
+    variant: "sharp",
+    mask: 0x801,
+    connections: [
+      { direction: "up", ports: "12" },
+      { direction: "left", ports: "34" },
+    ],
+  },
+  {
+    id: "sharp-802",
+    variant: "sharp",
+    mask: 0x802,
+    connections: [
+      { direction: "up", ports: "23" },
+      { direction: "left", ports: "34" },
+    ],
+  },
+  {
+    id: "sharp-810",
+    variant: "sharp",
+    mask: 0x810,
+    connections: [
+      { direction: "down", ports: "12" },
+      { direction: "left", ports: "34" },
+    ],
+  },
+  {
+    id: "sharp-820",
+    variant: "sharp",
+    mask: 0x820,
+    connections: [
+      { direction: "down", ports: "23" },
+      { direction: "left", ports: "34" },
+    ],
+  },
+  {
+    id: "sharp-900",
+    variant: "sharp",
+    mask: 0x900,
+    connections: [
+      { direction: "up", ports: "34" },
+      { direction: "left", ports: "34" },
+    ],
+  },
+  {
+    id: "sharp-C00",
+    variant: "sharp",
+    mask: 0xC00,
+    connections: [
+      { direction: "down", ports: "34" },
+      { direction: "left", ports: "34" },
+    ],
+  },
+  // Straight tiles using the third lane - Vertical (5 variations)
+  {
+    id: "straight-v-13",
+    variant: "straight",
+    mask: 0x401,
+    connections: [
+      { direction: "up", ports: "12" },
+      { direction: "down", ports: "34" },
+    ],
+  },
+  {
+    id: "straight-v-23",
+    variant: "straight",
+    mask: 0x402,
+    connections: [
+      { direction: "up", ports: "23" },
+      { direction: "down", ports: "34" },
+    ],
+  },
+  {
+    id: "straight-v-31",
+    variant: "straight",
+    mask: 0x110,
+    connections: [
+      { direction: "up", ports: "34" },
+      { direction: "down", ports: "12" },
+    ],
+  },
+  {
+    id: "straight-v-32",
+    variant: "straight",
+    mask: 0x120,
+    connections: [
+      { direction: "up", ports: "34" },
+      { direction: "down", ports: "23" },
+    ],
+  },
+  {
+    id: "straight-v-33",
+    variant: "straight",
+    mask: 0x500,
+    connections: [
+      { direction: "up", ports: "34" },
+      { direction: "down", ports: "34" },
+    ],
+  },
+  // Straight tiles using the third lane - Horizontal (5 variations)
+  {
+    id: "straight-h-240",
+    variant: "straight",
+    mask: 0x240,
+    connections: [
+      { direction: "left", ports: "12" },
+      { direction: "right", ports: "34" },
+    ],
+  },
+  {
+    id: "straight-h-280",
+    variant: "straight",
+    mask: 0x280,
+    connections: [
+      { direction: "left", ports: "23" },
+      { direction: "right", ports: "34" },
+    ],
+  },
+  {
+    id: "straight-h-804",
+    variant: "straight",
+    mask: 0x804,
+    connections: [
+      { direction: "left", ports: "34" },
+      { direction: "right", ports: "12" },
+    ],
+  },
+  {
+    id: "straight-h-808",
+    variant: "straight",
+    mask: 0x808,
+    connections: [
+      { direction: "left", ports: "34" },
+      { direction: "right", ports: "23" },
+    ],
+  },
+  {
+    id: "straight-h-A00",
+    variant: "straight",
+    mask: 0xA00,
+    connections: [
+      { direction: "left", ports: "34" },
+      { direction: "right", ports: "34" },
+    ],
+  },
 ]
 
 // Marker definitions
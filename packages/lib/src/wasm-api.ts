@@ -6,6 +6,7 @@
  */
 
 import type { GridSize, Point } from "./grid-utils"
+import type { PortSet } from "./tile-types"
 
 // Import WASM functions (will be initialized later)
 let wasmModule: typeof import("@road-tile/wasm/pkg/road_tile_wasm") | null = null
@@ -19,6 +20,29 @@ export interface PathResult {
   found: boolean
   path: Point[]
   iterations: number
+  /** Cells taken back off the path (wasted exploration) out of `iterations` */
+  backtracks: number
+  cancelled: boolean
+  timed_out: boolean
+  depth_counts: number[] | null
+  reason: string | null
+  /** Longest prefix reached when the search ran but found nothing */
+  best_partial: Point[] | null
+  /** Visited flags at the deepest point of a failed debug search */
+  visited_grid: boolean[][] | null
+  /** Set when the input itself was invalid, e.g. an endpoint off the grid */
+  input_error: InputError | null
+}
+
+/**
+ * Search input rejected before searching
+ */
+export interface InputError {
+  kind: "invalid_dimensions" | "out_of_bounds" | "same_endpoints"
+  /** Which endpoint is outside the grid, for "out_of_bounds" */
+  point: "start" | "end" | null
+  cell: Point | null
+  grid_size: GridSize
 }
 
 /**
@@ -36,6 +60,16 @@ export interface CellData {
   tile_id: string
   connections: ConnectionData[]
   path_index: number
+  /** Clockwise rotation (degrees) to apply to the base sprite */
+  rotation: number
+  /** Direction of travel into the cell (null for the start) */
+  entry_dir: string | null
+  /** Direction of travel out of the cell (null for the goal) */
+  exit_dir: string | null
+  /** Index of the road the cell belongs to (0 for a single road) */
+  road_id: number
+  /** Other tiles with the same connections, to swap in; filled with `withAlternatives` */
+  alternatives: string[]
 }
 
 /**
@@ -44,6 +78,25 @@ export interface CellData {
 export interface RoadGridResult {
   grid: (CellData | null)[][]
   valid: boolean
+  /** Cell where path tiling found no fitting tile (the grid is kept up to it) */
+  failure: TileFailure | null
+  /** Why tiling stopped, when a constraint such as a lane lock ruled the cell out */
+  reason: string | null
+  /** Shape version of the payload (0 when saved before versioning) */
+  schema_version: number
+}
+
+/**
+ * Path cell that no tile could satisfy
+ */
+export interface TileFailure {
+  path_index: number
+  /** Direction of travel into the cell */
+  entry_direction: string
+  /** Direction of travel out of the cell */
+  exit_direction: string
+  /** Lane the previous tile exited on */
+  required_entry_port: string | null
 }
 
 /**
@@ -88,13 +141,37 @@ function getWasmModule(): NonNullable<typeof wasmModule> {
   return wasmModule
 }
 
+/** Backtracking strategy for the path search */
+export type SearchStrategy = "warnsdorff" | "snake"
+
+/** Order of neighbors that tie under Warnsdorff's rule */
+export type TieBreak = "fixed" | "straight" | "turn" | "spacious"
+
+/** Shape the path search leans toward, after Warnsdorff's rule */
+export type PathStyle = "default" | "spiral" | "snake"
+
 /**
  * Find a Hamiltonian path from start to end
  *
  * @param start - Starting point
  * @param end - Ending point
  * @param gridSize - Grid dimensions
- * @param maxIterations - Maximum iterations for search (default: 500000)
+ * @param maxIterations - Maximum iterations for search (default: 500000; 0 picks a budget from the grid area)
+ * @param blocked - Obstacle cells the path must skip (default: none)
+ * @param seed - Nonzero seed for reproducible tie-breaking (default: 0, fixed order)
+ * @param strategy - Search strategy; "snake" answers corner starts whose end a
+ *   snake reaches without searching (default: "warnsdorff")
+ * @param targetLength - Exact number of cells the path should have instead of
+ *   covering the whole grid (default: full cover)
+ * @param tieBreak - Tie order; "straight" continues the last step first for
+ *   fewer turns, "turn" turns first, "spacious" avoids running alongside the
+ *   path (default: "fixed")
+ * @param memoizeUnsolvable - Answer pairs proven unsolvable earlier in the
+ *   session without searching, and remember new ones (default: false)
+ * @param style - "spiral" hugs the border and winds inward, "snake" keeps
+ *   running along rows; only reorders the search (default: "default")
+ * @param pruning - Skip branches whose unvisited cells are split or
+ *   dead-ended, which helps on long thin grids (default: false)
  * @returns Path result with found flag and path points
  */
 export function findRoadPath(
@@ -102,6 +179,14 @@ export function findRoadPath(
   end: Point,
   gridSize: GridSize,
   maxIterations = 500000,
+  blocked: Point[] = [],
+  seed = 0,
+  strategy: SearchStrategy = "warnsdorff",
+  targetLength?: number,
+  tieBreak: TieBreak = "fixed",
+  memoizeUnsolvable = false,
+  style: PathStyle = "default",
+  pruning = false,
 ): PathResult {
   const wasm = getWasmModule()
 
@@ -113,22 +198,2118 @@ export function findRoadPath(
     gridSize.rows,
     gridSize.cols,
     maxIterations,
+    blocked,
+    seed,
+    strategy,
+    targetLength,
+    tieBreak,
+    memoizeUnsolvable,
+    style,
+    pruning,
   )
 
   return result as PathResult
 }
 
 /**
- * Convert a path to a road grid with tile assignments
+ * Find a Hamiltonian path that may also step diagonally
  *
- * @param path - Array of points representing the path
+ * Diagonal steps never cross each other. Checkerboard parity does not apply,
+ * so endpoints of the same color can be joined.
+ *
+ * @param start - Starting point
+ * @param end - Ending point
  * @param gridSize - Grid dimensions
- * @returns Road grid result with tile data
+ * @param maxIterations - Maximum iterations for search (default: 500000)
+ * @param blocked - Obstacle cells the path must skip (default: none)
+ * @returns Path result with found flag and path points
+ */
+export function findRoadPathDiagonal(
+  start: Point,
+  end: Point,
+  gridSize: GridSize,
+  maxIterations = 500000,
+  blocked: Point[] = [],
+): PathResult {
+  const wasm = getWasmModule()
+
+  const result = wasm.find_road_path_diagonal(
+    start.row,
+    start.col,
+    end.row,
+    end.col,
+    gridSize.rows,
+    gridSize.cols,
+    maxIterations,
+    blocked,
+  )
+
+  return result as PathResult
+}
+
+/**
+ * Find a Hamiltonian path from a start cell that may end anywhere
+ *
+ * The end is the last point of the returned path.
+ *
+ * @param start - Starting point
+ * @param gridSize - Grid dimensions
+ * @param maxIterations - Maximum iterations for search (default: 500000)
+ * @returns Path result with found flag and path points
  */
-export function pathToRoadGrid(path: Point[], gridSize: GridSize): RoadGridResult {
+export function findRoadPathOpenEnd(
+  start: Point,
+  gridSize: GridSize,
+  maxIterations = 500000,
+): PathResult {
   const wasm = getWasmModule()
 
-  const result = wasm.path_to_road_grid(path, gridSize.rows, gridSize.cols)
+  const result = wasm.find_road_path_open_end(
+    start.row,
+    start.col,
+    gridSize.rows,
+    gridSize.cols,
+    maxIterations,
+  )
+
+  return result as PathResult
+}
 
-  return result as RoadGridResult
+/**
+ * Find a Hamiltonian path, preferring low-weight cells
+ *
+ * Among candidates that tie under Warnsdorff's rule, cells with lower weight
+ * are tried first. Every cell is still covered exactly once.
+ *
+ * @param start - Starting point
+ * @param end - Ending point
+ * @param gridSize - Grid dimensions
+ * @param weights - Per-cell weights indexed [row][col]
+ * @param maxIterations - Maximum iterations for search (default: 500000)
+ * @param blocked - Obstacle cells the path must skip (default: none)
+ * @returns Path result with found flag and path points
+ */
+export function findRoadPathWeighted(
+  start: Point,
+  end: Point,
+  gridSize: GridSize,
+  weights: number[][],
+  maxIterations = 500000,
+  blocked: Point[] = [],
+): PathResult {
+  const wasm = getWasmModule()
+
+  const result = wasm.find_road_path_weighted(
+    start.row,
+    start.col,
+    end.row,
+    end.col,
+    gridSize.rows,
+    gridSize.cols,
+    maxIterations,
+    weights,
+    blocked,
+  )
+
+  return result as PathResult
+}
+
+/**
+ * Find a Hamiltonian path that JavaScript can abort
+ *
+ * `shouldCancel` is called every 1024 iterations; once it returns a truthy
+ * value (or throws) the search stops with `cancelled: true`. From a Web
+ * Worker it can read a stop flag that the UI thread sets in a
+ * SharedArrayBuffer.
+ *
+ * @param start - Starting point
+ * @param end - Ending point
+ * @param gridSize - Grid dimensions
+ * @param shouldCancel - Polled during the search; truthy aborts it
+ * @param maxIterations - Maximum iterations for search (default: 500000)
+ * @param blocked - Obstacle cells the path must skip (default: none)
+ * @returns Path result; `cancelled` tells an aborted search apart
+ */
+export function findRoadPathCancellable(
+  start: Point,
+  end: Point,
+  gridSize: GridSize,
+  shouldCancel: () => unknown,
+  maxIterations = 500000,
+  blocked: Point[] = [],
+): PathResult {
+  const wasm = getWasmModule()
+
+  const result = wasm.find_road_path_cancellable(
+    start.row,
+    start.col,
+    end.row,
+    end.col,
+    gridSize.rows,
+    gridSize.cols,
+    maxIterations,
+    blocked,
+    shouldCancel,
+  )
+
+  return result as PathResult
+}
+
+/**
+ * Several distinct full-cover paths between the same endpoints
+ */
+export interface MultiPathResult {
+  found: boolean
+  paths: Point[][]
+  /** True only when the search was exhaustive and found exactly one path */
+  unique: boolean
+  iterations: number
+}
+
+/**
+ * Find up to `maxSolutions` distinct Hamiltonian paths from start to end
+ *
+ * @param start - Starting point
+ * @param end - Ending point
+ * @param gridSize - Grid dimensions
+ * @param maxSolutions - Most paths to return
+ * @param maxIterations - Iteration budget shared by all paths (default: 500000)
+ * @param blocked - Obstacle cells the paths must skip (default: none)
+ * @param pruning - Skip branches that can no longer cover every cell (default: false)
+ * @returns The paths found, and whether the one path is provably unique
+ */
+export function findRoadPaths(
+  start: Point,
+  end: Point,
+  gridSize: GridSize,
+  maxSolutions: number,
+  maxIterations = 500000,
+  blocked: Point[] = [],
+  pruning = false,
+): MultiPathResult {
+  const wasm = getWasmModule()
+
+  const result = wasm.find_road_paths(
+    start.row,
+    start.col,
+    end.row,
+    end.col,
+    gridSize.rows,
+    gridSize.cols,
+    maxIterations,
+    maxSolutions,
+    blocked,
+    pruning,
+  )
+
+  return result as MultiPathResult
+}
+
+/**
+ * Find a Hamiltonian path that passes through waypoints in order
+ *
+ * @param start - Starting point
+ * @param end - Ending point
+ * @param gridSize - Grid dimensions
+ * @param waypoints - Cells to visit, in this order, between start and end
+ * @param maxIterations - Maximum iterations for search (default: 500000)
+ * @param blocked - Obstacle cells the path must skip (default: none)
+ * @returns Path result with found flag and path points
+ */
+export function findRoadPathWithWaypoints(
+  start: Point,
+  end: Point,
+  gridSize: GridSize,
+  waypoints: Point[],
+  maxIterations = 500000,
+  blocked: Point[] = [],
+): PathResult {
+  const wasm = getWasmModule()
+
+  const result = wasm.find_road_path_with_waypoints(
+    start.row,
+    start.col,
+    end.row,
+    end.col,
+    gridSize.rows,
+    gridSize.cols,
+    maxIterations,
+    waypoints,
+    blocked,
+  )
+
+  return result as PathResult
+}
+
+/**
+ * Number of Hamiltonian paths between two cells
+ */
+export interface PathCount {
+  count: number
+  iterations: number
+  /** True when the budget cut counting short; `count` is then a lower bound */
+  capped: boolean
+}
+
+/**
+ * Count the Hamiltonian paths between two cells without storing them
+ *
+ * @param start - Starting point
+ * @param end - Ending point
+ * @param gridSize - Grid dimensions
+ * @param maxIterations - Maximum iterations for the enumeration (default: 500000)
+ * @returns The count, and whether it is capped
+ */
+export function countHamiltonianPaths(
+  start: Point,
+  end: Point,
+  gridSize: GridSize,
+  maxIterations = 500000,
+): PathCount {
+  const wasm = getWasmModule()
+
+  const result = wasm.count_hamiltonian_paths(
+    start.row,
+    start.col,
+    end.row,
+    end.col,
+    gridSize.rows,
+    gridSize.cols,
+    maxIterations,
+  )
+
+  return result as PathCount
+}
+
+/**
+ * Find a Hamiltonian path over a non-rectangular board
+ *
+ * @param start - Starting point
+ * @param end - Ending point
+ * @param gridSize - Bounding grid dimensions
+ * @param mask - Row-major flags, one per cell of the bounding grid, marking
+ *   the cells that exist
+ * @param maxIterations - Maximum iterations for search (default: 500000)
+ * @returns Path result, or null when the mask has the wrong length
+ */
+export function findRoadPathMasked(
+  start: Point,
+  end: Point,
+  gridSize: GridSize,
+  mask: boolean[],
+  maxIterations = 500000,
+): PathResult | null {
+  const wasm = getWasmModule()
+
+  const result = wasm.find_road_path_masked(
+    start.row,
+    start.col,
+    end.row,
+    end.col,
+    gridSize.rows,
+    gridSize.cols,
+    maxIterations,
+    mask,
+  )
+
+  return result as PathResult | null
+}
+
+/**
+ * Find a Hamiltonian path, reporting progress while it searches
+ *
+ * @param start - Starting point
+ * @param end - Ending point
+ * @param gridSize - Grid dimensions
+ * @param onProgress - Called with the iterations so far and the path depth;
+ *   throwing stops the search with `cancelled: true`
+ * @param maxIterations - Maximum iterations for search (default: 500000)
+ * @param blocked - Obstacle cells the path must skip (default: none)
+ * @param progressInterval - Iterations between reports (default: 0, every 1024)
+ * @returns Path result with found flag and path points
+ */
+export function findRoadPathWithProgress(
+  start: Point,
+  end: Point,
+  gridSize: GridSize,
+  onProgress: (iterations: number, depth: number) => void,
+  maxIterations = 500000,
+  blocked: Point[] = [],
+  progressInterval = 0,
+): PathResult {
+  const wasm = getWasmModule()
+
+  const result = wasm.find_road_path_with_progress(
+    start.row,
+    start.col,
+    end.row,
+    end.col,
+    gridSize.rows,
+    gridSize.cols,
+    maxIterations,
+    blocked,
+    onProgress,
+    progressInterval,
+  )
+
+  return result as PathResult
+}
+
+/**
+ * Find a Hamiltonian path within a wall-clock time budget
+ *
+ * Checked with `performance.now()`; where that is missing only
+ * `maxIterations` limits the search.
+ *
+ * @param start - Starting point
+ * @param end - Ending point
+ * @param gridSize - Grid dimensions
+ * @param timeBudgetMs - Milliseconds after which the search stops with `timed_out: true`
+ * @param maxIterations - Maximum iterations for search (default: 500000)
+ * @param blocked - Obstacle cells the path must skip (default: none)
+ * @returns Path result, or null on invalid input
+ */
+export function findRoadPathTimed(
+  start: Point,
+  end: Point,
+  gridSize: GridSize,
+  timeBudgetMs: number,
+  maxIterations = 500000,
+  blocked: Point[] = [],
+): PathResult | null {
+  const wasm = getWasmModule()
+
+  const result = wasm.find_road_path_timed(
+    start.row,
+    start.col,
+    end.row,
+    end.col,
+    gridSize.rows,
+    gridSize.cols,
+    maxIterations,
+    blocked,
+    timeBudgetMs,
+  )
+
+  return result as PathResult | null
+}
+
+/**
+ * Find a Hamiltonian path and record where the search spent its effort
+ *
+ * `depth_counts[d]` is the number of nodes expanded at path length `d + 1`.
+ *
+ * @param start - Starting point
+ * @param end - Ending point
+ * @param gridSize - Grid dimensions
+ * @param maxIterations - Maximum iterations for search (default: 500000)
+ * @param blocked - Obstacle cells the path must skip (default: none)
+ * @param seed - Nonzero seed for reproducible tie-breaking (default: 0, fixed order)
+ * @param pruning - Skip branches that can no longer cover every cell (default: false)
+ * @param debug - Also return `visited_grid` at the deepest point of a failed
+ *   search (default: false)
+ * @returns Path result with `depth_counts`, or null on invalid input
+ */
+export function findRoadPathInstrumented(
+  start: Point,
+  end: Point,
+  gridSize: GridSize,
+  maxIterations = 500000,
+  blocked: Point[] = [],
+  seed = 0,
+  pruning = false,
+  debug = false,
+): PathResult | null {
+  const wasm = getWasmModule()
+
+  const result = wasm.find_road_path_instrumented(
+    start.row,
+    start.col,
+    end.row,
+    end.col,
+    gridSize.rows,
+    gridSize.cols,
+    maxIterations,
+    blocked,
+    seed,
+    pruning,
+    debug,
+  )
+
+  return result as PathResult | null
+}
+
+/** Opaque state of a suspended search */
+export type SearchSnapshot = { readonly __brand: "SearchSnapshot" }
+
+/**
+ * Result of one slice of a resumable search
+ */
+export interface ResumableResult {
+  result: PathResult
+  /** Set when the slice's budget ran out; pass to `resumeRoadPath` */
+  snapshot: SearchSnapshot | null
+}
+
+/**
+ * Start a path search that can be continued in later calls
+ *
+ * Useful for splitting a long search into slices, e.g. between frames or
+ * worker messages.
+ *
+ * @param start - Starting point
+ * @param end - Ending point
+ * @param gridSize - Grid dimensions
+ * @param maxIterations - Iteration budget of this slice
+ * @param blocked - Obstacle cells the path must skip (default: none)
+ * @returns Result so far, plus a snapshot if the search is unfinished
+ */
+export function findRoadPathResumable(
+  start: Point,
+  end: Point,
+  gridSize: GridSize,
+  maxIterations: number,
+  blocked: Point[] = [],
+): ResumableResult {
+  const wasm = getWasmModule()
+
+  const result = wasm.find_road_path_resumable(
+    start.row,
+    start.col,
+    end.row,
+    end.col,
+    gridSize.rows,
+    gridSize.cols,
+    maxIterations,
+    blocked,
+  )
+
+  return result as ResumableResult
+}
+
+/**
+ * Continue a search suspended by `findRoadPathResumable` or `resumeRoadPath`
+ *
+ * @param snapshot - Snapshot from the previous slice
+ * @param extraIterations - Iteration budget of this slice
+ * @returns Result so far (iterations are cumulative), or null for a bad snapshot
+ */
+export function resumeRoadPath(
+  snapshot: SearchSnapshot,
+  extraIterations: number,
+): ResumableResult | null {
+  const wasm = getWasmModule()
+
+  const result = wasm.resume_road_path(snapshot, extraIterations)
+
+  return result as ResumableResult | null
+}
+
+/**
+ * A cell the designer locked to a specific tile
+ */
+export interface TileLock extends Point {
+  tile_id: string
+}
+
+export interface LockedPathResult {
+  result: PathResult
+  /** The found path tiled with the locked tiles in place, or null */
+  grid: RoadGridResult | null
+}
+
+/**
+ * Find a full-cover path that routes through locked tiles
+ *
+ * The path enters and leaves each locked cell through its tile's sides, and
+ * tiling it with the default `pathToRoadGrid` options gives the locked
+ * tile's ports there. `grid` is that tiling with the locked tile ids in
+ * place (a locked sharp corner replaces the curve the tiler would pick).
+ *
+ * @param start - Starting point
+ * @param end - Ending point
+ * @param gridSize - Grid dimensions
+ * @param locks - Locked cells with their tile ids; endpoints can't be locked
+ * @param maxIterations - Maximum iterations for search (default: 500000)
+ * @returns Search result and tiled grid, or null for an unknown tile id or a
+ * lock outside the grid
+ */
+export function findRoadPathWithLockedTiles(
+  start: Point,
+  end: Point,
+  gridSize: GridSize,
+  locks: TileLock[],
+  maxIterations = 500000,
+): LockedPathResult | null {
+  const wasm = getWasmModule()
+
+  const result = wasm.find_road_path_with_locked_tiles(
+    start.row,
+    start.col,
+    end.row,
+    end.col,
+    gridSize.rows,
+    gridSize.cols,
+    maxIterations,
+    locks,
+  )
+
+  return result as LockedPathResult | null
+}
+
+/**
+ * Area and checkerboard coloring of a grid
+ */
+export interface GridInfo {
+  area: number
+  is_even_area: boolean
+  /** Equal even and odd counts: endpoints must then differ in parity */
+  parity_balanced: boolean
+  /** Cells with parity 0 */
+  even_cells: number
+  /** Cells with parity 1 */
+  odd_cells: number
+}
+
+/**
+ * Summarize a grid's area and parity classes, as the search's parity check sees them
+ *
+ * @param gridSize - Grid dimensions
+ * @returns Grid summary, or null for non-positive dimensions or more than 2^20 cells
+ */
+export function gridInfo(gridSize: GridSize): GridInfo | null {
+  const wasm = getWasmModule()
+
+  const result = wasm.grid_info(gridSize.rows, gridSize.cols)
+
+  return result as GridInfo | null
+}
+
+/**
+ * Result of the fewest-turns search
+ */
+export interface SmoothestPathResult {
+  found: boolean
+  path: Point[]
+  /** Direction changes along the path */
+  turns: number
+  iterations: number
+  /** False when the budget ran out, so a smoother path may exist */
+  optimal: boolean
+}
+
+/**
+ * Find the Hamiltonian path with the fewest direction changes
+ *
+ * Exponential in the worst case; keep the budget bounded on larger grids.
+ *
+ * @param start - Starting point
+ * @param end - Ending point
+ * @param gridSize - Grid dimensions
+ * @param maxIterations - Iteration budget for the whole search
+ * @param blocked - Obstacle cells the path must skip (default: none)
+ * @returns The smoothest path found and whether it is proven optimal
+ */
+export function findSmoothestRoadPath(
+  start: Point,
+  end: Point,
+  gridSize: GridSize,
+  maxIterations = 500000,
+  blocked: Point[] = [],
+): SmoothestPathResult {
+  const wasm = getWasmModule()
+
+  const result = wasm.find_smoothest_road_path(
+    start.row,
+    start.col,
+    end.row,
+    end.col,
+    gridSize.rows,
+    gridSize.cols,
+    maxIterations,
+    blocked,
+  )
+
+  return result as SmoothestPathResult
+}
+
+/**
+ * Heuristic difficulty of a path search
+ */
+export interface DifficultyEstimate {
+  feasible: boolean
+  /** Rough log10 of the iterations a plain search needs */
+  score: number
+  suggested_max_iterations: number
+}
+
+/**
+ * Estimate how hard a path search will be, without running it
+ *
+ * @param start - Starting point
+ * @param end - Ending point
+ * @param gridSize - Grid dimensions
+ * @returns Estimate including a suggested maxIterations budget
+ */
+export function estimateSearchDifficulty(
+  start: Point,
+  end: Point,
+  gridSize: GridSize,
+): DifficultyEstimate {
+  const wasm = getWasmModule()
+
+  const result = wasm.estimate_search_difficulty(
+    start.row,
+    start.col,
+    end.row,
+    end.col,
+    gridSize.rows,
+    gridSize.cols,
+  )
+
+  return result as DifficultyEstimate
+}
+
+/**
+ * Timing of one benchmark search
+ */
+export interface BenchmarkEntry extends GridSize {
+  /** Corner the search ran to from (0, 0) */
+  end: Point
+  found: boolean
+  iterations: number
+  elapsed_ms: number
+}
+
+/**
+ * Time the solver across grid sizes, for profiling and regression reports
+ *
+ * Unlike estimateSearchDifficulty this runs the search: one plain search per
+ * size from the top-left corner to the opposite corner, or to another corner
+ * when parity rules the opposite one out.
+ *
+ * @param sizes - Grid sizes to run
+ * @param maxIterations - Iteration budget of each search (default: 500000)
+ * @returns One entry per size, or null when the sizes are malformed
+ */
+export function benchmark(sizes: GridSize[], maxIterations = 500000): BenchmarkEntry[] | null {
+  const wasm = getWasmModule()
+
+  const result = wasm.benchmark(sizes, maxIterations)
+
+  return result as BenchmarkEntry[] | null
+}
+
+/**
+ * Iteration range of a batch histogram
+ */
+export interface IterationBucket {
+  /** "<1k", "1k-10k", "10k-100k", "100k-1M" or "1M+" */
+  name: string
+  min: number
+  /** Exclusive upper bound; null for the last bucket */
+  max: number | null
+  count: number
+}
+
+/**
+ * Aggregate figures over a batch of search results
+ */
+export interface BatchStats {
+  count: number
+  found: number
+  found_fraction: number
+  mean_iterations: number
+  median_iterations: number
+  histogram: IterationBucket[]
+}
+
+/**
+ * Summarize a batch of search results, e.g. from a puzzle generation run
+ *
+ * Complements benchmark, which times synthetic corner-to-corner searches.
+ *
+ * @param results - Results returned by the path searches
+ * @returns Found fraction, mean and median iterations and a histogram, or
+ *   null when the results are malformed
+ */
+export function summarizeResults(results: PathResult[]): BatchStats | null {
+  const wasm = getWasmModule()
+
+  return wasm.summarize_results(results) as BatchStats | null
+}
+
+/**
+ * Star rating of a start/end pair as a puzzle
+ */
+export interface PuzzleRating {
+  solvable: boolean
+  /** 1 (easiest) to 5 (hardest); 0 when unsolvable */
+  stars: number
+  /** Weighted sum of the normalized metrics, in [0, 1] */
+  score: number
+  first_solution_iterations: number
+  solution_count: number
+  /** True when the budget cut the count short */
+  count_capped: boolean
+}
+
+/**
+ * Rate a puzzle by search effort, number of solutions and grid area
+ *
+ * Runs a search and a full enumeration, each with `maxIterations`.
+ *
+ * @param start - Starting point
+ * @param end - Ending point
+ * @param gridSize - Grid dimensions
+ * @param maxIterations - Iteration budget for the search and for the count
+ * @returns The rating, or null for invalid dimensions or endpoints
+ */
+export function ratePuzzle(
+  start: Point,
+  end: Point,
+  gridSize: GridSize,
+  maxIterations = 500000,
+): PuzzleRating | null {
+  const wasm = getWasmModule()
+
+  const result = wasm.rate_puzzle(
+    start.row,
+    start.col,
+    end.row,
+    end.col,
+    gridSize.rows,
+    gridSize.cols,
+    maxIterations,
+  )
+
+  return result as PuzzleRating | null
+}
+
+/**
+ * Start and end of one search in a batch
+ */
+export interface EndpointPair {
+  start: Point
+  end: Point
+}
+
+/**
+ * Find paths for many endpoint pairs on the same grid in one WASM call
+ *
+ * @param pairs - Endpoint pairs to solve
+ * @param gridSize - Grid dimensions
+ * @param maxIterations - Iteration budget for each search
+ * @param memoizeUnsolvable - Skip pairs proven unsolvable earlier in the
+ *   session and remember new ones (default: false)
+ * @returns One path result per pair, in input order
+ */
+export function findRoadPathsBatch(
+  pairs: EndpointPair[],
+  gridSize: GridSize,
+  maxIterations = 500000,
+  memoizeUnsolvable = false,
+): PathResult[] {
+  const wasm = getWasmModule()
+
+  const result = wasm.find_road_paths_batch(
+    pairs,
+    gridSize.rows,
+    gridSize.cols,
+    maxIterations,
+    memoizeUnsolvable,
+  )
+
+  return result as PathResult[]
+}
+
+/**
+ * Forget the endpoint pairs memoized as unsolvable by `memoizeUnsolvable`
+ * searches, to reclaim memory in a long-lived session
+ */
+export function clearSolvabilityCache(): void {
+  const wasm = getWasmModule()
+
+  wasm.clear_solvability_cache()
+}
+
+/**
+ * An endpoint pair known to have a Hamiltonian path
+ */
+export interface SolvableEndpoints extends EndpointPair {
+  iterations: number
+}
+
+/**
+ * List the endpoint pairs of a grid that admit a Hamiltonian path
+ *
+ * Each unordered pair appears once; pairs whose search exhausts the budget
+ * are left out, so keep grids small or the budget modest.
+ *
+ * @param gridSize - Grid dimensions
+ * @param maxIterations - Iteration budget for each pair
+ * @returns Solvable pairs with the iterations each search took
+ */
+export function solvableEndpoints(gridSize: GridSize, maxIterations = 500000): SolvableEndpoints[] {
+  const wasm = getWasmModule()
+
+  const result = wasm.solvable_endpoints(gridSize.rows, gridSize.cols, maxIterations)
+
+  return (result as SolvableEndpoints[] | null) ?? []
+}
+
+/**
+ * A cell reachable from the start, with its distance in steps
+ */
+export interface ReachableCell extends Point {
+  distance: number
+}
+
+/**
+ * Cells within `steps` moves of the start, for highlighting
+ *
+ * @param start - Starting point
+ * @param gridSize - Grid dimensions
+ * @param steps - Maximum number of moves
+ * @param blocked - Cells that cannot be entered
+ * @returns Reachable cells in BFS order, starting with `start` itself
+ */
+export function reachableCells(
+  start: Point,
+  gridSize: GridSize,
+  steps: number,
+  blocked: Point[] = [],
+): ReachableCell[] {
+  const wasm = getWasmModule()
+
+  const result = wasm.reachable_cells(
+    start.row,
+    start.col,
+    gridSize.rows,
+    gridSize.cols,
+    steps,
+    blocked,
+  )
+
+  return (result as ReachableCell[] | null) ?? []
+}
+
+/**
+ * Shortest route between two cells, ignoring coverage
+ */
+export interface ShortestPath {
+  found: boolean
+  /** Start to end inclusive; empty when not found */
+  path: Point[]
+  /** Number of steps along the path */
+  length: number
+}
+
+/**
+ * Find the fewest-steps orthogonal route from start to end, e.g. for hints
+ *
+ * Unlike `findRoadPath` the route needn't visit every cell.
+ *
+ * @param start - Starting point
+ * @param end - Ending point
+ * @param gridSize - Grid dimensions
+ * @param blocked - Cells that cannot be entered
+ * @returns The route, with found=false when the end is unreachable or an
+ * endpoint is blocked or off the grid; null for invalid grid dimensions
+ */
+export function shortestPath(
+  start: Point,
+  end: Point,
+  gridSize: GridSize,
+  blocked: Point[] = [],
+): ShortestPath | null {
+  const wasm = getWasmModule()
+
+  const result = wasm.shortest_path(
+    start.row,
+    start.col,
+    end.row,
+    end.col,
+    gridSize.rows,
+    gridSize.cols,
+    blocked,
+  )
+
+  return result as ShortestPath | null
+}
+
+/**
+ * Free cell whose blocking would split its part of the board
+ */
+export interface CutCell extends Point {
+  /** Pieces its part of the board falls into without it (at least 2) */
+  pieces: number
+}
+
+/**
+ * Cells that hold the free part of a board together
+ */
+export interface CutCellReport {
+  /** Connected groups of free cells */
+  components: number
+  /** Articulation cells in row-major order */
+  cut_cells: CutCell[]
+  /** True when no path can cover every free cell, whatever the endpoints */
+  ruled_out: boolean
+}
+
+/**
+ * Find the cells that split the free part of a board, to explain why it has
+ * no path ("this wall makes it impossible")
+ *
+ * A path passes a cell once, so it can join at most two of the pieces a cut
+ * cell leaves; three or more (or disconnected free cells) rule the board out.
+ *
+ * @param gridSize - Grid dimensions
+ * @param blocked - Cells that cannot be used
+ * @returns Cut cells and verdict, or null for invalid grid dimensions
+ */
+export function cutCells(gridSize: GridSize, blocked: Point[] = []): CutCellReport | null {
+  const wasm = getWasmModule()
+
+  const result = wasm.cut_cells(gridSize.rows, gridSize.cols, blocked)
+
+  return result as CutCellReport | null
+}
+
+/**
+ * Cells a partial path still has to cover
+ */
+export interface RemainingCells {
+  /** Cells neither on the path nor blocked, in row-major order */
+  cells: Point[]
+  /** Whether all of them can still be reached from the path's last cell */
+  all_reachable: boolean
+}
+
+/**
+ * List the cells not yet on a path being drawn, for a live "you've stranded
+ * these cells" warning
+ *
+ * @param path - The path drawn so far
+ * @param gridSize - Grid dimensions
+ * @param blocked - Cells that cannot be used
+ * @returns The uncovered cells and whether the head can still reach them all,
+ *   or null when the path is invalid or crosses a blocked cell
+ */
+export function remainingCells(
+  path: Point[],
+  gridSize: GridSize,
+  blocked: Point[] = [],
+): RemainingCells | null {
+  const wasm = getWasmModule()
+
+  const result = wasm.remaining_cells(path, gridSize.rows, gridSize.cols, blocked)
+
+  return result as RemainingCells | null
+}
+
+/**
+ * Canonical form of an endpoint pair under the grid's symmetries
+ *
+ * Rotated or mirrored copies of the same puzzle map to the same pair, so the
+ * result can be used as a dedupe key.
+ *
+ * @param start - Starting point
+ * @param end - Ending point
+ * @param gridSize - Grid dimensions
+ * @returns Canonical pair, or null if an endpoint is outside the grid
+ */
+export function canonicalEndpoints(
+  start: Point,
+  end: Point,
+  gridSize: GridSize,
+): EndpointPair | null {
+  const wasm = getWasmModule()
+
+  const result = wasm.canonical_endpoints(
+    start.row,
+    start.col,
+    end.row,
+    end.col,
+    gridSize.rows,
+    gridSize.cols,
+  )
+
+  return result as EndpointPair | null
+}
+
+/**
+ * Shape metrics of a path
+ */
+export interface PathStats {
+  /** Number of cells */
+  length: number
+  /** Direction changes between consecutive steps */
+  turns: number
+  /** Interior cells without a direction change */
+  straights: number
+  /** Interior cells with a direction change */
+  corners: number
+  /** Cells covered by the longest straight segment */
+  longest_straight_run: number
+  /** Non-consecutive path cells that sit side by side */
+  self_adjacencies: number
+}
+
+/**
+ * Compute shape metrics of a path, using the same definitions as the WASM side
+ *
+ * @param path - Path cells in order
+ * @returns Metrics, or null if the path has non-adjacent steps
+ */
+export function pathStats(path: Point[]): PathStats | null {
+  const wasm = getWasmModule()
+
+  const result = wasm.path_stats(path)
+
+  return result as PathStats | null
+}
+
+/**
+ * A path with fewer turns, over the same cells between the same endpoints
+ */
+export interface SimplifiedPath {
+  path: Point[]
+  turns_before: number
+  turns_after: number
+  /** Moves applied, each removing at least one turn */
+  swaps: number
+}
+
+/**
+ * Make a found path less wiggly with local 2-opt moves
+ *
+ * Stretches are reversed, or cut out as a loop and spliced back elsewhere,
+ * only when that lowers the turn count, so a Hamiltonian path stays one.
+ * The result is a local minimum, not necessarily the smoothest path.
+ *
+ * @param path - Path cells in order
+ * @returns The simplified path with turn counts, or null if the path has
+ *   non-adjacent steps or a repeated cell
+ */
+export function simplifyPath(path: Point[]): SimplifiedPath | null {
+  const wasm = getWasmModule()
+
+  const result = wasm.simplify_path(path)
+
+  return result as SimplifiedPath | null
+}
+
+/**
+ * A found path with the steps that had no alternative
+ */
+export interface ForcedMoveReport {
+  result: PathResult
+  /** One flag per step: true when the cell left had one unvisited neighbor */
+  forced: boolean[]
+  /** Number of forced steps */
+  forced_moves: number
+}
+
+/**
+ * Find a path and count its forced moves, a difficulty signal: the more
+ * steps have only one continuation, the easier the puzzle
+ *
+ * @param start - Starting point
+ * @param end - Ending point
+ * @param gridSize - Grid dimensions
+ * @param maxIterations - Maximum search iterations (default: 500000)
+ * @returns The path result with per-step forced flags and their count
+ */
+export function forcedMoveCount(
+  start: Point,
+  end: Point,
+  gridSize: GridSize,
+  maxIterations = 500000,
+): ForcedMoveReport {
+  const wasm = getWasmModule()
+
+  const result = wasm.forced_move_count(
+    start.row,
+    start.col,
+    end.row,
+    end.col,
+    gridSize.rows,
+    gridSize.cols,
+    maxIterations,
+  )
+
+  return result as ForcedMoveReport
+}
+
+/**
+ * Pack a cell's connections into the tile mask format
+ *
+ * Two cells have the same connections exactly when their masks are equal,
+ * so grids can be diffed by XORing masks.
+ *
+ * @param connections - Connections of one cell
+ * @returns Mask, or null for unknown direction or port names
+ */
+export function connectionsToMask(connections: ConnectionData[]): number | null {
+  const wasm = getWasmModule()
+
+  return wasm.connections_to_mask(connections)
+}
+
+/**
+ * Unpack a tile mask into connections, ordered up, right, down, left
+ *
+ * @param mask - Tile mask
+ * @returns Connections encoded in the mask
+ */
+export function maskToConnections(mask: number): ConnectionData[] {
+  const wasm = getWasmModule()
+
+  return wasm.mask_to_connections(mask)
+}
+
+/**
+ * Tile definition (built-in, or installed with setCustomTiles)
+ */
+export interface TileInfo {
+  id: string
+  variant: "curve" | "sharp" | "straight" | "diagonal" | "tee" | "cross"
+  mask: number
+  connections: ConnectionData[]
+  /** Clockwise rotation (degrees) applied to the base sprite */
+  rotation: number
+  base_id: string
+}
+
+/**
+ * List every tile of the active set
+ *
+ * @returns Tile definitions with their masks and connections
+ */
+export function listTiles(): TileInfo[] {
+  const wasm = getWasmModule()
+
+  return wasm.list_tiles()
+}
+
+/**
+ * List the tiles that connect on one side, e.g. for a palette of tiles
+ * connecting upward
+ *
+ * @param direction - Direction name ("up", "right", "up-right", ...)
+ * @returns Tile definitions, or null for an unknown direction
+ */
+export function tilesWithDirection(direction: string): TileInfo[] | null {
+  const wasm = getWasmModule()
+
+  return wasm.tiles_with_direction(direction)
+}
+
+/**
+ * Replace the tile set used by every lookup and tiler
+ *
+ * @param tiles - Tiles in the listTiles shape; rotation and base_id may be
+ *   left out to derive them from the mask
+ * @returns undefined once the set is active, or a message naming the first
+ *   invalid tile (the active set is kept)
+ */
+export function setCustomTiles(
+  tiles: Array<Omit<TileInfo, "rotation" | "base_id"> & Partial<TileInfo>>,
+): string | undefined {
+  const wasm = getWasmModule()
+
+  return wasm.set_custom_tiles(tiles)
+}
+
+/**
+ * Restore the built-in tile set after setCustomTiles
+ */
+export function resetTiles(): void {
+  const wasm = getWasmModule()
+
+  wasm.reset_tiles()
+}
+
+/**
+ * Look up the tile of a variant by its connection mask
+ *
+ * @param variant - Tile variant ("curve", "sharp", "straight", ...)
+ * @param mask - Connection mask as produced by connectionsToMask
+ * @returns The tile definition, or null when none matches
+ */
+export function tileByMask(variant: string, mask: number): TileInfo | null {
+  const wasm = getWasmModule()
+
+  return wasm.tile_by_mask(variant, mask)
+}
+
+/**
+ * Check whether tile B can be placed on the `direction` side of tile A
+ *
+ * @param tileAId - Id of the placed tile
+ * @param tileBId - Id of the tile to place next to it
+ * @param direction - Side of tile A the new tile goes on ("up", "right", ...)
+ * @returns True when both tiles connect across the shared edge with the same ports
+ */
+export function tilesConnect(tileAId: string, tileBId: string, direction: string): boolean {
+  const wasm = getWasmModule()
+
+  return wasm.tiles_connect(tileAId, tileBId, direction)
+}
+
+/**
+ * Ports on each side of a tile, null where the tile has no connection
+ */
+export interface TileConnections {
+  up: PortSet | null
+  right: PortSet | null
+  down: PortSet | null
+  left: PortSet | null
+}
+
+/**
+ * Get a tile's ports on all four sides in one call, e.g. for sprite rendering
+ *
+ * @param tileId - Tile id
+ * @returns The ports per side, or null for an unknown id (including start/goal)
+ */
+export function tileConnections(tileId: string): TileConnections | null {
+  const wasm = getWasmModule()
+
+  return wasm.tile_connections(tileId)
+}
+
+/**
+ * Outcome of parsing a direction or port set name
+ */
+export interface ParsedName {
+  /** The canonical name, or null when it didn't parse */
+  value: string | null
+  /** Why it didn't parse, listing the accepted names */
+  error: string | null
+}
+
+/**
+ * Parse a direction name, e.g. to validate loaded or hand-written grids
+ *
+ * @param name - Direction name ("up", "right", "up-right", ...)
+ * @returns The direction, or an error naming the accepted values
+ */
+export function parseDirection(name: string): ParsedName {
+  const wasm = getWasmModule()
+
+  return wasm.parse_direction(name)
+}
+
+/**
+ * Parse a port set name
+ *
+ * @param name - Port set name ("12", "23" or "34")
+ * @returns The port set, or an error naming the accepted values
+ */
+export function parsePortSet(name: string): ParsedName {
+  const wasm = getWasmModule()
+
+  return wasm.parse_port_set(name)
+}
+
+/**
+ * Get the direction facing the other way, diagonals included
+ *
+ * @param direction - Direction name ("up", "right", "up-right", ...)
+ * @returns The opposite direction, or undefined for an unknown name
+ */
+export function oppositeDirection(direction: string): string | undefined {
+  const wasm = getWasmModule()
+
+  return wasm.opposite_direction(direction)
+}
+
+/**
+ * Get the row and column offset of one step in a direction
+ *
+ * @param direction - Direction name ("up", "right", "up-right", ...)
+ * @returns `[dr, dc]` (e.g. `[-1, 0]` for "up"), or null for an unknown name
+ */
+export function directionDelta(direction: string): [number, number] | null {
+  const wasm = getWasmModule()
+
+  return wasm.direction_delta(direction)
+}
+
+/**
+ * Get the direction of the step between two orthogonally adjacent cells
+ *
+ * @param from - Cell the step leaves
+ * @param to - Cell the step enters
+ * @returns "up", "down", "left" or "right", or undefined when not adjacent
+ */
+export function directionBetween(
+  from: Point,
+  to: Point,
+): "up" | "down" | "left" | "right" | undefined {
+  const wasm = getWasmModule()
+
+  return wasm.direction_between(from.row, from.col, to.row, to.col)
+}
+
+/**
+ * Get the positions along a cell edge of a port set's two lanes
+ *
+ * @param ports - Port set name ("12", "23" or "34")
+ * @returns The two edge positions (e.g. `[10, 20]` for "12"), or null if unknown
+ */
+export function portPositions(ports: string): [number, number] | null {
+  const wasm = getWasmModule()
+
+  return wasm.port_positions(ports)
+}
+
+/**
+ * Tile chosen for a single turn, with the ports on its entry and exit sides
+ */
+export interface TurnTiling {
+  tile_id: string
+  entry_port: PortSet
+  exit_port: PortSet
+}
+
+/**
+ * Resolve which tile `pathToRoadGrid` would place for one turn
+ *
+ * @param entry - Direction of travel into the cell ("up", "right", ...)
+ * @param exit - Direction of travel out of the cell
+ * @param requiredEntryPort - Lane the previous tile exits on (default: any)
+ * @param allowLaneChange - Allow tiles that switch lanes (default: false)
+ * @returns The tile and its ports, or null when no tile makes the turn
+ */
+export function resolveTurn(
+  entry: string,
+  exit: string,
+  requiredEntryPort?: PortSet,
+  allowLaneChange = false,
+): TurnTiling | null {
+  const wasm = getWasmModule()
+
+  return wasm.resolve_turn(entry, exit, requiredEntryPort, allowLaneChange) as TurnTiling | null
+}
+
+/** Mirror or clockwise rotation applied by `transformPath` */
+export type PathTransform = "flip_h" | "flip_v" | "rot90" | "rot180" | "rot270"
+
+/**
+ * Path moved onto a transformed grid
+ */
+export interface TransformedPath {
+  path: Point[]
+  /** Quarter turns swap rows and cols */
+  grid_size: GridSize
+}
+
+/**
+ * Mirror or rotate a path to generate a sibling puzzle
+ *
+ * @param path - Path cells in order
+ * @param gridSize - Grid dimensions
+ * @param transform - Transform to apply
+ * @returns Transformed path and grid size, or null if a point is off the grid
+ */
+export function transformPath(
+  path: Point[],
+  gridSize: GridSize,
+  transform: PathTransform,
+): TransformedPath | null {
+  const wasm = getWasmModule()
+
+  const result = wasm.transform_path(path, gridSize.rows, gridSize.cols, transform)
+
+  return result as TransformedPath | null
+}
+
+/**
+ * Outcome of checking a user-supplied path
+ */
+export interface PathCheck {
+  valid: boolean
+  /** Index of the first point that breaks the path */
+  first_bad_index: number | null
+  reason: string | null
+}
+
+/**
+ * Check that a path is in bounds, orthogonally connected and non-repeating
+ *
+ * @param path - Path cells in order
+ * @param gridSize - Grid dimensions
+ * @returns Check result with the first offending index
+ */
+export function isValidPath(path: Point[], gridSize: GridSize): PathCheck {
+  const wasm = getWasmModule()
+
+  const result = wasm.is_valid_path(path, gridSize.rows, gridSize.cols)
+
+  return result as PathCheck
+}
+
+/**
+ * Check that a path covers the grid exactly once: every cell outside the
+ * obstacles, in bounds, with orthogonally adjacent steps
+ *
+ * Stricter than isValidPath, which accepts partial paths.
+ *
+ * @param path - Path cells in order
+ * @param gridSize - Grid dimensions
+ * @param blocked - Obstacle cells the path must skip (default: none)
+ * @returns True for a complete Hamiltonian path
+ */
+export function isHamiltonianPath(
+  path: Point[],
+  gridSize: GridSize,
+  blocked: Point[] = [],
+): boolean {
+  const wasm = getWasmModule()
+
+  return wasm.is_hamiltonian_path(path, gridSize.rows, gridSize.cols, blocked)
+}
+
+/**
+ * Draw a path as text for logs and snapshot tests
+ *
+ * @param path - Path to draw
+ * @param gridSize - Grid dimensions
+ * @returns Rows joined by newlines ('S' start, 'G' goal, '.' unused cells),
+ *   or undefined when the path is invalid
+ */
+export function renderAscii(path: Point[], gridSize: GridSize): string | undefined {
+  const wasm = getWasmModule()
+
+  return wasm.render_ascii(path, gridSize.rows, gridSize.cols)
+}
+
+/**
+ * Trace a tiled road grid as an SVG `<path>` element for vector export
+ *
+ * The path follows the road centerline with Bézier corners on curve tiles;
+ * it has `fill="none"` and no stroke, so style it in the enclosing SVG.
+ *
+ * @param grid - Grid from `pathToRoadGrid` or `pathsToRoadGrid`
+ * @param gridSize - Grid dimensions
+ * @param cellSize - Width of one cell in SVG units
+ * @returns The `<path>` element, or undefined for a malformed grid
+ */
+export function gridToSvgPath(
+  grid: RoadGridResult["grid"],
+  gridSize: GridSize,
+  cellSize: number,
+): string | undefined {
+  const wasm = getWasmModule()
+
+  return wasm.grid_to_svg_path(grid, gridSize.rows, gridSize.cols, cellSize)
+}
+
+/**
+ * Rotate a whole tiled design by quarter turns
+ *
+ * Unlike `transformPath`, this works on the tiles: cells move, connections
+ * are remapped and each tile becomes its rotated equivalent of the same
+ * variant. Odd turns swap the grid's rows and cols.
+ *
+ * @param grid - Grid from `pathToRoadGrid` or `pathsToRoadGrid`
+ * @param gridSize - Grid dimensions before rotating
+ * @param quarterTurns - Clockwise quarter turns (negative for counterclockwise)
+ * @returns The rotated grid with `valid` from port validation, or null for
+ * invalid dimensions, a malformed grid or a tile without a rotated counterpart
+ */
+export function rotateGrid(
+  grid: RoadGridResult["grid"],
+  gridSize: GridSize,
+  quarterTurns: number,
+): RoadGridResult | null {
+  const wasm = getWasmModule()
+
+  const result = wasm.rotate_grid(grid, gridSize.rows, gridSize.cols, quarterTurns)
+
+  return result as RoadGridResult | null
+}
+
+/**
+ * A neighbor the search would try next
+ */
+export interface Candidate extends Point {
+  direction: string
+  /** Unvisited neighbors of this cell (Warnsdorff degree) */
+  unvisited_neighbors: number
+}
+
+/**
+ * Neighbors of `current` in the exact order the path search tries them
+ *
+ * @param visited - Cells already on the path (and obstacles)
+ * @param current - Cell the path is at
+ * @param gridSize - Grid dimensions
+ * @returns Candidates in search order, or null if `current` is off the grid
+ */
+export function nextCandidates(
+  visited: Point[],
+  current: Point,
+  gridSize: GridSize,
+): Candidate[] | null {
+  const wasm = getWasmModule()
+
+  const result = wasm.next_candidates(
+    visited,
+    current.row,
+    current.col,
+    gridSize.rows,
+    gridSize.cols,
+  )
+
+  return result as Candidate[] | null
+}
+
+/**
+ * Build a snake path over every cell of a rectangular grid without searching
+ *
+ * @param startCorner - A corner of the grid; rows are swept starting along its row
+ * @param gridSize - Grid dimensions
+ * @returns The path in order, or null when the start is not a corner
+ */
+export function snakePath(startCorner: Point, gridSize: GridSize): Point[] | null {
+  const wasm = getWasmModule()
+
+  const result = wasm.snake_path(startCorner.row, startCorner.col, gridSize.rows, gridSize.cols)
+
+  return result as Point[] | null
+}
+
+/**
+ * Build a closed loop over every cell of a rectangular grid without searching
+ *
+ * @param gridSize - Grid dimensions
+ * @returns The loop in order from the top-left corner, or null when the grid
+ *   has no Hamiltonian cycle (a side below 2, or both sides odd)
+ */
+export function snakeCycle(gridSize: GridSize): Point[] | null {
+  const wasm = getWasmModule()
+
+  const result = wasm.snake_cycle(gridSize.rows, gridSize.cols)
+
+  return result as Point[] | null
+}
+
+/**
+ * Convert a closed loop to a road grid
+ *
+ * Every cell gets two connections, the last one joining back to the first;
+ * there are no start/goal markers.
+ *
+ * @param cycle - Loop cells in order, e.g. from findRoadCycle or snakeCycle
+ * @param gridSize - Grid dimensions
+ * @returns The road grid
+ */
+export function cycleToRoadGrid(cycle: Point[], gridSize: GridSize): RoadGridResult {
+  const wasm = getWasmModule()
+
+  const result = wasm.cycle_to_road_grid(cycle, gridSize.rows, gridSize.cols)
+
+  return result as RoadGridResult
+}
+
+/**
+ * Find a closed-loop road covering every cell, passing through `start`
+ *
+ * @param start - Cell the loop passes through; the path starts there
+ * @param gridSize - Grid dimensions
+ * @param maxIterations - Maximum iterations for search (default: 500000)
+ * @returns Path result whose last cell is adjacent to the first; never found
+ *   on grids with an odd number of cells
+ */
+export function findRoadCycle(
+  start: Point,
+  gridSize: GridSize,
+  maxIterations = 500000,
+): PathResult {
+  const wasm = getWasmModule()
+
+  const result = wasm.find_road_cycle(
+    start.row,
+    start.col,
+    gridSize.rows,
+    gridSize.cols,
+    maxIterations,
+  )
+
+  return result as PathResult
+}
+
+/**
+ * A full-cover path ending next to its start
+ */
+export interface NearLoopResult {
+  result: PathResult
+  /** The neighbor of the start the path ends on, or null if none works */
+  end: Point | null
+}
+
+/**
+ * Find a path over every cell that ends next to its start, for circuits
+ *
+ * Each neighbor of the start is tried as the end with one shared budget; the
+ * edge from the end back to the start closes the circuit.
+ *
+ * @param start - Starting point
+ * @param gridSize - Grid dimensions
+ * @param maxIterations - Iteration budget shared by all tried ends (default: 500000)
+ * @returns The path and the end it used
+ */
+export function findNearLoopPath(
+  start: Point,
+  gridSize: GridSize,
+  maxIterations = 500000,
+): NearLoopResult {
+  const wasm = getWasmModule()
+
+  const result = wasm.find_near_loop_path(
+    start.row,
+    start.col,
+    gridSize.rows,
+    gridSize.cols,
+    maxIterations,
+  )
+
+  return result as NearLoopResult
+}
+
+/**
+ * Find a path over every cell of a wrap-around (toroidal) grid
+ *
+ * Stepping off an edge enters the opposite edge, on each side of three or
+ * more cells. Pairs the checkerboard argument rules out on a plain grid are
+ * still rejected when both wrapping sides are even, but not otherwise.
+ *
+ * @param start - Starting point
+ * @param end - Ending point
+ * @param gridSize - Grid dimensions
+ * @param maxIterations - Maximum search iterations (default: 500000)
+ * @returns Path result with the path cells in order
+ */
+export function findWrappedRoadPath(
+  start: Point,
+  end: Point,
+  gridSize: GridSize,
+  maxIterations = 500000,
+): PathResult {
+  const wasm = getWasmModule()
+
+  const result = wasm.find_wrapped_road_path(
+    start.row,
+    start.col,
+    end.row,
+    end.col,
+    gridSize.rows,
+    gridSize.cols,
+    maxIterations,
+  )
+
+  return result as PathResult
+}
+
+/**
+ * Tile a path from findWrappedRoadPath; a step across an edge becomes a
+ * connection off the grid, continued from the opposite edge
+ *
+ * @param path - Path cells in order
+ * @param gridSize - Grid dimensions
+ * @returns The road grid, or null if the path can't be read
+ */
+export function wrappedPathToRoadGrid(path: Point[], gridSize: GridSize): RoadGridResult | null {
+  const wasm = getWasmModule()
+
+  const result = wasm.wrapped_path_to_road_grid(path, gridSize.rows, gridSize.cols)
+
+  return result as RoadGridResult | null
+}
+
+/**
+ * A wide-road route of 2x2 footprints
+ */
+export interface ThickPathResult {
+  /** Search outcome; `path` holds the top-left cell of each footprint */
+  result: PathResult
+  /** The same route on the block grid, one point per footprint */
+  block_path: Point[]
+  /** Half-size grid of 2x2 blocks */
+  block_grid: GridSize
+}
+
+/**
+ * Find a wide-road route whose every step claims a 2x2 footprint
+ *
+ * Needs even grid dimensions and endpoints on the top-left cell of a 2x2
+ * block. Tile `block_path` with `pathToRoadGrid` on `block_grid` and draw it at
+ * double scale.
+ *
+ * @param start - Top-left cell of the first footprint
+ * @param end - Top-left cell of the last footprint
+ * @param gridSize - Grid dimensions in cells
+ * @param maxIterations - Maximum search iterations (default: 500000)
+ * @returns The route in cells and on the block grid
+ */
+export function findThickRoadPath(
+  start: Point,
+  end: Point,
+  gridSize: GridSize,
+  maxIterations = 500000,
+): ThickPathResult {
+  const wasm = getWasmModule()
+
+  const result = wasm.find_thick_road_path(
+    start.row,
+    start.col,
+    end.row,
+    end.col,
+    gridSize.rows,
+    gridSize.cols,
+    maxIterations,
+  )
+
+  return result as ThickPathResult
+}
+
+/** Corner variant: curve, sharp, or alternating by path index */
+export type VariantPreference = "curve" | "sharp" | "alternate"
+
+/**
+ * Options for tiling a path
+ */
+export interface PathToRoadGridOptions {
+  /** Permit tiles that switch between the inner and outer lane (default: false) */
+  allowLaneChange?: boolean
+  /** Which variant to use for corners (default: 'curve') */
+  variantPreference?: VariantPreference
+  /** Port set the start cell exits on; "12" is the inner lane, "34" the third lane of a 3-lane road (default: "23") */
+  startPort?: PortSet
+  /** Nesting of the returned grid, pathToRoadGrid only: grid[row][col] or, for "column_major", grid[col][row] (default: "row_major") */
+  outputOrder?: "row_major" | "column_major"
+  /** Single port set for the whole route, pathToRoadGrid only; a forced lane change fails (default: none) */
+  laneLock?: PortSet
+  /** List on each cell the other tiles with the same connections, pathToRoadGrid only (default: false) */
+  withAlternatives?: boolean
+}
+
+/**
+ * Convert a path to a road grid with tile assignments
+ *
+ * @param path - Array of points representing the path
+ * @param gridSize - Grid dimensions
+ * @param options - Tiling options
+ * @returns Road grid result with tile data
+ */
+export function pathToRoadGrid(
+  path: Point[],
+  gridSize: GridSize,
+  options: PathToRoadGridOptions = {},
+): RoadGridResult {
+  const wasm = getWasmModule()
+
+  const result = wasm.path_to_road_grid(
+    path,
+    gridSize.rows,
+    gridSize.cols,
+    options.allowLaneChange ?? false,
+    options.variantPreference ?? "curve",
+    options.startPort ?? options.laneLock ?? "23",
+    options.outputOrder ?? "row_major",
+    options.laneLock,
+    options.withAlternatives ?? false,
+  )
+
+  return result as RoadGridResult
+}
+
+/**
+ * Cell claimed by two roads
+ */
+export interface RoadConflict {
+  row: number
+  col: number
+  /** Indices of the two roads in the input */
+  road_ids: [number, number]
+}
+
+/**
+ * Several roads tiled onto one grid
+ */
+export interface MultiRoadResult {
+  grid: (CellData | null)[][]
+  valid: boolean
+  /** First cell two roads share; nothing is tiled then */
+  conflict: RoadConflict | null
+  /** Road whose tiling failed */
+  failed_road: number | null
+  failure: TileFailure | null
+}
+
+/**
+ * Tile several roads that must not cross onto one grid
+ *
+ * Each road gets its own start and goal markers and lane propagation; cells
+ * carry the index of their path as `road_id`.
+ *
+ * @param paths - One point array per road
+ * @param gridSize - Grid dimensions
+ * @param options - Tiling options, applied to every road
+ * @returns Combined road grid, or a conflict when two roads share a cell
+ */
+export function pathsToRoadGrid(
+  paths: Point[][],
+  gridSize: GridSize,
+  options: PathToRoadGridOptions = {},
+): MultiRoadResult {
+  const wasm = getWasmModule()
+
+  const result = wasm.paths_to_road_grid(
+    paths,
+    gridSize.rows,
+    gridSize.cols,
+    options.allowLaneChange ?? false,
+    options.variantPreference ?? "curve",
+    options.startPort ?? "23",
+  )
+
+  return result as MultiRoadResult
+}
+
+/**
+ * Road grid after appending a cell
+ */
+export interface AppendCellResult extends RoadGridResult {
+  /** Port set the next appended cell must be entered on */
+  required_entry_port: PortSet | null
+}
+
+/**
+ * Extend a tiled path by one cell without retiling the whole path
+ *
+ * @param grid - Grid returned for `path`
+ * @param path - Path the grid was tiled from
+ * @param point - Cell to append; must be adjacent to the last path cell
+ * @param gridSize - Grid dimensions
+ * @returns Updated grid, or null if the cell cannot be appended
+ */
+export function appendCellToGrid(
+  grid: RoadGridResult["grid"],
+  path: Point[],
+  point: Point,
+  gridSize: GridSize,
+): AppendCellResult | null {
+  const wasm = getWasmModule()
+
+  const result = wasm.append_cell_to_grid(
+    grid,
+    path,
+    point.row,
+    point.col,
+    gridSize.rows,
+    gridSize.cols,
+  )
+
+  return result as AppendCellResult | null
+}
+
+/**
+ * A path with one segment reversed, and its retiled grid
+ */
+export interface SegmentReversal {
+  path: Point[]
+  result: RoadGridResult
+}
+
+/**
+ * Reverse part of a tiled path without retiling the cells before it
+ *
+ * @param grid - Grid returned for `path`
+ * @param path - Path the grid was tiled from
+ * @param fromIndex - First path index of the segment
+ * @param toIndex - Last path index of the segment (inclusive)
+ * @param gridSize - Grid dimensions
+ * @returns The reversed path and its grid, or null if the reversed segment
+ *   no longer joins the rest of the path
+ */
+export function reverseSegment(
+  grid: RoadGridResult["grid"],
+  path: Point[],
+  fromIndex: number,
+  toIndex: number,
+  gridSize: GridSize,
+): SegmentReversal | null {
+  const wasm = getWasmModule()
+
+  const result = wasm.reverse_segment(grid, path, fromIndex, toIndex, gridSize.rows, gridSize.cols)
+
+  return result as SegmentReversal | null
+}
+
+/**
+ * Upgrade a saved road grid result to the current shape
+ *
+ * @param saved - A previously serialized RoadGridResult
+ * @param fromVersion - Its schema_version (default: the saved field, or 0 when missing)
+ * @returns The migrated result, or null when it cannot be read or is newer than this build
+ */
+export function migrateGrid(
+  saved: unknown,
+  fromVersion = (saved as { schema_version?: number } | null)?.schema_version ?? 0,
+): RoadGridResult | null {
+  const wasm = getWasmModule()
+
+  const result = wasm.migrate_grid(saved, fromVersion)
+
+  return result as RoadGridResult | null
+}
+
+/**
+ * Connection whose neighbor does not connect back on the same ports
+ */
+export interface GridMismatch {
+  row: number
+  col: number
+  direction: string
+  kind:
+    | "unknown_direction"
+    | "unknown_ports"
+    | "out_of_bounds"
+    | "missing_neighbor"
+    | "missing_connection"
+    | "port_mismatch"
+}
+
+/**
+ * Check port continuity of a (possibly hand-edited) road grid
+ *
+ * @param grid - Grid of cells as returned in RoadGridResult
+ * @param gridSize - Grid dimensions
+ * @returns One mismatch per broken connection (empty when consistent), or
+ *   null if the grid can't be read
+ */
+export function validateRoadGrid(
+  grid: RoadGridResult["grid"],
+  gridSize: GridSize,
+): GridMismatch[] | null {
+  const wasm = getWasmModule()
+
+  const result = wasm.validate_road_grid(grid, gridSize.rows, gridSize.cols)
+
+  return result as GridMismatch[] | null
+}
+
+/**
+ * Encode a road grid as a compact string for storage
+ *
+ * @param grid - Grid of cells as returned in RoadGridResult
+ * @returns Compact string, or null if the grid cannot be represented
+ */
+export function gridToCompactString(grid: RoadGridResult["grid"]): string | null {
+  const wasm = getWasmModule()
+
+  return wasm.grid_to_compact_string(grid)
+}
+
+/**
+ * One tile to place on a tilemap
+ */
+export interface TilePlacement {
+  row: number
+  col: number
+  tile_id: string
+  /** Clockwise rotation (degrees) applied to the base sprite */
+  rotation: number
+}
+
+/**
+ * Flatten a road grid into tile placements for a tilemap engine (Phaser,
+ * Tiled, ...), skipping empty cells
+ *
+ * @param grid - Grid of cells as returned in RoadGridResult
+ * @param gridSize - Grid dimensions
+ * @returns Placements ordered by path index (then road id), or null for a
+ *   malformed grid
+ */
+export function gridToPlacements(
+  grid: RoadGridResult["grid"],
+  gridSize: GridSize,
+): TilePlacement[] | null {
+  const wasm = getWasmModule()
+
+  return wasm.grid_to_placements(grid, gridSize.rows, gridSize.cols) as TilePlacement[] | null
+}
+
+/**
+ * A cell whose tile changed between two grids
+ */
+export interface CellChange {
+  row: number
+  col: number
+  /** The cell before, or null if it was empty */
+  old_tile: CellData | null
+  /** The cell after, or null if it is now empty */
+  new_tile: CellData | null
+}
+
+/**
+ * List the cells that changed between two road grids, for undo/redo and
+ * sending patches instead of whole grids
+ *
+ * Only the tile id and connections count; connections listed in another
+ * order are the same.
+ *
+ * @param oldGrid - Grid before the edit
+ * @param newGrid - Grid after the edit
+ * @returns Changed cells row by row, or null for a malformed grid
+ */
+export function diffGrids(
+  oldGrid: RoadGridResult["grid"],
+  newGrid: RoadGridResult["grid"],
+): CellChange[] | null {
+  const wasm = getWasmModule()
+
+  return wasm.diff_grids(oldGrid, newGrid) as CellChange[] | null
+}
+
+/**
+ * Parse a string produced by gridToCompactString
+ *
+ * @param compact - Compact grid string
+ * @returns The decoded grid, or null if the string is malformed
+ */
+export function compactStringToGrid(compact: string): RoadGridResult["grid"] | null {
+  const wasm = getWasmModule()
+
+  return wasm.compact_string_to_grid(compact) as RoadGridResult["grid"] | null
+}
+
+/**
+ * One example of each main result type as produced by the Rust side
+ */
+export interface ResultTypeExamples {
+  path_result: PathResult
+  cell_data: CellData
+  road_grid_result: RoadGridResult
+}
+
+/**
+ * Get example result objects to check these interfaces against the Rust
+ * structs, or to feed a type generator
+ *
+ * Every optional field is filled in, so each key of the real payloads is
+ * present; the values are only shape examples.
+ *
+ * @returns Example PathResult, CellData and RoadGridResult
+ */
+export function resultTypeExamples(): ResultTypeExamples {
+  const wasm = getWasmModule()
+
+  return wasm.result_type_examples()
 }
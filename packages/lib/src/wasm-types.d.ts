@@ -5,15 +5,999 @@
  */
 
 declare module "@road-tile/wasm/pkg/road_tile_wasm" {
+  /**
+   * A point on the grid
+   */
+  export interface WasmPoint {
+    row: number
+    col: number
+  }
+
+  export interface WasmReachableCell {
+    row: number
+    col: number
+    distance: number
+  }
+
+  export interface WasmTileInfo {
+    id: string
+    variant: "curve" | "sharp" | "straight" | "diagonal" | "tee" | "cross"
+    mask: number
+    connections: Array<{ direction: string; ports: string }>
+    rotation: 0 | 90 | 180 | 270
+    base_id: string
+  }
+
+  export interface WasmSmoothestPathResult {
+    found: boolean
+    path: WasmPoint[]
+    turns: number
+    iterations: number
+    optimal: boolean
+  }
+
+  export interface WasmPuzzleRating {
+    solvable: boolean
+    stars: number
+    score: number
+    first_solution_iterations: number
+    solution_count: number
+    count_capped: boolean
+  }
+
+  export interface WasmGridInfo {
+    area: number
+    is_even_area: boolean
+    parity_balanced: boolean
+    even_cells: number
+    odd_cells: number
+  }
+
+  export interface WasmSolvableEndpoints {
+    start: WasmPoint
+    end: WasmPoint
+    iterations: number
+  }
+
+  export interface WasmPathStats {
+    length: number
+    turns: number
+    straights: number
+    corners: number
+    longest_straight_run: number
+    self_adjacencies: number
+  }
+
+  /**
+   * Result of path finding
+   */
+  export interface WasmPathResult {
+    found: boolean
+    path: WasmPoint[]
+    iterations: number
+    /** Cells taken back off the path, out of the `iterations` entered */
+    backtracks: number
+    cancelled: boolean
+    timed_out: boolean
+    depth_counts: number[] | null
+    reason: string | null
+    best_partial: WasmPoint[] | null
+    visited_grid: boolean[][] | null
+    input_error: WasmInputError | null
+  }
+
+  /** Search input rejected before searching; `reason` has the message */
+  export interface WasmInputError {
+    kind: "invalid_dimensions" | "out_of_bounds" | "same_endpoints"
+    point: "start" | "end" | null
+    cell: WasmPoint | null
+    grid_size: { rows: number; cols: number }
+  }
+
+  /** Opaque state of a suspended search; only pass it back to `resume_road_path` */
+  export type WasmSearchSnapshot = { readonly __brand: "SearchSnapshot" }
+
+  export interface WasmResumableResult {
+    result: WasmPathResult
+    snapshot: WasmSearchSnapshot | null
+  }
+
+  /** A cell locked to a specific tile */
+  export interface WasmTileLock {
+    row: number
+    col: number
+    tile_id: string
+  }
+
+  export interface WasmLockedPathResult {
+    result: WasmPathResult
+    /** The found path tiled with the locked tiles in place */
+    grid: WasmRoadGridResult | null
+  }
+
+  /**
+   * Cell data for rendering
+   */
+  export interface WasmCellData {
+    tile_id: string
+    connections: Array<{ direction: string; ports: string }>
+    path_index: number
+    /** Clockwise rotation (degrees) to apply to the base sprite */
+    rotation: number
+    /** Direction of travel into the cell (null for the start) */
+    entry_dir: string | null
+    /** Direction of travel out of the cell (null for the goal) */
+    exit_dir: string | null
+    /** Index of the road the cell belongs to (0 for a single road) */
+    road_id: number
+    /** Other tiles with the same connections; filled with `with_alternatives` */
+    alternatives: string[]
+  }
+
+  /**
+   * Road grid result
+   */
+  export interface WasmRoadGridResult {
+    grid: Array<Array<WasmCellData | null>>
+    valid: boolean
+    /** Cell where path tiling found no fitting tile */
+    failure: {
+      path_index: number
+      entry_direction: string
+      exit_direction: string
+      required_entry_port: string | null
+    } | null
+    /** Why tiling stopped, when a constraint such as a lane lock ruled the cell out */
+    reason: string | null
+    /** Shape version of the payload (0 when saved before versioning) */
+    schema_version: number
+  }
+
+  /**
+   * Several roads tiled onto one grid
+   */
+  export interface WasmMultiRoadResult {
+    grid: Array<Array<WasmCellData | null>>
+    valid: boolean
+    /** First cell two roads share */
+    conflict: { row: number; col: number; road_ids: [number, number] } | null
+    /** Road whose tiling failed, with the failure as in WasmRoadGridResult */
+    failed_road: number | null
+    failure: WasmRoadGridResult["failure"]
+  }
+
   /**
    * Get parity of a cell (0 or 1 based on row+col)
    */
-  export function cell_parity(row: number, col: number): number
+  export function cell_parity(row: number, col: number): number
+
+  /**
+   * Find a path from start to end that visits all cells
+   *
+   * `blocked_js` is an optional array of obstacle cells the path skips entirely.
+   * A nonzero `seed` breaks heuristic ties reproducibly; 0 keeps the fixed order.
+   * `strategy` selects the search; an unknown value returns null.
+   * With `target_length`, the path ends after exactly that many cells instead
+   * of covering the whole grid.
+   * `tie_break` orders neighbors that tie under Warnsdorff's rule.
+   * `style` leans the path toward hugging the border ("spiral") or running
+   * along rows ("snake") without changing whether one is found.
+   * `pruning` skips branches whose unvisited cells are split or dead-ended.
+   */
+  export function find_road_path(
+    start_row: number,
+    start_col: number,
+    end_row: number,
+    end_col: number,
+    grid_rows: number,
+    grid_cols: number,
+    max_iterations: number,
+    blocked_js?: WasmPoint[] | null,
+    seed?: number,
+    strategy?: "warnsdorff" | "snake",
+    target_length?: number,
+    tie_break?: "fixed" | "straight" | "turn" | "spacious",
+    memoize_unsolvable?: boolean,
+    style?: "default" | "spiral" | "snake",
+    pruning?: boolean,
+  ): WasmPathResult | null
+
+  /**
+   * Find a path like `find_road_path`, but abortable from JavaScript
+   *
+   * `should_cancel` is polled every 1024 iterations; returning a truthy value
+   * (or throwing) stops the search with `cancelled: true`.
+   */
+  export function find_road_path_cancellable(
+    start_row: number,
+    start_col: number,
+    end_row: number,
+    end_col: number,
+    grid_rows: number,
+    grid_cols: number,
+    max_iterations: number,
+    blocked_js: WasmPoint[] | null | undefined,
+    should_cancel: () => unknown,
+  ): WasmPathResult
+
+  /**
+   * Check if two cells have different parity
+   */
+  export function has_different_parity(r1: number, c1: number, r2: number, c2: number): boolean
+
+  /**
+   * Area and parity class counts of a grid; null for non-positive dimensions
+   * or more than 2^20 cells
+   */
+  export function grid_info(grid_rows: number, grid_cols: number): WasmGridInfo | null
+
+  /**
+   * Initialize the WASM module
+   */
+  export function init(): void
+
+  /**
+   * Convert a path to a road grid with tile assignments
+   *
+   * With `allow_lane_change`, tiles that switch lanes may be used.
+   * `variant_preference` picks curve or sharp corners and `start_port` the port
+   * set the start cell exits on (default "23"; "34" is the third lane of a
+   * 3-lane road). `output_order: "column_major"` nests the grid as
+   * `grid[col][row]`. `lane_lock` keeps the whole route on one port set and
+   * fails with a `reason` where a turn forces a lane change; a different
+   * `start_port` returns null. `with_alternatives` lists on each cell the
+   * other tiles with the same connections. An unknown value for any of them
+   * returns null.
+   */
+  export function path_to_road_grid(
+    path_js: WasmPoint[],
+    grid_rows: number,
+    grid_cols: number,
+    allow_lane_change?: boolean,
+    variant_preference?: "curve" | "sharp" | "alternate",
+    start_port?: "12" | "23" | "34",
+    output_order?: "row_major" | "column_major",
+    lane_lock?: "12" | "23" | "34",
+    with_alternatives?: boolean,
+  ): WasmRoadGridResult | null
+
+  /**
+   * Tile several non-crossing roads onto one grid, tagging each cell with the
+   * index of its path as `road_id`; `conflict` names a cell two roads share
+   */
+  export function paths_to_road_grid(
+    paths_js: WasmPoint[][],
+    grid_rows: number,
+    grid_cols: number,
+    allow_lane_change?: boolean,
+    variant_preference?: "curve" | "sharp" | "alternate",
+    start_port?: "12" | "23" | "34",
+  ): WasmMultiRoadResult | null
+
+  /**
+   * List every tile definition (id, variant, mask and its connections)
+   */
+  export function list_tiles(): WasmTileInfo[]
+
+  /**
+   * Tiles with a connection on the `dir` side; null for an unknown direction
+   */
+  export function tiles_with_direction(dir: string): WasmTileInfo[] | null
+
+  /**
+   * Replace the tile set used by every lookup and tiler. `rotation` and
+   * `base_id` may be left out and are then derived from the mask. Returns
+   * undefined on success, or a message naming the first invalid tile (the
+   * active set is kept).
+   */
+  export function set_custom_tiles(
+    tiles_js: Array<Omit<WasmTileInfo, "rotation" | "base_id"> & Partial<WasmTileInfo>>,
+  ): string | undefined
+
+  /**
+   * Restore the built-in tile set
+   */
+  export function reset_tiles(): void
+
+  /**
+   * One example of each main result type, with every optional field filled
+   * in, for checking or generating these declarations
+   */
+  export function result_type_examples(): {
+    path_result: WasmPathResult
+    cell_data: WasmCellData
+    road_grid_result: WasmRoadGridResult
+  }
+
+  /**
+   * The tile of `variant` whose mask is exactly `mask`, or null
+   */
+  export function tile_by_mask(variant: string, mask: number): WasmTileInfo | null
+
+  /**
+   * Find up to `max_solutions` distinct paths from start to end that visit all cells
+   *
+   * `unique` is true only when the search was exhaustive and found exactly one path.
+   * `pruning` skips branches that can no longer cover every cell.
+   */
+  export function find_road_paths(
+    start_row: number,
+    start_col: number,
+    end_row: number,
+    end_col: number,
+    grid_rows: number,
+    grid_cols: number,
+    max_iterations: number,
+    max_solutions: number,
+    blocked_js?: WasmPoint[] | null,
+    pruning?: boolean,
+  ): {
+    found: boolean
+    paths: WasmPoint[][]
+    unique: boolean
+    iterations: number
+  }
+
+  /**
+   * Check whether a Hamiltonian path between two cells is possible at all
+   * (checkerboard parity argument; necessary but not sufficient)
+   */
+  export function has_hamiltonian_path_possibility(
+    start_row: number,
+    start_col: number,
+    end_row: number,
+    end_col: number,
+    grid_rows: number,
+    grid_cols: number,
+  ): boolean
+
+  /**
+   * Find a path from start to end that visits all cells and passes through
+   * `waypoints_js` in the given order
+   */
+  export function find_road_path_with_waypoints(
+    start_row: number,
+    start_col: number,
+    end_row: number,
+    end_col: number,
+    grid_rows: number,
+    grid_cols: number,
+    max_iterations: number,
+    waypoints_js: WasmPoint[],
+    blocked_js?: WasmPoint[] | null,
+  ): WasmPathResult
+
+  /**
+   * Find a path from start to end that visits all cells and shows the
+   * locked tiles at their cells when tiled with the default options; null
+   * for an unknown tile id or a lock outside the grid
+   */
+  export function find_road_path_with_locked_tiles(
+    start_row: number,
+    start_col: number,
+    end_row: number,
+    end_col: number,
+    grid_rows: number,
+    grid_cols: number,
+    max_iterations: number,
+    locks_js: WasmTileLock[],
+  ): WasmLockedPathResult | null
+
+  /**
+   * Upgrade a saved road grid result from `from_version` (its
+   * `schema_version`, 0 if missing) to the current shape; null when it does
+   * not parse or is newer than this build
+   */
+  export function migrate_grid(json: unknown, from_version: number): WasmRoadGridResult | null
+
+  /**
+   * Check port continuity of a (possibly hand-edited) road grid
+   *
+   * Returns one entry per connection whose neighbor does not connect back on
+   * the same ports; an empty array means the grid is consistent.
+   */
+  export function validate_road_grid(
+    grid_js: Array<Array<WasmCellData | null>>,
+    grid_rows: number,
+    grid_cols: number,
+  ): Array<{
+    row: number
+    col: number
+    direction: string
+    kind:
+      | "unknown_direction"
+      | "unknown_ports"
+      | "out_of_bounds"
+      | "missing_neighbor"
+      | "missing_connection"
+      | "port_mismatch"
+  }>
+
+  /**
+   * Count the Hamiltonian paths between two cells without storing them
+   *
+   * When `capped` is true the iteration budget cut enumeration short and
+   * `count` is a lower bound.
+   */
+  export function count_hamiltonian_paths(
+    start_row: number,
+    start_col: number,
+    end_row: number,
+    end_col: number,
+    grid_rows: number,
+    grid_cols: number,
+    max_iterations: number,
+  ): { count: number; iterations: number; capped: boolean }
+
+  /**
+   * Rate a start/end pair from 1 to 5 stars by search effort, solution count
+   * and grid area; 0 stars when unsolvable, null on invalid input
+   */
+  export function rate_puzzle(
+    start_row: number,
+    start_col: number,
+    end_row: number,
+    end_col: number,
+    grid_rows: number,
+    grid_cols: number,
+    max_iterations: number,
+  ): WasmPuzzleRating | null
+
+  /**
+   * Find a path over a non-rectangular board
+   *
+   * `mask_js` is a flat, row-major boolean array of length `grid_rows * grid_cols`
+   * marking which cells exist. Returns null when the mask has the wrong length.
+   */
+  export function find_road_path_masked(
+    start_row: number,
+    start_col: number,
+    end_row: number,
+    end_col: number,
+    grid_rows: number,
+    grid_cols: number,
+    max_iterations: number,
+    mask_js: boolean[],
+  ): WasmPathResult | null
+
+  /**
+   * Find a path like `find_road_path`, reporting progress to JavaScript
+   *
+   * `on_progress(iterations, depth)` is called every `progress_interval`
+   * iterations (0 picks 1024). Throwing from it stops the search with
+   * `cancelled: true`.
+   */
+  export function find_road_path_with_progress(
+    start_row: number,
+    start_col: number,
+    end_row: number,
+    end_col: number,
+    grid_rows: number,
+    grid_cols: number,
+    max_iterations: number,
+    blocked_js: WasmPoint[] | null | undefined,
+    on_progress: (iterations: number, depth: number) => void,
+    progress_interval: number,
+  ): WasmPathResult
+
+  /**
+   * Find a closed-loop road covering every cell, passing through `start`
+   *
+   * The last cell of `path` is adjacent to the first, closing the circuit.
+   */
+  export function find_road_cycle(
+    start_row: number,
+    start_col: number,
+    grid_rows: number,
+    grid_cols: number,
+    max_iterations: number,
+  ): WasmPathResult
+
+  /**
+   * Find a path covering every cell that ends on a neighbor of `start`,
+   * trying each neighbor in turn; `end` is the one used, or null
+   */
+  export function find_near_loop_path(
+    start_row: number,
+    start_col: number,
+    grid_rows: number,
+    grid_cols: number,
+    max_iterations: number,
+  ): { result: WasmPathResult; end: WasmPoint | null }
+
+  /**
+   * Find a path covering every cell of a wrap-around grid, where stepping
+   * off an edge (of a side of 3 or more) enters the opposite one; endpoint
+   * pairs are parity-rejected only when both wrapping sides are even
+   */
+  export function find_wrapped_road_path(
+    start_row: number,
+    start_col: number,
+    end_row: number,
+    end_col: number,
+    grid_rows: number,
+    grid_cols: number,
+    max_iterations: number,
+  ): WasmPathResult
+
+  /**
+   * Convert a path from `find_wrapped_road_path` to a road grid; steps across
+   * an edge connect off the grid, toward the opposite edge
+   */
+  export function wrapped_path_to_road_grid(
+    path_js: WasmPoint[],
+    grid_rows: number,
+    grid_cols: number,
+  ): WasmRoadGridResult | null
+
+  /**
+   * Find a wide-road route whose every step claims a 2x2 footprint; needs
+   * even dimensions and endpoints on the top-left cell of a block.
+   * `result.path` lists each footprint's top-left cell, `block_path` the
+   * same route on the half-size `block_grid`
+   */
+  export function find_thick_road_path(
+    start_row: number,
+    start_col: number,
+    end_row: number,
+    end_col: number,
+    grid_rows: number,
+    grid_cols: number,
+    max_iterations: number,
+  ): {
+    result: WasmPathResult
+    block_path: WasmPoint[]
+    block_grid: { rows: number; cols: number }
+  }
+
+  /**
+   * Snake path over every cell of a rectangle from a corner, built without
+   * searching; null when the start is not a corner
+   */
+  export function snake_path(
+    start_row: number,
+    start_col: number,
+    grid_rows: number,
+    grid_cols: number,
+  ): WasmPoint[] | null
+
+  /**
+   * Closed loop over every cell of a rectangle, built without searching;
+   * null unless both sides are at least 2 and one is even
+   */
+  export function snake_cycle(grid_rows: number, grid_cols: number): WasmPoint[] | null
+
+  /**
+   * Convert a closed loop to a road grid (two connections per cell, no markers)
+   */
+  export function cycle_to_road_grid(
+    path_js: WasmPoint[],
+    grid_rows: number,
+    grid_cols: number,
+  ): WasmRoadGridResult
+
+  /**
+   * Whether tile B can sit on the `direction` side of tile A with matching
+   * ports on the shared edge; false for unknown ids or directions
+   */
+  export function tiles_connect(tile_a_id: string, tile_b_id: string, direction: string): boolean
+
+  /**
+   * Port set on each of a tile's four sides, null where it has no
+   * connection; null for unknown ids
+   */
+  export function tile_connections(tile_id: string): {
+    up: "12" | "23" | "34" | null
+    right: "12" | "23" | "34" | null
+    down: "12" | "23" | "34" | null
+    left: "12" | "23" | "34" | null
+  } | null
+
+  /**
+   * The direction facing the other way ("up-right" → "down-left"); undefined
+   * for an unknown name
+   */
+  export function opposite_direction(direction: string): string | undefined
+
+  /**
+   * Parse a direction name: `value` on success, otherwise an `error` listing
+   * the accepted names
+   */
+  export function parse_direction(name: string): { value: string | null; error: string | null }
+
+  /**
+   * Parse a port set name ("12", "23", "34"), reporting like `parse_direction`
+   */
+  export function parse_port_set(name: string): { value: string | null; error: string | null }
+
+  /**
+   * One step in `direction` as `[dr, dc]`; null for an unknown name
+   */
+  export function direction_delta(direction: string): [number, number] | null
+
+  /**
+   * Tile and ports `path_to_road_grid` picks for a cell entered travelling
+   * `entry` and left travelling `exit`; null for unknown names or no tile
+   */
+  export function resolve_turn(
+    entry: string,
+    exit: string,
+    required_entry_port?: string | null,
+    allow_lane_change?: boolean | null,
+  ): { tile_id: string; entry_port: string; exit_port: string } | null
+
+  /**
+   * Direction name of the step between two cells, or undefined when they are
+   * not orthogonally adjacent
+   */
+  export function direction_between(
+    r1: number,
+    c1: number,
+    r2: number,
+    c2: number,
+  ): "up" | "down" | "left" | "right" | undefined
+
+  /**
+   * Branch-and-bound search for the full-cover path with the fewest turns;
+   * `optimal` is false when the budget ran out before the search finished
+   */
+  export function find_smoothest_road_path(
+    start_row: number,
+    start_col: number,
+    end_row: number,
+    end_col: number,
+    grid_rows: number,
+    grid_cols: number,
+    max_iterations: number,
+    blocked_js?: WasmPoint[] | null,
+  ): WasmSmoothestPathResult | null
+
+  /**
+   * Find paths for many `{ start, end }` pairs on one grid in a single call.
+   * Results are returned in input order; each search gets its own budget.
+   * With `memoize_unsolvable`, pairs proven unsolvable earlier in the session
+   * return at once (see `clear_solvability_cache`).
+   */
+  export function find_road_paths_batch(
+    pairs_js: Array<{ start: WasmPoint; end: WasmPoint }>,
+    grid_rows: number,
+    grid_cols: number,
+    max_iterations: number,
+    memoize_unsolvable?: boolean,
+  ): WasmPathResult[] | null
+
+  /** Forget the endpoint pairs memoized as unsolvable */
+  export function clear_solvability_cache(): void
+
+  /**
+   * List every unordered endpoint pair that admits a Hamiltonian path, with
+   * the iterations the search took; pairs exhausting the budget are omitted.
+   */
+  export function solvable_endpoints(
+    grid_rows: number,
+    grid_cols: number,
+    max_iterations: number,
+  ): WasmSolvableEndpoints[] | null
+
+  /**
+   * Encode a road grid as a compact string ('/' between rows, '|' between
+   * cells, `*n` for n empty cells); null if the grid cannot be represented
+   */
+  export function grid_to_compact_string(grid_js: Array<Array<WasmCellData | null>>): string | null
+
+  /**
+   * Occupied cells of a road grid as tile placements, in path order; null
+   * for a malformed grid or a cell outside it
+   */
+  export function grid_to_placements(
+    grid_js: Array<Array<WasmCellData | null>>,
+    grid_rows: number,
+    grid_cols: number,
+  ): Array<{ row: number; col: number; tile_id: string; rotation: number }> | null
+
+  /**
+   * Cells whose tile id or connections differ between two grids, null for an
+   * empty cell; null when either grid is malformed
+   */
+  export function diff_grids(
+    old_js: Array<Array<WasmCellData | null>>,
+    new_js: Array<Array<WasmCellData | null>>,
+  ): Array<{
+    row: number
+    col: number
+    old_tile: WasmCellData | null
+    new_tile: WasmCellData | null
+  }> | null
+
+  /**
+   * Parse a string from `grid_to_compact_string`; null when malformed
+   */
+  export function compact_string_to_grid(compact: string): Array<Array<WasmCellData | null>> | null
+
+  /**
+   * Append one cell to a grid tiled by `path_to_road_grid`, retiling only the
+   * old goal and the new cell. Returns null when the cell is not adjacent to
+   * the last path cell, out of bounds or already on the path.
+   */
+  export function append_cell_to_grid(
+    prev_grid_js: Array<Array<WasmCellData | null>>,
+    prev_path_js: WasmPoint[],
+    new_row: number,
+    new_col: number,
+    grid_rows: number,
+    grid_cols: number,
+  ): (WasmRoadGridResult & { required_entry_port: string | null }) | null
+
+  /**
+   * Reverse `path[from_index..=to_index]` in a grid tiled by
+   * `path_to_road_grid`, retiling only from the cell before the segment on.
+   * Null when the indices are out of range, the reversed segment no longer
+   * joins its neighbors or the grid lacks the path's tiles.
+   */
+  export function reverse_segment(
+    grid_js: Array<Array<WasmCellData | null>>,
+    path_js: WasmPoint[],
+    from_index: number,
+    to_index: number,
+    grid_rows: number,
+    grid_cols: number,
+  ): { path: WasmPoint[]; result: WasmRoadGridResult } | null
+
+  /**
+   * Estimate how hard a path search will be without running it.
+   * `score` is a rough log10 of the iterations a plain search needs.
+   */
+  export function estimate_search_difficulty(
+    start_row: number,
+    start_col: number,
+    end_row: number,
+    end_col: number,
+    grid_rows: number,
+    grid_cols: number,
+  ): {
+    feasible: boolean
+    score: number
+    suggested_max_iterations: number
+  }
+
+  /**
+   * Time one corner-to-corner search per `{ rows, cols }` size with
+   * `performance.now()`; null when the sizes can't be parsed
+   */
+  export function benchmark(
+    sizes_js: Array<{ rows: number; cols: number }>,
+    max_iterations: number,
+  ): Array<{
+    rows: number
+    cols: number
+    end: WasmPoint
+    found: boolean
+    iterations: number
+    elapsed_ms: number
+  }> | null
+
+  /**
+   * Found fraction and iteration distribution over a batch of path results,
+   * with one histogram bucket per decade of iterations; null when the
+   * results can't be parsed
+   */
+  export function summarize_results(results_js: WasmPathResult[]): {
+    count: number
+    found: number
+    found_fraction: number
+    mean_iterations: number
+    median_iterations: number
+    histogram: Array<{ name: string; min: number; max: number | null; count: number }>
+  } | null
+
+  /**
+   * Find a path like `find_road_path`, stopping with `timed_out: true` once
+   * `time_budget_ms` of wall-clock time has passed (checked via
+   * `performance.now()`; without it only `max_iterations` applies)
+   */
+  export function find_road_path_timed(
+    start_row: number,
+    start_col: number,
+    end_row: number,
+    end_col: number,
+    grid_rows: number,
+    grid_cols: number,
+    max_iterations: number,
+    blocked_js: WasmPoint[] | null | undefined,
+    time_budget_ms: number,
+  ): WasmPathResult | null
+
+  /**
+   * Find a path like `find_road_path`, filling `depth_counts[d]` with the
+   * number of nodes expanded at path length `d + 1`; with `debug`, a failed
+   * search also returns `visited_grid` at its deepest point
+   */
+  export function find_road_path_instrumented(
+    start_row: number,
+    start_col: number,
+    end_row: number,
+    end_col: number,
+    grid_rows: number,
+    grid_cols: number,
+    max_iterations: number,
+    blocked_js?: WasmPoint[] | null,
+    seed?: number,
+    pruning?: boolean,
+    debug?: boolean,
+  ): WasmPathResult | null
+
+  /**
+   * Edge positions of a port set, e.g. "12" -> [10, 20]; null if unknown
+   */
+  export function port_positions(ports: string): [number, number] | null
+
+  /**
+   * Cells within `steps` moves of the start, with their BFS distance
+   * (blocked cells are skipped; the full-cover constraint is ignored)
+   */
+  export function reachable_cells(
+    start_row: number,
+    start_col: number,
+    grid_rows: number,
+    grid_cols: number,
+    steps: number,
+    blocked_js: WasmPoint[] | null | undefined,
+  ): WasmReachableCell[] | null
+
+  /**
+   * Fewest-steps orthogonal route from start to end around blocked cells,
+   * ignoring the full-cover constraint; null for invalid grid dimensions
+   */
+  export function shortest_path(
+    start_row: number,
+    start_col: number,
+    end_row: number,
+    end_col: number,
+    grid_rows: number,
+    grid_cols: number,
+    blocked_js: WasmPoint[] | null | undefined,
+  ): { found: boolean; path: WasmPoint[]; length: number } | null
+
+  /**
+   * Articulation cells of the free cells (`pieces` they would split into)
+   * and whether the board alone rules out a full-cover path; null for
+   * invalid grid dimensions
+   */
+  export function cut_cells(
+    grid_rows: number,
+    grid_cols: number,
+    blocked_js: WasmPoint[] | null | undefined,
+  ): {
+    components: number
+    cut_cells: Array<{ row: number; col: number; pieces: number }>
+    ruled_out: boolean
+  } | null
+
+  /**
+   * Cells neither on a partial path nor blocked, and whether all of them are
+   * still reachable from its last cell; null for an invalid path
+   */
+  export function remaining_cells(
+    path_js: WasmPoint[],
+    grid_rows: number,
+    grid_cols: number,
+    blocked_js: WasmPoint[] | null | undefined,
+  ): { cells: WasmPoint[]; all_reachable: boolean } | null
+
+  /**
+   * Canonical `{ start, end }` of a pair under the grid's rotations and
+   * reflections (only 180° and axis mirrors for non-square grids), for
+   * deduplicating equivalent puzzles; null if an endpoint is off the grid
+   */
+  export function canonical_endpoints(
+    start_row: number,
+    start_col: number,
+    end_row: number,
+    end_col: number,
+    grid_rows: number,
+    grid_cols: number,
+  ): { start: WasmPoint; end: WasmPoint } | null
+
+  /**
+   * Shape metrics of a path; null if the path has non-adjacent steps
+   */
+  export function path_stats(path_js: WasmPoint[]): WasmPathStats | null
+
+  /**
+   * Reverse and splice stretches of a path where that removes turns, keeping
+   * its cells and endpoints; null for non-adjacent steps or a repeated cell
+   */
+  export function simplify_path(path_js: WasmPoint[]): {
+    path: WasmPoint[]
+    turns_before: number
+    turns_after: number
+    swaps: number
+  } | null
+
+  /**
+   * Find a path and flag each step taken from a cell with only one unvisited
+   * neighbor; `forced_moves` counts the flags
+   */
+  export function forced_move_count(
+    start_row: number,
+    start_col: number,
+    end_row: number,
+    end_col: number,
+    grid_rows: number,
+    grid_cols: number,
+    max_iterations: number,
+  ): { result: WasmPathResult; forced: boolean[]; forced_moves: number }
+
+  /**
+   * Find a path like `find_road_path`, trying lower-weight cells first among
+   * Warnsdorff ties (`weights_js[row][col]`); a soft preference only
+   */
+  export function find_road_path_weighted(
+    start_row: number,
+    start_col: number,
+    end_row: number,
+    end_col: number,
+    grid_rows: number,
+    grid_cols: number,
+    max_iterations: number,
+    weights_js: number[][],
+    blocked_js: WasmPoint[] | null | undefined,
+  ): WasmPathResult | null
+
+  /**
+   * Pack connections into the tile `mask` format; null for unknown names
+   */
+  export function connections_to_mask(connections_js: Array<{ direction: string; ports: string }>): number | null
+
+  /**
+   * Unpack a tile mask into connections, ordered up, right, down, left
+   */
+  export function mask_to_connections(mask: number): Array<{ direction: string; ports: string }>
+
+  /**
+   * Mirror ("flip_h", "flip_v") or rotate ("rot90", "rot180", "rot270") a
+   * path; quarter turns swap rows and cols. Null for an unknown transform or
+   * a point off the grid
+   */
+  export function transform_path(
+    path_js: WasmPoint[],
+    grid_rows: number,
+    grid_cols: number,
+    transform: string,
+  ): { path: WasmPoint[]; grid_size: { rows: number; cols: number } } | null
+
+  /**
+   * Find a path like `find_road_path`, also allowing non-crossing diagonal
+   * steps (tiled with the `diagonal-*` tiles); no parity pre-check
+   */
+  export function find_road_path_diagonal(
+    start_row: number,
+    start_col: number,
+    end_row: number,
+    end_col: number,
+    grid_rows: number,
+    grid_cols: number,
+    max_iterations: number,
+    blocked_js: WasmPoint[] | null | undefined,
+  ): WasmPathResult | null
+
+  /**
+   * Find a path covering every cell from the start, ending on whichever cell
+   * comes last; only the start is parity-checked
+   */
+  export function find_road_path_open_end(
+    start_row: number,
+    start_col: number,
+    grid_rows: number,
+    grid_cols: number,
+    max_iterations: number,
+  ): WasmPathResult | null
 
   /**
-   * Find a path from start to end that visits all cells
+   * Start a path search that can be continued with `resume_road_path`;
+   * `snapshot` is set (and opaque) when `max_iterations` ran out first
    */
-  export function find_road_path(
+  export function find_road_path_resumable(
     start_row: number,
     start_col: number,
     end_row: number,
@@ -21,39 +1005,84 @@ declare module "@road-tile/wasm/pkg/road_tile_wasm" {
     grid_rows: number,
     grid_cols: number,
     max_iterations: number,
-  ): {
-    found: boolean
-    path: Array<{ row: number; col: number }>
-    iterations: number
-  }
+    blocked_js: WasmPoint[] | null | undefined,
+  ): WasmResumableResult | null
 
   /**
-   * Check if two cells have different parity
+   * Continue a suspended search with `extra_iterations` more budget; null for
+   * a malformed snapshot
    */
-  export function has_different_parity(r1: number, c1: number, r2: number, c2: number): boolean
+  export function resume_road_path(
+    snapshot_js: WasmSearchSnapshot,
+    extra_iterations: number,
+  ): WasmResumableResult | null
 
   /**
-   * Initialize the WASM module
+   * Rotate a tiled grid `quarter_turns` times clockwise, swapping each tile
+   * for its rotated equivalent; rows and cols swap for odd turns. Null for
+   * invalid dimensions, a malformed grid or a tile without a rotated
+   * counterpart
    */
-  export function init(): void
+  export function rotate_grid(
+    grid_js: Array<Array<WasmCellData | null>>,
+    grid_rows: number,
+    grid_cols: number,
+    quarter_turns: number,
+  ): WasmRoadGridResult | null
 
   /**
-   * Convert a path to a road grid with tile assignments
+   * Trace a tiled grid's road centerline (one subpath per road) as an SVG
+   * `<path fill="none">` element; undefined for a malformed grid
    */
-  export function path_to_road_grid(
-    path_js: Array<{ row: number; col: number }>,
+  export function grid_to_svg_path(
+    grid_js: Array<Array<WasmCellData | null>>,
     grid_rows: number,
     grid_cols: number,
-  ): {
-    grid: Array<
-      Array<{
-        tile_id: string
-        connections: Array<{ direction: string; ports: string }>
-        path_index: number
-      } | null>
-    >
-    valid: boolean
-  }
+    cell_size: number,
+  ): string | undefined
+
+  /**
+   * Draw a path as text, one line per row: 'S' start, 'G' goal, box-drawing
+   * road pieces and '.' for other cells; undefined for an invalid path
+   */
+  export function render_ascii(
+    path_js: WasmPoint[],
+    grid_rows: number,
+    grid_cols: number,
+  ): string | undefined
+
+  /**
+   * Check that a path stays in the grid, moves between orthogonally adjacent
+   * cells and never repeats a cell
+   */
+  export function is_valid_path(
+    path_js: WasmPoint[],
+    grid_rows: number,
+    grid_cols: number,
+  ): { valid: boolean; first_bad_index: number | null; reason: string | null } | null
+
+  /**
+   * Whether a path covers every cell outside `blocked_js` exactly once with
+   * adjacent steps; false for unparsable input
+   */
+  export function is_hamiltonian_path(
+    path_js: WasmPoint[],
+    grid_rows: number,
+    grid_cols: number,
+    blocked_js?: WasmPoint[] | null,
+  ): boolean
+
+  /**
+   * Neighbors of the current cell in the order the search would try them,
+   * with their unvisited-neighbor counts; null if `current` is off the grid
+   */
+  export function next_candidates(
+    visited_js: WasmPoint[] | null | undefined,
+    current_row: number,
+    current_col: number,
+    grid_rows: number,
+    grid_cols: number,
+  ): Array<{ row: number; col: number; direction: string; unvisited_neighbors: number }> | null
 
   export type InitInput = RequestInfo | URL | Response | BufferSource | WebAssembly.Module
 
@@ -67,10 +1096,345 @@ declare module "@road-tile/wasm/pkg/road_tile_wasm" {
       e: number,
       f: number,
       g: number,
+      h: unknown,
+      i: number,
+      j: number,
+      k: number,
+      l: number,
+      m: number,
+      n: number,
+      o: number,
+      p: number,
+      q: number,
+    ) => unknown
+    readonly find_road_path_cancellable: (
+      a: number,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+      f: number,
+      g: number,
+      h: unknown,
+      i: unknown,
+    ) => unknown
+    readonly path_to_road_grid: (
+      a: unknown,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+      f: number,
+      g: number,
+      h: number,
+      i: number,
+      j: number,
+      k: number,
+      l: number,
+      m: number,
+    ) => unknown
+    readonly paths_to_road_grid: (
+      a: unknown,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+      f: number,
+      g: number,
+      h: number,
     ) => unknown
-    readonly path_to_road_grid: (a: unknown, b: number, c: number) => unknown
     readonly cell_parity: (a: number, b: number) => number
     readonly has_different_parity: (a: number, b: number, c: number, d: number) => number
+    readonly grid_info: (a: number, b: number) => unknown
+    readonly list_tiles: () => unknown
+    readonly tiles_with_direction: (a: number, b: number) => unknown
+    readonly set_custom_tiles: (a: unknown) => unknown
+    readonly reset_tiles: () => void
+    readonly result_type_examples: () => unknown
+    readonly tile_by_mask: (a: number, b: number, c: number) => unknown
+    readonly find_road_paths: (
+      a: number,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+      f: number,
+      g: number,
+      h: number,
+      i: unknown,
+      j: number,
+    ) => unknown
+    readonly has_hamiltonian_path_possibility: (
+      a: number,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+      f: number,
+    ) => number
+    readonly find_road_path_with_waypoints: (
+      a: number,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+      f: number,
+      g: number,
+      h: unknown,
+      i: unknown,
+    ) => unknown
+    readonly find_road_path_with_locked_tiles: (
+      a: number,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+      f: number,
+      g: number,
+      h: unknown,
+    ) => unknown
+    readonly migrate_grid: (a: unknown, b: number) => unknown
+    readonly validate_road_grid: (a: unknown, b: number, c: number) => unknown
+    readonly count_hamiltonian_paths: (
+      a: number,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+      f: number,
+      g: number,
+    ) => unknown
+    readonly rate_puzzle: (
+      a: number,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+      f: number,
+      g: number,
+    ) => unknown
+    readonly find_road_path_masked: (
+      a: number,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+      f: number,
+      g: number,
+      h: unknown,
+    ) => unknown
+    readonly find_road_path_with_progress: (
+      a: number,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+      f: number,
+      g: number,
+      h: unknown,
+      i: unknown,
+      j: number,
+    ) => unknown
+    readonly find_road_cycle: (a: number, b: number, c: number, d: number, e: number) => unknown
+    readonly find_near_loop_path: (a: number, b: number, c: number, d: number, e: number) => unknown
+    readonly find_wrapped_road_path: (
+      a: number,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+      f: number,
+      g: number,
+    ) => unknown
+    readonly wrapped_path_to_road_grid: (a: unknown, b: number, c: number) => unknown
+    readonly find_thick_road_path: (
+      a: number,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+      f: number,
+      g: number,
+    ) => unknown
+    readonly snake_path: (a: number, b: number, c: number, d: number) => unknown
+    readonly snake_cycle: (a: number, b: number) => unknown
+    readonly cycle_to_road_grid: (a: unknown, b: number, c: number) => unknown
+    readonly direction_between: (a: number, b: number, c: number, d: number) => unknown
+    readonly tiles_connect: (a: number, b: number, c: number, d: number, e: number, f: number) => number
+    readonly tile_connections: (a: number, b: number) => unknown
+    readonly opposite_direction: (a: number, b: number) => unknown
+    readonly parse_direction: (a: number, b: number) => unknown
+    readonly parse_port_set: (a: number, b: number) => unknown
+    readonly direction_delta: (a: number, b: number) => unknown
+    readonly resolve_turn: (
+      a: number,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+      f: number,
+      g: number,
+    ) => unknown
+    readonly find_road_paths_batch: (
+      a: unknown,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+    ) => unknown
+    readonly clear_solvability_cache: () => void
+    readonly find_smoothest_road_path: (
+      a: number,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+      f: number,
+      g: number,
+      h: unknown,
+    ) => unknown
+    readonly solvable_endpoints: (a: number, b: number, c: number) => unknown
+    readonly grid_to_compact_string: (a: unknown) => unknown
+    readonly grid_to_placements: (a: unknown, b: number, c: number) => unknown
+    readonly diff_grids: (a: unknown, b: unknown) => unknown
+    readonly compact_string_to_grid: (a: number, b: number) => unknown
+    readonly append_cell_to_grid: (
+      a: unknown,
+      b: unknown,
+      c: number,
+      d: number,
+      e: number,
+      f: number,
+    ) => unknown
+    readonly reverse_segment: (
+      a: unknown,
+      b: unknown,
+      c: number,
+      d: number,
+      e: number,
+      f: number,
+    ) => unknown
+    readonly estimate_search_difficulty: (
+      a: number,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+      f: number,
+    ) => unknown
+    readonly benchmark: (a: unknown, b: number) => unknown
+    readonly summarize_results: (a: unknown) => unknown
+    readonly find_road_path_timed: (
+      a: number,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+      f: number,
+      g: number,
+      h: unknown,
+      i: number,
+    ) => unknown
+    readonly find_road_path_instrumented: (
+      a: number,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+      f: number,
+      g: number,
+      h: unknown,
+      i: number,
+      j: number,
+      k: number,
+    ) => unknown
+    readonly port_positions: (a: number, b: number) => unknown
+    readonly reachable_cells: (
+      a: number,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+      f: unknown,
+    ) => unknown
+    readonly shortest_path: (
+      a: number,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+      f: number,
+      g: unknown,
+    ) => unknown
+    readonly cut_cells: (a: number, b: number, c: unknown) => unknown
+    readonly remaining_cells: (a: unknown, b: number, c: number, d: unknown) => unknown
+    readonly canonical_endpoints: (
+      a: number,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+      f: number,
+    ) => unknown
+    readonly path_stats: (a: unknown) => unknown
+    readonly simplify_path: (a: unknown) => unknown
+    readonly forced_move_count: (
+      a: number,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+      f: number,
+      g: number,
+    ) => unknown
+    readonly find_road_path_weighted: (
+      a: number,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+      f: number,
+      g: number,
+      h: unknown,
+      i: unknown,
+    ) => unknown
+    readonly connections_to_mask: (a: unknown) => unknown
+    readonly mask_to_connections: (a: number) => unknown
+    readonly transform_path: (a: unknown, b: number, c: number, d: number, e: number) => unknown
+    readonly find_road_path_diagonal: (
+      a: number,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+      f: number,
+      g: number,
+      h: unknown,
+    ) => unknown
+    readonly find_road_path_open_end: (
+      a: number,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+    ) => unknown
+    readonly find_road_path_resumable: (
+      a: number,
+      b: number,
+      c: number,
+      d: number,
+      e: number,
+      f: number,
+      g: number,
+      h: unknown,
+    ) => unknown
+    readonly resume_road_path: (a: unknown, b: number) => unknown
+    readonly is_valid_path: (a: unknown, b: number, c: number) => unknown
+    readonly is_hamiltonian_path: (a: unknown, b: number, c: number, d: unknown) => number
+    readonly render_ascii: (a: unknown, b: number, c: number) => unknown
+    readonly grid_to_svg_path: (a: unknown, b: number, c: number, d: number) => unknown
+    readonly rotate_grid: (a: unknown, b: number, c: number, d: number) => unknown
+    readonly next_candidates: (a: unknown, b: number, c: number, d: number, e: number) => unknown
     readonly init: () => void
   }
 